@@ -0,0 +1,417 @@
+//! Finitely generated abelian groups, classified by the Smith normal form of
+//! their relations.
+//!
+//! Every such group is `Z^r × Z/d1 × ... × Z/dk` with each `di` dividing the
+//! next, the invariant factor form. Splitting each `Z/di` into prime powers
+//! gives the elementary divisor form instead.
+
+use std::fmt;
+
+use super::group::PermGroup;
+use super::perm::Permutation;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbelianError {
+    /// A relation doesn't have one coefficient per generator
+    WrongLength { row: usize, expected: usize },
+}
+
+impl fmt::Display for AbelianError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongLength { row, expected } => {
+                write!(f, "relation {} should have {} coefficients", row, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AbelianError {}
+
+pub type Matrix = Vec<Vec<i64>>;
+
+/// `left * matrix * right == diagonal` with `left` and `right` invertible over
+/// the integers. The diagonal entries are non-negative, each dividing the next,
+/// with the zeros last.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmithNormalForm {
+    pub diagonal: Vec<i64>,
+    pub left: Matrix,
+    pub right: Matrix,
+}
+
+/// Reduces an `m × n` matrix by integer row and column operations. Entries are
+/// expected to stay well within `i64`, which holds for the small presentations
+/// typed in by hand.
+pub fn smith_normal_form(matrix: &[Vec<i64>]) -> SmithNormalForm {
+    let rows = matrix.len();
+    let columns = matrix.first().map_or(0, Vec::len);
+    let mut a: Matrix = matrix.to_vec();
+    let mut left = identity(rows);
+    let mut right = identity(columns);
+
+    for t in 0..rows.min(columns) {
+        // Pivot on the smallest entry left, so every step shrinks it
+        while let Some((i, j)) = (t..rows)
+            .flat_map(|i| (t..columns).map(move |j| (i, j)))
+            .filter(|&(i, j)| a[i][j] != 0)
+            .min_by_key(|&(i, j)| a[i][j].abs())
+        {
+            a.swap(t, i);
+            left.swap(t, i);
+            swap_columns(&mut a, t, j);
+            swap_columns(&mut right, t, j);
+
+            let pivot = a[t][t];
+            let mut cleared = true;
+            for i in t + 1..rows {
+                let q = a[i][t] / pivot;
+                add_row(&mut a, i, t, -q);
+                add_row(&mut left, i, t, -q);
+                cleared &= a[i][t] == 0;
+            }
+            for j in t + 1..columns {
+                let q = a[t][j] / pivot;
+                add_column(&mut a, j, t, -q);
+                add_column(&mut right, j, t, -q);
+                cleared &= a[t][j] == 0;
+            }
+            if !cleared {
+                continue;
+            }
+            // The pivot has to divide everything below and right of it, adding a
+            // row it doesn't divide leaves a smaller remainder to pivot on next
+            let undivided = (t + 1..rows).find(|&i| (t + 1..columns).any(|j| a[i][j] % pivot != 0));
+            match undivided {
+                Some(i) => {
+                    add_row(&mut a, t, i, 1);
+                    add_row(&mut left, t, i, 1);
+                }
+                None => break,
+            }
+        }
+        if a[t][t] < 0 {
+            a[t].iter_mut().for_each(|x| *x = -*x);
+            left[t].iter_mut().for_each(|x| *x = -*x);
+        }
+    }
+
+    SmithNormalForm {
+        diagonal: (0..rows.min(columns)).map(|i| a[i][i]).collect(),
+        left,
+        right,
+    }
+}
+
+fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| i64::from(i == j)).collect())
+        .collect()
+}
+
+fn swap_columns(matrix: &mut Matrix, a: usize, b: usize) {
+    matrix.iter_mut().for_each(|row| row.swap(a, b));
+}
+
+/// `row[to] += factor * row[from]`
+fn add_row(matrix: &mut Matrix, to: usize, from: usize, factor: i64) {
+    for j in 0..matrix[to].len() {
+        matrix[to][j] += factor * matrix[from][j];
+    }
+}
+
+/// `column[to] += factor * column[from]`
+fn add_column(matrix: &mut Matrix, to: usize, from: usize, factor: i64) {
+    for row in matrix.iter_mut() {
+        row[to] += factor * row[from];
+    }
+}
+
+/// `Z^rank × Z/d1 × ... × Z/dk` with `1 < d1 | d2 | ... | dk`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AbelianGroup {
+    rank: usize,
+    invariant_factors: Vec<u64>,
+}
+
+impl AbelianGroup {
+    pub fn trivial() -> Self {
+        Self {
+            rank: 0,
+            invariant_factors: Vec::new(),
+        }
+    }
+
+    /// `Z/n`, or `Z` when `n` is 0
+    pub fn cyclic(n: u64) -> Self {
+        Self::product(&[n])
+    }
+
+    /// The direct product of cyclic groups of the given orders, 0 standing for `Z`
+    pub fn product(orders: &[u64]) -> Self {
+        let diagonal: Matrix = (0..orders.len())
+            .map(|i| {
+                (0..orders.len())
+                    .map(|j| if i == j { orders[i] as i64 } else { 0 })
+                    .collect()
+            })
+            .collect();
+        Self::from_relation_matrix(orders.len(), &diagonal)
+    }
+
+    /// The group with `generators` generators subject to one relation per row:
+    /// `row[0] g0 + row[1] g1 + ... = 0`
+    pub fn from_relations(generators: usize, relations: &[Vec<i64>]) -> Result<Self, AbelianError> {
+        if let Some(row) = relations.iter().position(|row| row.len() != generators) {
+            return Err(AbelianError::WrongLength {
+                row,
+                expected: generators,
+            });
+        }
+        Ok(Self::from_relation_matrix(generators, relations))
+    }
+
+    fn from_relation_matrix(generators: usize, relations: &[Vec<i64>]) -> Self {
+        let diagonal = smith_normal_form(relations).diagonal;
+        // Generators beyond the diagonal are unconstrained
+        let zeros = diagonal.iter().filter(|&&d| d == 0).count();
+        Self {
+            rank: zeros + generators - diagonal.len(),
+            invariant_factors: diagonal
+                .into_iter()
+                .filter(|&d| d > 1)
+                .map(|d| d as u64)
+                .collect(),
+        }
+    }
+
+    /// Every abelian group of order `n`, one per choice of a partition of each
+    /// prime's exponent
+    pub fn all_of_order(n: u64) -> Vec<AbelianGroup> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut choices: Vec<Vec<u64>> = vec![Vec::new()];
+        for (prime, exponent) in factorize(n) {
+            choices = choices
+                .iter()
+                .flat_map(|divisors| {
+                    partitions(exponent).into_iter().map(move |partition| {
+                        let mut divisors = divisors.clone();
+                        divisors.extend(partition.iter().map(|&e| prime.pow(e)));
+                        divisors
+                    })
+                })
+                .collect();
+        }
+        choices
+            .iter()
+            .map(|divisors| Self::product(divisors))
+            .collect()
+    }
+
+    /// Rank of the free part, 0 for finite groups
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.rank == 0
+    }
+
+    pub fn is_cyclic(&self) -> bool {
+        self.rank + self.invariant_factors.len() <= 1
+    }
+
+    /// `None` for infinite groups
+    pub fn order(&self) -> Option<u64> {
+        self.is_finite()
+            .then(|| self.invariant_factors.iter().product())
+    }
+
+    pub fn invariant_factors(&self) -> &[u64] {
+        &self.invariant_factors
+    }
+
+    /// Prime powers, grouped by prime in increasing order
+    pub fn elementary_divisors(&self) -> Vec<u64> {
+        let mut divisors: Vec<(u64, u64)> = self
+            .invariant_factors
+            .iter()
+            .flat_map(|&d| factorize(d))
+            .map(|(prime, exponent)| (prime, prime.pow(exponent)))
+            .collect();
+        divisors.sort_unstable();
+        divisors.into_iter().map(|(_, power)| power).collect()
+    }
+
+    /// Like the `Display` form but with elementary divisors, `Z/2 × Z/4 × Z/3`
+    pub fn elementary_notation(&self) -> String {
+        notation(self.rank, &self.elementary_divisors())
+    }
+
+    /// The finite group acting on disjoint cycles, one per elementary divisor
+    pub fn perm_group(&self) -> Option<PermGroup> {
+        if !self.is_finite() {
+            return None;
+        }
+        let mut start = 0;
+        let mut generators = Vec::new();
+        for length in self.elementary_divisors() {
+            let points: Vec<usize> = (start..start + length as usize).collect();
+            generators.push(Permutation::cycle(&points).expect("Distinct points"));
+            start += length as usize;
+        }
+        Some(PermGroup::new(start, generators))
+    }
+}
+
+fn notation(rank: usize, orders: &[u64]) -> String {
+    let mut factors: Vec<String> = std::iter::repeat_n("Z".to_string(), rank).collect();
+    factors.extend(orders.iter().map(|order| format!("Z/{}", order)));
+    if factors.is_empty() {
+        "0".to_string()
+    } else {
+        factors.join(" × ")
+    }
+}
+
+/// Invariant factor form such as `Z × Z/2 × Z/12`, the trivial group is `0`
+impl fmt::Display for AbelianGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", notation(self.rank, &self.invariant_factors))
+    }
+}
+
+/// Prime factors with their exponents, by increasing prime
+fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    let mut prime = 2;
+    while prime * prime <= n {
+        let mut exponent = 0;
+        while n.is_multiple_of(prime) {
+            n /= prime;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((prime, exponent));
+        }
+        prime += 1;
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// The ways of writing `n` as a sum of positive parts, largest parts first
+fn partitions(n: u32) -> Vec<Vec<u32>> {
+    fn build(n: u32, max: u32, prefix: &mut Vec<u32>, out: &mut Vec<Vec<u32>>) {
+        if n == 0 {
+            out.push(prefix.clone());
+            return;
+        }
+        for part in (1..=n.min(max)).rev() {
+            prefix.push(part);
+            build(n - part, part, prefix, out);
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    build(n, n, &mut Vec::new(), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multiply(a: &[Vec<i64>], b: &[Vec<i64>]) -> Matrix {
+        a.iter()
+            .map(|row| {
+                (0..b[0].len())
+                    .map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_smith_normal_form() {
+        let matrix = vec![vec![2, 4, 4], vec![-6, 6, 12], vec![10, -4, -16]];
+        let smith = smith_normal_form(&matrix);
+        assert_eq!(smith.diagonal, [2, 6, 12]);
+
+        let product = multiply(&multiply(&smith.left, &matrix), &smith.right);
+        for (i, row) in product.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                assert_eq!(x, if i == j { smith.diagonal[i] } else { 0 });
+            }
+        }
+
+        // Rectangular, with a zero on the diagonal
+        let smith = smith_normal_form(&[vec![4, 6, 0], vec![2, 3, 0]]);
+        assert_eq!(smith.diagonal, [1, 0]);
+        assert!(smith_normal_form(&[]).diagonal.is_empty());
+    }
+
+    #[test]
+    fn test_relations() {
+        let group = AbelianGroup::from_relations(2, &[vec![2, 0], vec![0, 3]]).unwrap();
+        assert_eq!(group, AbelianGroup::cyclic(6));
+        assert_eq!(group.elementary_divisors(), [2, 3]);
+
+        let group = AbelianGroup::from_relations(2, &[vec![4, 6]]).unwrap();
+        assert_eq!(group.to_string(), "Z × Z/2");
+        assert_eq!(group.order(), None);
+
+        let group =
+            AbelianGroup::from_relations(3, &[vec![2, 4, 4], vec![-6, 6, 12], vec![10, -4, -16]])
+                .unwrap();
+        assert_eq!(group.to_string(), "Z/2 × Z/6 × Z/12");
+        assert_eq!(group.elementary_notation(), "Z/2 × Z/2 × Z/4 × Z/3 × Z/3");
+        assert_eq!(group.order(), Some(144));
+
+        assert_eq!(
+            AbelianGroup::from_relations(2, &[vec![1]]),
+            Err(AbelianError::WrongLength {
+                row: 0,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            AbelianGroup::from_relations(1, &[vec![1]])
+                .unwrap()
+                .to_string(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_all_of_order() {
+        let names: Vec<String> = AbelianGroup::all_of_order(8)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(names, ["Z/8", "Z/2 × Z/4", "Z/2 × Z/2 × Z/2"]);
+
+        let counts = [(1, 1), (7, 1), (16, 5), (72, 6), (360, 6), (1024, 42)];
+        for (n, count) in counts {
+            let groups = AbelianGroup::all_of_order(n);
+            assert_eq!(groups.len(), count, "order {}", n);
+            assert!(groups.iter().all(|group| group.order() == Some(n)));
+        }
+        // Z/m × Z/n is cyclic exactly when m and n are coprime
+        assert!(AbelianGroup::product(&[4, 9]).is_cyclic());
+        assert!(!AbelianGroup::product(&[6, 9]).is_cyclic());
+    }
+
+    #[test]
+    fn test_perm_group() {
+        let group = AbelianGroup::product(&[2, 6]);
+        let perms = group.perm_group().unwrap();
+        assert_eq!(perms.order(), 12);
+        assert!(perms.is_abelian());
+        assert!(AbelianGroup::cyclic(0).perm_group().is_none());
+    }
+}
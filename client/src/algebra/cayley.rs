@@ -0,0 +1,144 @@
+//! Cayley graphs: a vertex per element and an edge from `g` to `g s` for each
+//! generator `s`, so walking from the identity spells out words in the generators
+
+use super::finite::FiniteGroup;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// Position of the generator in the generating set
+    pub generator: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct CayleyGraph {
+    generators: Vec<usize>,
+    edges: Vec<Edge>,
+    /// A shortest word for each element, as positions in the generating set
+    words: Vec<Vec<usize>>,
+}
+
+impl CayleyGraph {
+    /// The graph of `group` with respect to `generators`, which are element indices.
+    /// Elements the generators don't reach get no word.
+    pub fn new(group: &FiniteGroup, generators: &[usize]) -> Self {
+        let edges = generators
+            .iter()
+            .enumerate()
+            .flat_map(|(generator, &s)| {
+                (0..group.order()).map(move |from| Edge {
+                    from,
+                    to: group.multiply(from, s),
+                    generator,
+                })
+            })
+            .collect();
+
+        // Breadth first from the identity finds shortest words
+        let mut words: Vec<Option<Vec<usize>>> = vec![None; group.order()];
+        words[group.identity()] = Some(Vec::new());
+        let mut queue = std::collections::VecDeque::from([group.identity()]);
+        while let Some(element) = queue.pop_front() {
+            for (generator, &s) in generators.iter().enumerate() {
+                let next = group.multiply(element, s);
+                if words[next].is_none() {
+                    let mut word = words[element].clone().expect("Queued elements have words");
+                    word.push(generator);
+                    words[next] = Some(word);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Self {
+            generators: generators.to_vec(),
+            edges,
+            words: words.into_iter().map(Option::unwrap_or_default).collect(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn generators(&self) -> &[usize] {
+        &self.generators
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Generators applied left to right give `element`, empty for the identity
+    pub fn word(&self, element: usize) -> &[usize] {
+        &self.words[element]
+    }
+
+    /// The word for `element` written with `a`, `b`, ... and powers collected, like `a²b`
+    pub fn word_string(&self, element: usize) -> String {
+        let word = self.word(element);
+        if word.is_empty() {
+            return "e".to_string();
+        }
+        let mut string = String::new();
+        let mut rest = word;
+        while let Some(&generator) = rest.first() {
+            let run = rest.iter().take_while(|&&g| g == generator).count();
+            string.push_str(&generator_name(generator));
+            if run > 1 {
+                string.push_str(&superscript(run));
+            }
+            rest = &rest[run..];
+        }
+        string
+    }
+}
+
+/// `a`, `b`, ... for the first 26 generators, then `g26`, `g27`, ...
+pub fn generator_name(generator: usize) -> String {
+    match u8::try_from(generator) {
+        Ok(offset) if offset < 26 => char::from(b'a' + offset).to_string(),
+        _ => format!("g{}", generator),
+    }
+}
+
+fn superscript(n: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    n.to_string()
+        .bytes()
+        .map(|digit| DIGITS[(digit - b'0') as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::group::PermGroup;
+
+    #[test]
+    fn test_words() {
+        let group = FiniteGroup::new(&PermGroup::dihedral(4)).unwrap();
+        let graph = CayleyGraph::new(&group, group.generators());
+        assert_eq!(graph.vertex_count(), 8);
+        assert_eq!(graph.edges().len(), 16);
+        for element in 0..8 {
+            let product = graph
+                .word(element)
+                .iter()
+                .fold(group.identity(), |product, &g| {
+                    group.multiply(product, graph.generators()[g])
+                });
+            assert_eq!(product, element);
+        }
+        // The rotation by a half turn is a², nothing shorter
+        let rotation = group.generators()[0];
+        let half_turn = group.multiply(rotation, rotation);
+        assert_eq!(graph.word_string(half_turn), "a²");
+        assert_eq!(graph.word_string(group.identity()), "e");
+        let longest = (0..8).map(|e| graph.word(e).len()).max();
+        assert_eq!(longest, Some(3));
+        assert_eq!(generator_name(1), "b");
+        assert_eq!(generator_name(30), "g30");
+    }
+}
@@ -0,0 +1,205 @@
+//! Small groups with every element listed and products looked up in a table,
+//! which is what the lattice, Cayley graph and Cayley table work with
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::group::PermGroup;
+use super::perm::Permutation;
+
+/// Largest order worth listing every element and product of
+pub const MAX_ORDER: usize = 240;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FiniteGroupError {
+    TooLarge(u128),
+}
+
+impl fmt::Display for FiniteGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge(order) => write!(
+                f,
+                "the group has {} elements, more than the {} that can be shown",
+                order, MAX_ORDER
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FiniteGroupError {}
+
+/// Elements are referred to by index, the identity is always 0
+#[derive(Clone, Debug)]
+pub struct FiniteGroup {
+    elements: Vec<Permutation>,
+    index: HashMap<Permutation, usize>,
+    /// `table[a][b]` is the index of `a * b`
+    table: Vec<Vec<usize>>,
+    inverses: Vec<usize>,
+    /// Indices of the generators the group was given by
+    generators: Vec<usize>,
+}
+
+impl FiniteGroup {
+    pub fn new(group: &PermGroup) -> Result<Self, FiniteGroupError> {
+        if group.order() > MAX_ORDER as u128 {
+            return Err(FiniteGroupError::TooLarge(group.order()));
+        }
+        let elements = group.elements();
+        let index: HashMap<Permutation, usize> = elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| (element.clone(), i))
+            .collect();
+        let table: Vec<Vec<usize>> = elements
+            .iter()
+            .map(|a| elements.iter().map(|b| index[&a.compose(b)]).collect())
+            .collect();
+        let inverses = (0..elements.len())
+            .map(|a| {
+                table[a]
+                    .iter()
+                    .position(|&product| product == 0)
+                    .expect("Every element has an inverse")
+            })
+            .collect();
+        let generators = group
+            .generators()
+            .iter()
+            .map(|generator| index[generator])
+            .collect();
+        Ok(Self {
+            elements,
+            index,
+            table,
+            inverses,
+            generators,
+        })
+    }
+
+    pub fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn identity(&self) -> usize {
+        0
+    }
+
+    pub fn element(&self, index: usize) -> &Permutation {
+        &self.elements[index]
+    }
+
+    pub fn elements(&self) -> &[Permutation] {
+        &self.elements
+    }
+
+    pub fn index_of(&self, element: &Permutation) -> Option<usize> {
+        self.index.get(element).copied()
+    }
+
+    pub fn generators(&self) -> &[usize] {
+        &self.generators
+    }
+
+    pub fn multiply(&self, a: usize, b: usize) -> usize {
+        self.table[a][b]
+    }
+
+    pub fn inverse(&self, a: usize) -> usize {
+        self.inverses[a]
+    }
+
+    /// `g a g⁻¹`
+    pub fn conjugate(&self, a: usize, g: usize) -> usize {
+        self.multiply(self.multiply(g, a), self.inverse(g))
+    }
+
+    /// Smallest positive power of `a` that is the identity
+    pub fn element_order(&self, a: usize) -> usize {
+        let mut power = a;
+        let mut order = 1;
+        while power != 0 {
+            power = self.multiply(power, a);
+            order += 1;
+        }
+        order
+    }
+
+    pub fn is_abelian(&self) -> bool {
+        (0..self.order()).all(|a| (0..a).all(|b| self.table[a][b] == self.table[b][a]))
+    }
+
+    /// Elements of the subgroup generated by `generators`, in increasing order
+    pub fn generate(&self, generators: &[usize]) -> Vec<usize> {
+        let mut member = vec![false; self.order()];
+        member[0] = true;
+        let mut elements = vec![0];
+        let mut next = 0;
+        while let Some(&a) = elements.get(next) {
+            next += 1;
+            for &g in generators {
+                let product = self.multiply(a, g);
+                if !std::mem::replace(&mut member[product], true) {
+                    elements.push(product);
+                }
+            }
+        }
+        elements.sort_unstable();
+        elements
+    }
+
+    /// The left cosets `g H` of the subgroup with elements `subgroup`, starting
+    /// with `H` itself. Each coset lists `g h` for the `h` in the order given,
+    /// and `g` is the first element of the group not in an earlier coset.
+    pub fn left_cosets(&self, subgroup: &[usize]) -> Vec<Vec<usize>> {
+        let mut covered = vec![false; self.order()];
+        let mut cosets = Vec::new();
+        for g in 0..self.order() {
+            if covered[g] {
+                continue;
+            }
+            let coset: Vec<usize> = subgroup.iter().map(|&h| self.multiply(g, h)).collect();
+            coset.iter().for_each(|&element| covered[element] = true);
+            cosets.push(coset);
+        }
+        cosets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let group = FiniteGroup::new(&PermGroup::dihedral(4)).unwrap();
+        assert_eq!(group.order(), 8);
+        assert!(group.element(group.identity()).is_identity());
+        for a in 0..8 {
+            assert_eq!(group.multiply(a, group.inverse(a)), 0);
+            let expected = group.element(a).compose(group.element(a));
+            assert_eq!(group.element(group.multiply(a, a)), &expected);
+        }
+        assert!(!group.is_abelian());
+        let orders: Vec<usize> = (0..8).map(|a| group.element_order(a)).collect();
+        assert_eq!(orders.iter().filter(|&&order| order == 4).count(), 2);
+
+        let rotation = group.generators()[0];
+        assert_eq!(group.generate(&[rotation]).len(), 4);
+        assert_eq!(group.generate(group.generators()).len(), 8);
+
+        let rotations = group.generate(&[rotation]);
+        let cosets = group.left_cosets(&rotations);
+        assert_eq!(cosets.len(), 2);
+        assert_eq!(cosets[0], rotations);
+        let mut all: Vec<usize> = cosets.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..8).collect::<Vec<_>>());
+
+        assert_eq!(
+            FiniteGroup::new(&PermGroup::symmetric(6)).unwrap_err(),
+            FiniteGroupError::TooLarge(720)
+        );
+    }
+}
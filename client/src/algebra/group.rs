@@ -0,0 +1,495 @@
+//! Permutation groups given by generators, stored as a stabilizer chain built
+//! with the Schreier–Sims algorithm.
+//!
+//! The chain picks base points `b0, b1, ...` and for each level `i` the orbit
+//! of `bi` under the elements fixing `b0..bi`, with a transversal element
+//! carrying `bi` to each point of the orbit. Every group element is then
+//! uniquely a product of one transversal element per level, which gives the
+//! order, membership testing, uniform random elements and enumeration.
+
+use std::collections::HashSet;
+
+use super::perm::Permutation;
+use super::random::Random;
+
+/// One level of the stabilizer chain
+#[derive(Debug, Clone)]
+struct Level {
+    base_point: usize,
+    /// Orbit of the base point in the order it was found, starting with it
+    orbit: Vec<usize>,
+    /// `transversal[p]` carries the base point to `p`, for each `p` in the orbit
+    transversal: Vec<Option<Permutation>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PermGroup {
+    degree: usize,
+    generators: Vec<Permutation>,
+    strong_generators: Vec<Permutation>,
+    levels: Vec<Level>,
+}
+
+impl PermGroup {
+    /// The group generated by `generators` acting on `0..degree`, the degree
+    /// grows to fit generators moving larger points
+    pub fn new(degree: usize, generators: Vec<Permutation>) -> Self {
+        let generators: Vec<Permutation> = generators
+            .into_iter()
+            .filter(|generator| !generator.is_identity())
+            .collect();
+        let degree = generators
+            .iter()
+            .map(Permutation::degree)
+            .fold(degree, usize::max);
+        let mut group = Self {
+            degree,
+            strong_generators: generators.clone(),
+            generators,
+            levels: Vec::new(),
+        };
+        group.schreier_sims();
+        group
+    }
+
+    pub fn trivial(degree: usize) -> Self {
+        Self::new(degree, Vec::new())
+    }
+
+    /// All permutations of `n` points, generated by a transposition and an `n`-cycle
+    pub fn symmetric(n: usize) -> Self {
+        let cycle: Vec<usize> = (0..n).collect();
+        let generators = match n {
+            0 | 1 => Vec::new(),
+            _ => vec![Permutation::transposition(0, 1), long_cycle(&cycle)],
+        };
+        Self::new(n, generators)
+    }
+
+    /// The even permutations of `n` points, generated by the 3-cycles `(0 1 i)`
+    pub fn alternating(n: usize) -> Self {
+        let generators = (2..n)
+            .map(|i| Permutation::cycle(&[0, 1, i]).expect("Distinct points"))
+            .collect();
+        Self::new(n, generators)
+    }
+
+    /// Rotations of an `n`-gon's vertices
+    pub fn cyclic(n: usize) -> Self {
+        let cycle: Vec<usize> = (0..n).collect();
+        Self::new(n, vec![long_cycle(&cycle)])
+    }
+
+    /// Symmetries of a regular `n`-gon acting on its vertices, of order `2n`.
+    /// A monogon or digon has too few vertices to tell its symmetries apart, so
+    /// those act on their vertices and edge midpoints taken in turn instead.
+    pub fn dihedral(n: usize) -> Self {
+        assert!(n > 0, "A polygon has at least one vertex");
+        if n < 3 {
+            let points = 2 * n;
+            let rotation: Vec<usize> = (0..points).map(|i| (i + 2) % points).collect();
+            let mirror: Vec<usize> = (0..points).map(|i| (points + 1 - i) % points).collect();
+            let generators = [rotation, mirror]
+                .into_iter()
+                .map(|images| {
+                    Permutation::from_images(images).expect("Shifts and reflections are bijections")
+                })
+                .collect();
+            return Self::new(points, generators);
+        }
+        let cycle: Vec<usize> = (0..n).collect();
+        let mirror: Vec<usize> = (0..n).map(|i| (n - i) % n).collect();
+        let mirror = Permutation::from_images(mirror).expect("i -> -i is a bijection");
+        Self::new(n, vec![long_cycle(&cycle), mirror])
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn generators(&self) -> &[Permutation] {
+        &self.generators
+    }
+
+    /// Points only the identity fixes all of
+    pub fn base(&self) -> Vec<usize> {
+        self.levels.iter().map(|level| level.base_point).collect()
+    }
+
+    /// Generators including, for each level, generators of the elements fixing
+    /// the base points before it
+    pub fn strong_generators(&self) -> &[Permutation] {
+        &self.strong_generators
+    }
+
+    pub fn order(&self) -> u128 {
+        self.levels
+            .iter()
+            .map(|level| level.orbit.len() as u128)
+            .product()
+    }
+
+    pub fn is_trivial(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    pub fn contains(&self, element: &Permutation) -> bool {
+        element.degree() <= self.degree && self.sift(element.clone(), 0).0.is_identity()
+    }
+
+    /// Whether the generators, and so all the elements, commute
+    pub fn is_abelian(&self) -> bool {
+        self.generators.iter().enumerate().all(|(i, a)| {
+            self.generators[i + 1..]
+                .iter()
+                .all(|b| a.compose(b) == b.compose(a))
+        })
+    }
+
+    /// Points `point` can be moved to, in the order they are reached
+    pub fn orbit(&self, point: usize) -> Vec<usize> {
+        orbit_transversal(point, self.degree, &self.generators).0
+    }
+
+    /// The orbits partitioning `0..degree`, each starting at its smallest point
+    pub fn orbits(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.degree];
+        let mut orbits = Vec::new();
+        for point in 0..self.degree {
+            if !seen[point] {
+                let orbit = self.orbit(point);
+                orbit.iter().for_each(|&p| seen[p] = true);
+                orbits.push(orbit);
+            }
+        }
+        orbits
+    }
+
+    /// The elements fixing `point`, generated by Schreier generators
+    pub fn stabilizer(&self, point: usize) -> PermGroup {
+        let (orbit, transversal) = orbit_transversal(point, self.degree, &self.generators);
+        let mut generators = Vec::new();
+        let mut seen = HashSet::new();
+        for &p in &orbit {
+            let to_p = transversal[p]
+                .as_ref()
+                .expect("Orbit points have transversals");
+            for generator in &self.generators {
+                let image = transversal[generator.apply(p)]
+                    .as_ref()
+                    .expect("Orbits are closed under the generators");
+                let schreier = image.inverse().compose(generator).compose(to_p);
+                if !schreier.is_identity() && seen.insert(schreier.clone()) {
+                    generators.push(schreier);
+                }
+            }
+        }
+        PermGroup::new(self.degree, generators)
+    }
+
+    /// The elements fixing every point in `points`
+    pub fn pointwise_stabilizer(&self, points: &[usize]) -> PermGroup {
+        points
+            .iter()
+            .fold(self.clone(), |group, &point| group.stabilizer(point))
+    }
+
+    /// A uniformly random element
+    pub fn random_element(&self, random: &mut Random) -> Permutation {
+        self.levels
+            .iter()
+            .rev()
+            .fold(Permutation::identity(), |element, level| {
+                let point = level.orbit[random.below(level.orbit.len())];
+                let coset = level.transversal[point]
+                    .as_ref()
+                    .expect("Orbit points have transversals");
+                coset.compose(&element)
+            })
+    }
+
+    /// Every element, starting with the identity. Only sensible for small groups.
+    pub fn elements(&self) -> Vec<Permutation> {
+        self.levels
+            .iter()
+            .rev()
+            .fold(vec![Permutation::identity()], |elements, level| {
+                level
+                    .orbit
+                    .iter()
+                    .flat_map(|&point| {
+                        let coset = level.transversal[point]
+                            .as_ref()
+                            .expect("Orbit points have transversals");
+                        elements.iter().map(move |element| coset.compose(element))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Strips transversal elements off `element` from level `start` down, returning
+    /// what is left and the level it got stuck at, past the last level if none
+    fn sift(&self, mut element: Permutation, start: usize) -> (Permutation, usize) {
+        for (i, level) in self.levels.iter().enumerate().skip(start) {
+            let image = element.apply(level.base_point);
+            match level.transversal.get(image).and_then(Option::as_ref) {
+                Some(coset) => element = coset.inverse().compose(&element),
+                None => return (element, i),
+            }
+        }
+        (element, self.levels.len())
+    }
+
+    fn level_generators(&self, level: usize) -> Vec<Permutation> {
+        let fixed: Vec<usize> = self.levels[..level]
+            .iter()
+            .map(|level| level.base_point)
+            .collect();
+        self.strong_generators
+            .iter()
+            .filter(|generator| fixed.iter().all(|&b| generator.apply(b) == b))
+            .cloned()
+            .collect()
+    }
+
+    fn rebuild_level(&mut self, level: usize) {
+        let generators = self.level_generators(level);
+        let base_point = self.levels[level].base_point;
+        let (orbit, transversal) = orbit_transversal(base_point, self.degree, &generators);
+        self.levels[level].orbit = orbit;
+        self.levels[level].transversal = transversal;
+    }
+
+    fn push_level(&mut self, base_point: usize) {
+        self.levels.push(Level {
+            base_point,
+            orbit: vec![base_point],
+            transversal: Vec::new(),
+        });
+        self.rebuild_level(self.levels.len() - 1);
+    }
+
+    /// The deterministic Schreier–Sims algorithm: makes the Schreier generators
+    /// of every level sift through the levels below it, adding whatever is left
+    /// over as a new strong generator
+    fn schreier_sims(&mut self) {
+        // Every generator has to move some base point
+        for generator in self.generators.clone() {
+            if self.base().iter().all(|&b| generator.apply(b) == b) {
+                let moved = (0..self.degree)
+                    .find(|&p| generator.apply(p) != p)
+                    .expect("The identity was filtered out");
+                self.push_level(moved);
+            }
+        }
+        for level in 0..self.levels.len() {
+            self.rebuild_level(level);
+        }
+
+        let mut level = self.levels.len();
+        while level > 0 {
+            let i = level - 1;
+            match self.failed_schreier_generator(i) {
+                None => level -= 1,
+                Some((residue, stuck)) => {
+                    if stuck == self.levels.len() {
+                        let moved = (0..self.degree)
+                            .find(|&p| residue.apply(p) != p)
+                            .expect("Residues aren't the identity");
+                        self.push_level(moved);
+                    }
+                    self.strong_generators.push(residue);
+                    for below in i + 1..=stuck {
+                        self.rebuild_level(below);
+                    }
+                    level = stuck + 1;
+                }
+            }
+        }
+    }
+
+    /// A Schreier generator of level `i` that doesn't sift through the levels below
+    fn failed_schreier_generator(&self, i: usize) -> Option<(Permutation, usize)> {
+        let level = &self.levels[i];
+        let generators = self.level_generators(i);
+        for &point in &level.orbit {
+            let to_point = level.transversal[point]
+                .as_ref()
+                .expect("Orbit points have transversals");
+            for generator in &generators {
+                let image = level.transversal[generator.apply(point)]
+                    .as_ref()
+                    .expect("Orbits are closed under the generators");
+                let schreier = image.inverse().compose(generator).compose(to_point);
+                let (residue, stuck) = self.sift(schreier, i + 1);
+                if !residue.is_identity() {
+                    return Some((residue, stuck));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn long_cycle(points: &[usize]) -> Permutation {
+    Permutation::cycle(points).expect("Distinct points")
+}
+
+/// Orbit of `point` in breadth first order, and for each point of it an
+/// element carrying `point` there
+fn orbit_transversal(
+    point: usize,
+    degree: usize,
+    generators: &[Permutation],
+) -> (Vec<usize>, Vec<Option<Permutation>>) {
+    let mut transversal = vec![None; degree.max(point + 1)];
+    transversal[point] = Some(Permutation::identity());
+    let mut orbit = vec![point];
+    let mut next = 0;
+    while let Some(&p) = orbit.get(next) {
+        next += 1;
+        for generator in generators {
+            let image = generator.apply(p);
+            if transversal[image].is_none() {
+                let to_p = transversal[p]
+                    .as_ref()
+                    .expect("Orbit points have transversals");
+                transversal[image] = Some(generator.compose(to_p));
+                orbit.push(image);
+            }
+        }
+    }
+    (orbit, transversal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::polyhedra::Solid;
+
+    fn perm(input: &str) -> Permutation {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_orders() {
+        let factorial = |n: u128| (1..=n).product::<u128>();
+        for n in 1..=8 {
+            assert_eq!(
+                PermGroup::symmetric(n).order(),
+                factorial(n as u128),
+                "S{}",
+                n
+            );
+            assert_eq!(PermGroup::cyclic(n).order(), n as u128);
+        }
+        for n in 3..=8 {
+            assert_eq!(PermGroup::alternating(n).order(), factorial(n as u128) / 2);
+            assert_eq!(PermGroup::dihedral(n).order(), 2 * n as u128);
+        }
+        assert_eq!(PermGroup::symmetric(20).order(), factorial(20));
+        assert_eq!(PermGroup::trivial(4).order(), 1);
+        // Two overlapping 5-cycles generate all of A7
+        let group = PermGroup::new(7, vec![perm("(0 1 2 3 4)"), perm("(2 3 4 5 6)")]);
+        assert_eq!(group.order(), 2520);
+    }
+
+    #[test]
+    fn test_polyhedral_groups() {
+        for (solid, order) in [
+            (Solid::Tetrahedron, 12),
+            (Solid::Cube, 24),
+            (Solid::Octahedron, 24),
+            (Solid::Dodecahedron, 60),
+            (Solid::Icosahedron, 60),
+        ] {
+            let group = solid.polyhedron().rotation_group();
+            assert_eq!(group.order(), order, "{}", solid.name());
+            // Rotations are transitive on vertices
+            assert_eq!(group.orbits().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_small_dihedral() {
+        assert_eq!(PermGroup::dihedral(1).order(), 2);
+
+        // The digon's symmetries are the Klein four group, with no element of order 4
+        let digon = PermGroup::dihedral(2);
+        assert_eq!(digon.order(), 4);
+        assert!(digon.is_abelian());
+        assert!(digon
+            .elements()
+            .iter()
+            .all(|element| element.compose(element).is_identity()));
+    }
+
+    #[test]
+    fn test_membership() {
+        let symmetric = PermGroup::symmetric(5);
+        let alternating = PermGroup::alternating(5);
+        let odd = perm("(0 3)");
+        assert!(symmetric.contains(&odd));
+        assert!(!alternating.contains(&odd));
+        assert!(alternating.contains(&perm("(0 1)(2 4)")));
+        assert!(!symmetric.contains(&perm("(0 5)")));
+
+        let square = PermGroup::dihedral(4);
+        assert!(square.contains(&perm("(0 2)(1 3)")));
+        assert!(square.contains(&perm("(1 3)")));
+        assert!(!square.contains(&perm("(0 1)")));
+
+        // The base really is one, fixing it fixes everything
+        let base = symmetric.base();
+        assert!(symmetric.pointwise_stabilizer(&base).is_trivial());
+        assert!(symmetric
+            .strong_generators()
+            .iter()
+            .all(|generator| symmetric.contains(generator)));
+    }
+
+    #[test]
+    fn test_orbits_and_stabilizers() {
+        let group = PermGroup::new(7, vec![perm("(0 1 2)"), perm("(3 4)")]);
+        assert_eq!(
+            group.orbits(),
+            [vec![0, 1, 2], vec![3, 4], vec![5], vec![6]]
+        );
+
+        // Orbit-stabilizer: |G| = |orbit| |stabilizer|
+        let cube = Solid::Cube.polyhedron().rotation_group();
+        for point in 0..cube.degree() {
+            let stabilizer = cube.stabilizer(point);
+            assert_eq!(
+                stabilizer.order() * cube.orbit(point).len() as u128,
+                cube.order()
+            );
+            assert!(stabilizer
+                .generators()
+                .iter()
+                .all(|g| g.apply(point) == point));
+        }
+        assert_eq!(PermGroup::symmetric(6).stabilizer(2).order(), 120);
+    }
+
+    #[test]
+    fn test_elements_and_random() {
+        let group = PermGroup::alternating(4);
+        let elements = group.elements();
+        assert_eq!(elements.len(), 12);
+        assert!(elements[0].is_identity());
+        let distinct: HashSet<&Permutation> = elements.iter().collect();
+        assert_eq!(distinct.len(), 12);
+        assert!(elements.iter().all(|element| element.is_even()));
+
+        let mut random = Random::new(7);
+        let group = PermGroup::dihedral(6);
+        let mut seen = HashSet::new();
+        for _ in 0..500 {
+            let element = group.random_element(&mut random);
+            assert!(group.contains(&element));
+            seen.insert(element);
+        }
+        assert_eq!(seen.len(), 12);
+    }
+}
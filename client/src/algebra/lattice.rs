@@ -0,0 +1,213 @@
+//! All subgroups of a small group ordered by inclusion.
+//!
+//! Every subgroup is generated by cyclic subgroups, so starting from the
+//! trivial group and repeatedly joining on one more element reaches them all.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::finite::FiniteGroup;
+
+/// Beyond this the diagram is too crowded to read, and the search too slow
+pub const MAX_SUBGROUPS: usize = 400;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LatticeError {
+    TooManySubgroups,
+}
+
+impl fmt::Display for LatticeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManySubgroups => {
+                write!(f, "the group has more than {} subgroups", MAX_SUBGROUPS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LatticeError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Subgroup {
+    /// Indices into the group's elements, increasing
+    pub elements: Vec<usize>,
+    /// A few elements generating the subgroup
+    pub generators: Vec<usize>,
+    pub normal: bool,
+    /// Number of cosets in the whole group
+    pub index: usize,
+}
+
+impl Subgroup {
+    pub fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn contains(&self, element: usize) -> bool {
+        self.elements.binary_search(&element).is_ok()
+    }
+
+    /// Whether `self` is a subgroup of `other`
+    pub fn is_subgroup_of(&self, other: &Subgroup) -> bool {
+        self.order() <= other.order() && self.elements.iter().all(|&a| other.contains(a))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubgroupLattice {
+    /// By increasing order, the trivial group first and the whole group last
+    pub subgroups: Vec<Subgroup>,
+    /// Hasse diagram edges `(smaller, larger)`, where nothing fits strictly between
+    pub covers: Vec<(usize, usize)>,
+}
+
+impl SubgroupLattice {
+    pub fn new(group: &FiniteGroup) -> Result<Self, LatticeError> {
+        let mut found: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+        found.insert(vec![0], Vec::new());
+        let mut frontier = vec![vec![0]];
+        while let Some(elements) = frontier.pop() {
+            let generators = found[&elements].clone();
+            for a in 0..group.order() {
+                if elements.binary_search(&a).is_ok() {
+                    continue;
+                }
+                let mut joined_generators = generators.clone();
+                joined_generators.push(a);
+                let joined = group.generate(&joined_generators);
+                match found.get_mut(&joined) {
+                    // Keep the shortest generating set seen
+                    Some(existing) => {
+                        if existing.len() > joined_generators.len() {
+                            *existing = joined_generators;
+                        }
+                    }
+                    None => {
+                        if found.len() == MAX_SUBGROUPS {
+                            return Err(LatticeError::TooManySubgroups);
+                        }
+                        found.insert(joined.clone(), joined_generators);
+                        frontier.push(joined);
+                    }
+                }
+            }
+        }
+
+        let mut subgroups: Vec<Subgroup> = found
+            .into_iter()
+            .map(|(elements, generators)| {
+                // Conjugating the generators by the group's generators is enough
+                let normal = group.generators().iter().all(|&g| {
+                    generators
+                        .iter()
+                        .all(|&a| elements.binary_search(&group.conjugate(a, g)).is_ok())
+                });
+                Subgroup {
+                    index: group.order() / elements.len(),
+                    elements,
+                    generators,
+                    normal,
+                }
+            })
+            .collect();
+        subgroups.sort_by(|a, b| (a.order(), &a.elements).cmp(&(b.order(), &b.elements)));
+
+        let count = subgroups.len();
+        let below: Vec<Vec<bool>> = (0..count)
+            .map(|i| {
+                (0..count)
+                    .map(|j| i != j && subgroups[i].is_subgroup_of(&subgroups[j]))
+                    .collect()
+            })
+            .collect();
+        let covers = (0..count)
+            .flat_map(|i| (0..count).map(move |j| (i, j)))
+            .filter(|&(i, j)| below[i][j] && !(0..count).any(|k| below[i][k] && below[k][j]))
+            .collect();
+
+        Ok(Self { subgroups, covers })
+    }
+
+    /// Layers for drawing, by the number of prime factors of the order counted
+    /// with multiplicity, so each cover goes up at least one layer
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        for (i, subgroup) in self.subgroups.iter().enumerate() {
+            let height = prime_factor_count(subgroup.order());
+            if layers.len() <= height {
+                layers.resize(height + 1, Vec::new());
+            }
+            layers[height].push(i);
+        }
+        layers
+    }
+}
+
+fn prime_factor_count(mut n: usize) -> usize {
+    let mut count = 0;
+    let mut prime = 2;
+    while n > 1 {
+        while n.is_multiple_of(prime) {
+            n /= prime;
+            count += 1;
+        }
+        prime += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::abelian::AbelianGroup;
+    use crate::algebra::group::PermGroup;
+
+    fn lattice(group: &PermGroup) -> SubgroupLattice {
+        SubgroupLattice::new(&FiniteGroup::new(group).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_subgroup_counts() {
+        // (group, subgroups, normal subgroups)
+        let cases = [
+            (PermGroup::symmetric(3), 6, 3),
+            (PermGroup::dihedral(4), 10, 6),
+            (PermGroup::alternating(4), 10, 3),
+            (PermGroup::symmetric(4), 30, 4),
+            (PermGroup::alternating(5), 59, 2),
+            (AbelianGroup::cyclic(12).perm_group().unwrap(), 6, 6),
+            (
+                AbelianGroup::product(&[2, 2, 2]).perm_group().unwrap(),
+                16,
+                16,
+            ),
+        ];
+        for (group, count, normal) in cases {
+            let lattice = lattice(&group);
+            assert_eq!(lattice.subgroups.len(), count, "order {}", group.order());
+            let normal_count = lattice.subgroups.iter().filter(|s| s.normal).count();
+            assert_eq!(normal_count, normal, "order {}", group.order());
+        }
+    }
+
+    #[test]
+    fn test_hasse_diagram() {
+        let lattice = lattice(&PermGroup::symmetric(3));
+        let orders: Vec<usize> = lattice.subgroups.iter().map(Subgroup::order).collect();
+        assert_eq!(orders, [1, 2, 2, 2, 3, 6]);
+        assert_eq!(lattice.subgroups[5].index, 1);
+        assert_eq!(lattice.subgroups[1].index, 3);
+        // The trivial group under each prime order subgroup, each of those under S3
+        assert_eq!(lattice.covers.len(), 8);
+        assert!(!lattice.covers.contains(&(0, 5)));
+        assert_eq!(lattice.layers(), [vec![0], vec![1, 2, 3, 4], vec![5]]);
+
+        let group = FiniteGroup::new(&PermGroup::symmetric(4)).unwrap();
+        let lattice = SubgroupLattice::new(&group).unwrap();
+        for subgroup in &lattice.subgroups {
+            assert_eq!(group.generate(&subgroup.generators), subgroup.elements);
+            assert!(subgroup.generators.len() <= 2);
+        }
+    }
+}
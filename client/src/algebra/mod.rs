@@ -0,0 +1,9 @@
+//! Finite group theory on the CPU, independent of anything drawn on screen
+
+pub mod abelian;
+pub mod cayley;
+pub mod finite;
+pub mod group;
+pub mod lattice;
+pub mod perm;
+pub mod random;
@@ -0,0 +1,434 @@
+//! Permutations of the points `0..degree`.
+//!
+//! Products compose like functions, `(a * b).apply(i) == a.apply(b.apply(i))`,
+//! so `b` acts first. Points past a permutation's degree are fixed, which lets
+//! permutations of different degrees be multiplied and compared.
+
+use std::fmt;
+use std::ops::Mul;
+use std::str::FromStr;
+
+/// Points `str::parse` accepts are below this, the image table of a
+/// permutation takes a word per point it could move
+pub const MAX_PARSED_DEGREE: usize = 1 << 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PermError {
+    /// A point appears twice in one-line notation or in the cycles
+    Repeated(usize),
+    /// A point is too large for the degree asked for
+    OutOfRange(usize),
+    UnexpectedChar(char, usize),
+    /// A `(` or `[` without its closing bracket
+    Unclosed,
+    NumberTooLarge(usize),
+}
+
+impl fmt::Display for PermError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Repeated(point) => write!(f, "{} appears more than once", point),
+            Self::OutOfRange(point) => write!(f, "{} is out of range", point),
+            Self::UnexpectedChar(c, at) => write!(f, "unexpected character '{}' at {}", c, at),
+            Self::Unclosed => write!(f, "missing closing bracket"),
+            Self::NumberTooLarge(at) => write!(f, "number at {} is too large", at),
+        }
+    }
+}
+
+impl std::error::Error for PermError {}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    /// One-line notation, `images[i]` is where `i` goes. Trailing fixed points
+    /// are trimmed so equal permutations have equal images.
+    images: Vec<usize>,
+}
+
+impl Permutation {
+    pub fn identity() -> Self {
+        Self { images: Vec::new() }
+    }
+
+    /// From one-line notation, `images[i]` is where `i` goes
+    pub fn from_images(images: Vec<usize>) -> Result<Self, PermError> {
+        let mut seen = vec![false; images.len()];
+        for &image in &images {
+            match seen.get_mut(image) {
+                None => return Err(PermError::OutOfRange(image)),
+                Some(true) => return Err(PermError::Repeated(image)),
+                Some(seen) => *seen = true,
+            }
+        }
+        Ok(Self::trimmed(images))
+    }
+
+    /// From disjoint or overlapping cycles, applied right to left like a product
+    pub fn from_cycles<C: AsRef<[usize]>>(cycles: &[C]) -> Result<Self, PermError> {
+        cycles.iter().try_fold(Self::identity(), |product, cycle| {
+            Ok(product * Self::cycle(cycle.as_ref())?)
+        })
+    }
+
+    /// Sends each point of `points` to the next one and the last to the first
+    pub fn cycle(points: &[usize]) -> Result<Self, PermError> {
+        let degree = points.iter().max().map_or(0, |max| max + 1);
+        let mut images: Vec<usize> = (0..degree).collect();
+        let mut seen = vec![false; degree];
+        for (i, &point) in points.iter().enumerate() {
+            if std::mem::replace(&mut seen[point], true) {
+                return Err(PermError::Repeated(point));
+            }
+            images[point] = points[(i + 1) % points.len()];
+        }
+        Ok(Self::trimmed(images))
+    }
+
+    /// Swaps two points
+    pub fn transposition(a: usize, b: usize) -> Self {
+        if a == b {
+            return Self::identity();
+        }
+        Self::cycle(&[a, b]).expect("Distinct points form a cycle")
+    }
+
+    fn trimmed(mut images: Vec<usize>) -> Self {
+        while images.last().is_some_and(|&last| last == images.len() - 1) {
+            images.pop();
+        }
+        Self { images }
+    }
+
+    /// One more than the largest point that moves
+    pub fn degree(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn apply(&self, point: usize) -> usize {
+        self.images.get(point).copied().unwrap_or(point)
+    }
+
+    /// One-line notation padded with fixed points up to `degree`
+    pub fn images(&self, degree: usize) -> Vec<usize> {
+        (0..degree.max(self.degree()))
+            .map(|i| self.apply(i))
+            .collect()
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Applies `other` first and then `self`, the same as `self * other`
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        let degree = self.degree().max(other.degree());
+        Self::trimmed((0..degree).map(|i| self.apply(other.apply(i))).collect())
+    }
+
+    pub fn inverse(&self) -> Permutation {
+        let mut images = vec![0; self.degree()];
+        for (i, &image) in self.images.iter().enumerate() {
+            images[image] = i;
+        }
+        Self { images }
+    }
+
+    /// `self` applied `exponent` times, negative exponents use the inverse
+    pub fn pow(&self, exponent: i64) -> Permutation {
+        let mut base = if exponent < 0 {
+            self.inverse()
+        } else {
+            self.clone()
+        };
+        let mut exponent = exponent.unsigned_abs();
+        let mut result = Self::identity();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.compose(&base);
+            }
+            base = base.compose(&base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Cycles of length at least two, each starting at its smallest point,
+    /// ordered by that point
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.degree()];
+        let mut cycles = Vec::new();
+        for start in 0..self.degree() {
+            if seen[start] || self.images[start] == start {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut point = start;
+            while !seen[point] {
+                seen[point] = true;
+                cycle.push(point);
+                point = self.images[point];
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    /// Lengths of the cycles of a permutation of `degree` points, longest first,
+    /// fixed points counting as cycles of length one
+    pub fn cycle_type(&self, degree: usize) -> Vec<usize> {
+        let cycles = self.cycles();
+        let moved: usize = cycles.iter().map(Vec::len).sum();
+        let fixed = degree.max(self.degree()) - moved;
+        let mut lengths: Vec<usize> = cycles.iter().map(Vec::len).collect();
+        lengths.extend(std::iter::repeat_n(1, fixed));
+        lengths.sort_unstable_by(|a, b| b.cmp(a));
+        lengths
+    }
+
+    /// Smallest positive power that is the identity
+    pub fn order(&self) -> u64 {
+        self.cycles()
+            .iter()
+            .fold(1, |order, cycle| lcm(order, cycle.len() as u64))
+    }
+
+    /// 1 for even permutations and -1 for odd ones
+    pub fn sign(&self) -> i32 {
+        // A cycle of length k is a product of k - 1 transpositions
+        let transpositions: usize = self.cycles().iter().map(|cycle| cycle.len() - 1).sum();
+        if transpositions.is_multiple_of(2) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.sign() == 1
+    }
+
+    /// One-line notation such as `[2 0 1]`, padded with fixed points up to `degree`
+    pub fn one_line(&self, degree: usize) -> String {
+        let images: Vec<String> = self.images(degree).iter().map(usize::to_string).collect();
+        format!("[{}]", images.join(" "))
+    }
+}
+
+impl Default for Permutation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+impl Mul for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: &Permutation) -> Permutation {
+        self.compose(rhs)
+    }
+}
+
+impl Mul for Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: Permutation) -> Permutation {
+        self.compose(&rhs)
+    }
+}
+
+/// Cycle notation such as `(0 3 5)(1 2)`, fixed points are left out and the
+/// identity is `()`
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cycles = self.cycles();
+        if cycles.is_empty() {
+            return write!(f, "()");
+        }
+        for cycle in cycles {
+            let points: Vec<String> = cycle.iter().map(usize::to_string).collect();
+            write!(f, "({})", points.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Parses cycle notation like `(0 1 2)(3, 4)` or one-line notation like
+/// `[1 2 0]`. Cycles are multiplied right to left, so they needn't be disjoint.
+impl FromStr for Permutation {
+    type Err = PermError;
+
+    fn from_str(input: &str) -> Result<Self, PermError> {
+        Self::parse_within(input, MAX_PARSED_DEGREE)
+    }
+}
+
+impl Permutation {
+    /// Parses like `str::parse` but rejects points at or past `degree` before
+    /// anything is allocated for them, so typed input can't ask for huge permutations
+    pub fn parse_within(input: &str, degree: usize) -> Result<Self, PermError> {
+        let mut groups: Vec<(char, Vec<usize>)> = Vec::new();
+        let mut open: Option<(char, Vec<usize>)> = None;
+        let mut chars = input.char_indices().peekable();
+        while let Some((at, c)) = chars.next() {
+            match (c, &mut open) {
+                (c, _) if c.is_whitespace() => {}
+                (',', Some(_)) => {}
+                // One-line notation stands alone, cycles can be chained
+                ('[', None) if groups.is_empty() => open = Some((c, Vec::new())),
+                ('(', None) if groups.iter().all(|(kind, _)| *kind == '(') => {
+                    open = Some((c, Vec::new()));
+                }
+                (')', Some(('(', _))) | (']', Some(('[', _))) => {
+                    groups.extend(open.take());
+                }
+                ('0'..='9', Some((_, points))) => {
+                    let mut end = at + c.len_utf8();
+                    while let Some(&(next, '0'..='9')) = chars.peek() {
+                        end = next + 1;
+                        chars.next();
+                    }
+                    let point = input[at..end]
+                        .parse()
+                        .map_err(|_| PermError::NumberTooLarge(at))?;
+                    if point >= degree {
+                        return Err(PermError::OutOfRange(point));
+                    }
+                    points.push(point);
+                }
+                _ => return Err(PermError::UnexpectedChar(c, at)),
+            }
+        }
+        if open.is_some() {
+            return Err(PermError::Unclosed);
+        }
+
+        match groups.as_slice() {
+            [('[', images)] => Self::from_images(images.clone()),
+            _ => {
+                let cycles: Vec<Vec<usize>> =
+                    groups.into_iter().map(|(_, points)| points).collect();
+                Self::from_cycles(&cycles)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perm(input: &str) -> Permutation {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_notation_round_trips() {
+        let p = perm("(0 3 5)(1 2)");
+        assert_eq!(p.to_string(), "(0 3 5)(1 2)");
+        assert_eq!(p.images(6), [3, 2, 1, 5, 4, 0]);
+        assert_eq!(p.one_line(7), "[3 2 1 5 4 0 6]");
+        assert_eq!(perm("[3 2 1 5 4 0 6]"), p);
+        assert_eq!(
+            Permutation::from_images(vec![3, 2, 1, 5, 4, 0]),
+            Ok(p.clone())
+        );
+        assert_eq!(perm("(5, 0, 3) (2 1)"), p);
+
+        assert_eq!(perm("()"), Permutation::identity());
+        assert_eq!(perm(""), Permutation::identity());
+        assert_eq!(Permutation::identity().to_string(), "()");
+        // Fixed points don't change the permutation
+        assert_eq!(perm("[0 1 2]"), Permutation::identity());
+        assert_eq!(perm("(1 2)(4)"), perm("(1 2)"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("(0 1".parse::<Permutation>(), Err(PermError::Unclosed));
+        assert_eq!(
+            "(0 1 0)".parse::<Permutation>(),
+            Err(PermError::Repeated(0))
+        );
+        assert_eq!("[0 0]".parse::<Permutation>(), Err(PermError::Repeated(0)));
+        assert_eq!(
+            "[0 5]".parse::<Permutation>(),
+            Err(PermError::OutOfRange(5))
+        );
+        assert_eq!(
+            "(0 a)".parse::<Permutation>(),
+            Err(PermError::UnexpectedChar('a', 3))
+        );
+        // One-line notation can't be mixed with cycles
+        assert_eq!(
+            "(0 1)[1 0]".parse::<Permutation>(),
+            Err(PermError::UnexpectedChar('[', 5))
+        );
+        assert!("(99999999999999999999999)".parse::<Permutation>().is_err());
+        assert_eq!(
+            "(0 99999999999)".parse::<Permutation>(),
+            Err(PermError::OutOfRange(99999999999))
+        );
+        assert_eq!(
+            Permutation::parse_within("(0 100000000)", 32),
+            Err(PermError::OutOfRange(100000000))
+        );
+        assert_eq!(Permutation::parse_within("[1 0]", 2), Ok(perm("(0 1)")));
+    }
+
+    #[test]
+    fn test_products_compose_right_to_left() {
+        let a = perm("(0 1)");
+        let b = perm("(1 2)");
+        // b sends 1 to 2, then a leaves 2 alone
+        assert_eq!((&a * &b).apply(1), 2);
+        assert_eq!(&a * &b, perm("(0 1 2)"));
+        assert_eq!(&b * &a, perm("(0 2 1)"));
+        // Overlapping cycles in notation multiply the same way
+        assert_eq!(perm("(0 1)(1 2)"), &a * &b);
+
+        let p = perm("(0 4 2)(1 3)(5 6 7 8)");
+        assert!((&p * &p.inverse()).is_identity());
+        assert!((&p.inverse() * &p).is_identity());
+        assert_eq!(p.pow(-1), p.inverse());
+        assert_eq!(p.pow(3), perm("(1 3)(5 8 7 6)"));
+        assert!(p.pow(12).is_identity());
+    }
+
+    #[test]
+    fn test_invariants() {
+        let p = perm("(0 4 2)(1 3)(5 6 7 8)");
+        assert_eq!(p.order(), 12);
+        // 2 + 1 + 3 transpositions
+        assert_eq!(p.sign(), 1);
+        assert_eq!(p.cycle_type(10), [4, 3, 2, 1]);
+        assert_eq!(p.cycles(), [vec![0, 4, 2], vec![1, 3], vec![5, 6, 7, 8]]);
+        assert_eq!(p.degree(), 9);
+
+        assert_eq!(Permutation::identity().order(), 1);
+        assert!(Permutation::identity().is_even());
+        assert!(!Permutation::transposition(2, 5).is_even());
+        assert!(perm("(0 1 2)").is_even());
+
+        // The sign is a homomorphism
+        let q = perm("(0 8)(2 3 4)");
+        assert_eq!((&p * &q).sign(), p.sign() * q.sign());
+    }
+}
@@ -0,0 +1,35 @@
+//! A small seeded generator, enough for picking random group elements and
+//! scattering layouts without pulling in a crate
+
+/// xorshift64*, deterministic for a given seed so results can be reproduced
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // The state must never be zero
+        Self {
+            state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in `0..bound`, `bound` must be positive
+    pub fn below(&mut self, bound: usize) -> usize {
+        // The modulo bias is negligible for the small bounds used here
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
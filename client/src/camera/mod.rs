@@ -0,0 +1,166 @@
+//! Cameras for 3D scenes, a view plus a perspective or orthographic projection
+
+mod orbit;
+
+use glam::{Mat4, Vec2, Vec3};
+use winit::dpi::PhysicalSize;
+
+pub use orbit::OrbitController;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// `fov_y` is the vertical field of view in radians
+    Perspective { fov_y: f32 },
+    /// `height` is how many world units fit vertically in the window
+    Orthographic { height: f32 },
+}
+
+/// What shaders see of the camera
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_projection: [[f32; 4]; 4],
+    /// Camera position, w is unused
+    pub eye: [f32; 4],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    /// Width over height of the window
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera {
+    /// Looks down at the z = 0 plane with y up, two units of it fitting the window
+    /// height and everything with z between -1 and 1 visible
+    fn default() -> Self {
+        Self {
+            eye: Vec3::Z,
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            projection: Projection::Orthographic { height: 2.0 },
+            aspect: 1.0,
+            near: 0.0,
+            far: 2.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn perspective(eye: Vec3, target: Vec3, fov_y: f32) -> Self {
+        Self {
+            eye,
+            target,
+            projection: Projection::Perspective { fov_y },
+            near: 0.1,
+            far: 100.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn orthographic(eye: Vec3, target: Vec3, height: f32) -> Self {
+        Self {
+            eye,
+            target,
+            projection: Projection::Orthographic { height },
+            near: 0.0,
+            far: 100.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_size(&mut self, size: PhysicalSize<u32>) {
+        if size.height > 0 {
+            self.aspect = size.width as f32 / size.height as f32;
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    /// Maps view space to clip space with depth from 0 at `near` to 1 at `far`
+    pub fn projection_matrix(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y } => {
+                Mat4::perspective_rh(fov_y, self.aspect, self.near, self.far)
+            }
+            Projection::Orthographic { height } => {
+                let (half_width, half_height) = (height * self.aspect / 2.0, height / 2.0);
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view()
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            view_projection: self.view_projection().to_cols_array_2d(),
+            eye: self.eye.extend(1.0).to_array(),
+        }
+    }
+
+    /// The ray through a pixel of the window (y down) as an origin and a unit direction
+    pub fn ray(&self, pixel: [f32; 2], size: [f32; 2]) -> (Vec3, Vec3) {
+        let ndc = Vec2::new(
+            pixel[0] / size[0] * 2.0 - 1.0,
+            1.0 - pixel[1] / size[1] * 2.0,
+        );
+        let inverse = self.view_projection().inverse();
+        let near = inverse.project_point3(ndc.extend(0.0));
+        let far = inverse.project_point3(ndc.extend(1.0));
+        (near, (far - near).normalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(camera: &Camera, point: Vec3) -> Vec3 {
+        camera.view_projection().project_point3(point)
+    }
+
+    #[test]
+    fn test_default_matches_flat_view() {
+        let mut camera = Camera::default();
+        camera.set_size(PhysicalSize::new(200, 100));
+
+        assert!(clip(&camera, Vec3::ZERO).abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-6));
+        // Two units tall, four wide, nearer points have smaller depth
+        assert!(clip(&camera, Vec3::new(2.0, 1.0, 1.0)).abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+        assert!((clip(&camera, Vec3::new(0.0, 0.0, -1.0)).z - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_perspective() {
+        let camera = Camera::perspective(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, 1.0);
+        let near = clip(&camera, Vec3::new(0.5, 0.0, 1.0));
+        let far = clip(&camera, Vec3::new(0.5, 0.0, -1.0));
+        assert!(near.z < far.z);
+        assert!(near.z >= 0.0 && far.z <= 1.0);
+        // Further away looks smaller
+        assert!(near.x > far.x);
+
+        // The ray through the middle of the window runs along the view direction
+        let (origin, direction) = camera.ray([50.0, 50.0], [100.0, 100.0]);
+        assert!(direction.abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!((origin.z - 4.9).abs() < 1e-4);
+    }
+}
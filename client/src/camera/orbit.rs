@@ -0,0 +1,148 @@
+use std::f32::consts::FRAC_PI_2;
+
+use glam::Vec3;
+
+use super::Camera;
+use crate::engine::{EventResponse, InputEvent, PointerButton};
+
+/// Radians turned per pixel dragged
+const ROTATION_PER_PIXEL: f32 = 0.008;
+/// Distance factor applied per line scrolled
+const ZOOM_PER_LINE: f32 = 1.1;
+/// Keeps the camera from flipping over the poles
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Circles a camera around a target, dragging with the left mouse button
+/// rotates and scrolling moves closer or further away
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitController {
+    pub target: Vec3,
+    /// Angle around the y axis, 0 looks from +z
+    pub yaw: f32,
+    /// Angle above the xz plane
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    dragging: bool,
+}
+
+impl OrbitController {
+    pub fn new(distance: f32) -> Self {
+        Self {
+            target: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance,
+            min_distance: distance / 10.0,
+            max_distance: distance * 10.0,
+            dragging: false,
+        }
+    }
+
+    pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        let direction = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        self.target + direction * self.distance
+    }
+
+    /// Points `camera` at the target from the current angles
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye();
+        camera.target = self.target;
+        camera.up = Vec3::Y;
+    }
+
+    pub fn rotate(&mut self, delta: [f32; 2]) {
+        self.yaw -= delta[0] * ROTATION_PER_PIXEL;
+        self.pitch = (self.pitch + delta[1] * ROTATION_PER_PIXEL).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Positive lines move closer
+    pub fn zoom(&mut self, lines: f32) {
+        self.distance =
+            (self.distance / ZOOM_PER_LINE.powf(lines)).clamp(self.min_distance, self.max_distance);
+    }
+
+    pub fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match *event {
+            InputEvent::PointerButton {
+                button: PointerButton::Left,
+                pressed: true,
+                ..
+            } => self.dragging = true,
+            InputEvent::PointerMoved { delta, .. } if self.dragging => self.rotate(delta),
+            InputEvent::Scroll { delta, .. } => self.zoom(delta[1]),
+            // Any release ends the drag, the engine passes them on even when consumed
+            InputEvent::PointerButton { pressed: false, .. } | InputEvent::PointerLeft => {
+                self.dragging = false;
+                return EventResponse::Ignored;
+            }
+            _ => return EventResponse::Ignored,
+        }
+        EventResponse::Consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orbit() {
+        let mut orbit = OrbitController::new(5.0);
+        assert!(orbit.eye().abs_diff_eq(Vec3::new(0.0, 0.0, 5.0), 1e-6));
+
+        // Moving the pointer without a button held does nothing
+        let moved = InputEvent::PointerMoved {
+            position: [10.0, 0.0],
+            delta: [-100.0, 0.0],
+        };
+        assert_eq!(orbit.input(&moved), EventResponse::Ignored);
+        orbit.input(&InputEvent::PointerButton {
+            button: PointerButton::Left,
+            pressed: true,
+            position: [0.0, 0.0],
+        });
+        assert_eq!(orbit.input(&moved), EventResponse::Consumed);
+        assert!((orbit.yaw - 0.8).abs() < 1e-6);
+        assert!((orbit.eye().length() - 5.0).abs() < 1e-5);
+        assert!(orbit.eye().x > 0.0);
+
+        // Releasing any button or leaving the window ends the drag
+        orbit.input(&InputEvent::PointerButton {
+            button: PointerButton::Right,
+            pressed: false,
+            position: [0.0, 0.0],
+        });
+        assert_eq!(orbit.input(&moved), EventResponse::Ignored);
+        orbit.input(&InputEvent::PointerButton {
+            button: PointerButton::Left,
+            pressed: true,
+            position: [0.0, 0.0],
+        });
+        orbit.input(&InputEvent::PointerLeft);
+        assert_eq!(orbit.input(&moved), EventResponse::Ignored);
+        assert!((orbit.yaw - 0.8).abs() < 1e-6);
+
+        // Dragging far down stops short of the pole
+        orbit.rotate([0.0, 1000.0]);
+        assert_eq!(orbit.pitch, MAX_PITCH);
+
+        orbit.zoom(100.0);
+        assert_eq!(orbit.distance, 0.5);
+
+        let mut camera = Camera::default();
+        orbit.apply(&mut camera);
+        assert_eq!(camera.eye, orbit.eye());
+    }
+}
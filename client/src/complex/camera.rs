@@ -0,0 +1,125 @@
+/// What `complex.wgsl` sees of the camera
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default, PartialEq)]
+pub struct CameraUniform {
+    pub center: [f32; 2],
+    /// Half the width of the visible region of the plane
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl CameraUniform {
+    /// Maps a fragment coordinate (pixels, y down) to a point on the complex plane (y up)
+    pub fn screen_to_plane(&self, pixel: [f32; 2], size: [f32; 2]) -> [f32; 2] {
+        let offset = [pixel[0] - size[0] / 2.0, -(pixel[1] - size[1] / 2.0)];
+        let local = offset.map(|x| x / size[0] * 2.0 * self.scale);
+        let [x, y] = rotate(local, self.rotation);
+        [x + self.center[0], y + self.center[1]]
+    }
+}
+
+/// 2D camera looking at the complex plane
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera2D {
+    pub center: [f32; 2],
+    pub zoom: f32,
+    pub rotation: f32,
+    /// Half width of the view at zoom 1, comes from the graphed function
+    pub base_scale: f32,
+}
+
+impl Camera2D {
+    pub fn new(base_scale: f32) -> Self {
+        Self {
+            center: [0.0, 0.0],
+            zoom: 1.0,
+            rotation: 0.0,
+            base_scale,
+        }
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            center: self.center,
+            scale: self.base_scale / self.zoom,
+            rotation: self.rotation,
+        }
+    }
+
+    pub fn screen_to_plane(&self, pixel: [f32; 2], size: [f32; 2]) -> [f32; 2] {
+        self.uniform().screen_to_plane(pixel, size)
+    }
+
+    /// Moves the view so the plane follows a drag of `delta` pixels
+    pub fn pan(&mut self, delta: [f32; 2], size: [f32; 2]) {
+        let scale = self.base_scale / self.zoom;
+        let local = [delta[0], -delta[1]].map(|x| x / size[0] * 2.0 * scale);
+        let [dx, dy] = rotate(local, self.rotation);
+        self.center = [self.center[0] - dx, self.center[1] - dy];
+    }
+
+    /// Zooms by `factor` keeping the point under `pixel` fixed
+    pub fn zoom_at(&mut self, factor: f32, pixel: [f32; 2], size: [f32; 2]) {
+        let before = self.screen_to_plane(pixel, size);
+        self.zoom = (self.zoom * factor).clamp(1e-4, 1e5);
+        let after = self.screen_to_plane(pixel, size);
+        self.center = [
+            self.center[0] + before[0] - after[0],
+            self.center[1] + before[1] - after[1],
+        ];
+    }
+
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation = (self.rotation + angle).rem_euclid(std::f32::consts::TAU);
+    }
+}
+
+fn rotate([x, y]: [f32; 2], angle: f32) -> [f32; 2] {
+    let (s, c) = angle.sin_cos();
+    [c * x - s * y, s * x + c * y]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: [f32; 2] = [200.0, 100.0];
+
+    fn assert_close(a: [f32; 2], b: [f32; 2]) {
+        assert!(
+            (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_screen_to_plane() {
+        let mut camera = Camera2D::new(2.0);
+        assert_close(camera.screen_to_plane([100.0, 50.0], SIZE), [0.0, 0.0]);
+        assert_close(camera.screen_to_plane([200.0, 0.0], SIZE), [2.0, 1.0]);
+
+        camera.rotate(std::f32::consts::FRAC_PI_2);
+        assert_close(camera.screen_to_plane([200.0, 50.0], SIZE), [0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_zoom_keeps_cursor_fixed() {
+        let mut camera = Camera2D::new(2.0);
+        camera.rotate(0.3);
+        let cursor = [30.0, 80.0];
+        let before = camera.screen_to_plane(cursor, SIZE);
+        camera.zoom_at(2.5, cursor, SIZE);
+        assert_close(camera.screen_to_plane(cursor, SIZE), before);
+        assert_eq!(camera.uniform().scale, 0.8);
+    }
+
+    #[test]
+    fn test_pan_follows_drag() {
+        let mut camera = Camera2D::new(2.0);
+        let grabbed = camera.screen_to_plane([120.0, 40.0], SIZE);
+        camera.pan([-15.0, 25.0], SIZE);
+        assert_close(camera.screen_to_plane([105.0, 65.0], SIZE), grabbed);
+    }
+}
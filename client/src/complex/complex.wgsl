@@ -17,6 +17,15 @@ struct Uniforms {
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+struct Camera {
+    center: vec2<f32>,
+    scale: f32,
+    rotation: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
 @vertex
 fn vs_main(
     model: VertexInput,
@@ -177,8 +186,56 @@ fn tan_z(x: f32, y: f32) -> vec2<f32> {
 	return vec2(u / phi, v / phi);
 }
 
-fn foo(x: f32, y: f32) -> vec2<f32> {
-	return vec2(y * x * x, x / 2.0);
+fn c_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+	return vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+fn c_div(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+	let denom = b.x * b.x + b.y * b.y;
+	return vec2(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / denom;
+}
+
+fn rotate(p: vec2<f32>, angle: f32) -> vec2<f32> {
+	let s = sin(angle);
+	let c = cos(angle);
+	return vec2(c * p.x - s * p.y, s * p.x + c * p.y);
+}
+
+// Helpers taking z as a single vec2, used by expressions compiled from complex::expr
+fn c_exp(z: vec2<f32>) -> vec2<f32> {
+	return e_z(z.x, z.y);
+}
+
+fn c_ln(z: vec2<f32>) -> vec2<f32> {
+	let rt = xy_to_rt(z.x, z.y);
+	return ln_z(rt.x, rt.y);
+}
+
+fn c_sin(z: vec2<f32>) -> vec2<f32> {
+	return sin_z(z.x, z.y);
+}
+
+fn c_cos(z: vec2<f32>) -> vec2<f32> {
+	return cos_z(z.x, z.y);
+}
+
+fn c_tan(z: vec2<f32>) -> vec2<f32> {
+	return tan_z(z.x, z.y);
+}
+
+fn c_pow_real(z: vec2<f32>, n: f32) -> vec2<f32> {
+	let rt = xy_to_rt(z.x, z.y);
+	return z_n(rt.x, rt.y, n);
+}
+
+// a^b = e^(b * ln(a)), principal branch
+fn c_pow(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+	return c_exp(c_mul(b, c_ln(a)));
+}
+
+// The function being graphed, spliced in by ComplexFunction::wgsl_body
+fn f(z: vec2<f32>) -> vec2<f32> {
+	{{function}}
 }
 
 @fragment
@@ -187,8 +244,6 @@ fn fs_main(
 ) -> @location(0) vec4<f32> {
     let time = uniforms.run_time * 10.0;
     let time2 = mod_n(uniforms.run_time / 10.0, 1.0);
-	let power = -2.0; // * sin(uniforms.run_time / 8.0);
-    let scale = pow(10.0, 1.0 / power);
 
 	let offset = vec2(uniforms.width / 2.0, uniforms.height / 2.0);
 
@@ -199,12 +254,10 @@ fn fs_main(
 	// Multi sample loop
 	for (var i: i32 = -1; i <= 1; i++) {
 		for (var j: i32 = -1; j <= 1; j++) {
-			let x = ((pos.x + (factor * f32(i))) / uniforms.width) * 2.0 * scale;
-			let y = ((pos.y + (factor * f32(j))) / uniforms.width) * 2.0 * scale;
-			let rt = xy_to_rt(x, y);
-			// let xy = z_n(x, y, 2.0);
-			let out = z_n(rt.x, rt.y, power);
-			// let out = e_z(x, y);
+			// Screen y points down, the imaginary axis points up
+			let p = vec2(pos.x + (factor * f32(i)), -(pos.y + (factor * f32(j))));
+			let z = rotate((p / uniforms.width) * 2.0 * camera.scale, camera.rotation) + camera.center;
+			let out = f(z);
 			uv.x += norm_mod_n(out.x, n);
 			uv.y += norm_mod_n(out.y, n);
 		}
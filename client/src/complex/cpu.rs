@@ -0,0 +1,551 @@
+//! CPU mirrors of the helpers in `complex.wgsl`. Everything here uses `f32` and
+//! follows the shader line for line so it can act as an oracle for the GPU output
+//! and as a software renderer when no adapter is available.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::camera::CameraUniform;
+use super::expr::{BinOp, Expr, Func};
+use super::{ComplexFunction, ComplexParam};
+use crate::engine::FrameCapture;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const I: Self = Self::new(0.0, 1.0);
+
+    pub const fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn norm(self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+
+    pub fn arg(self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn exp(self) -> Self {
+        e_z(self.re, self.im)
+    }
+
+    pub fn ln(self) -> Self {
+        let [r, t] = xy_to_rt(self.re, self.im);
+        ln_z(r, t)
+    }
+
+    pub fn sin(self) -> Self {
+        sin_z(self.re, self.im)
+    }
+
+    pub fn cos(self) -> Self {
+        cos_z(self.re, self.im)
+    }
+
+    pub fn tan(self) -> Self {
+        tan_z(self.re, self.im)
+    }
+
+    pub fn powf(self, n: f32) -> Self {
+        let [r, t] = xy_to_rt(self.re, self.im);
+        z_n(r, t, n)
+    }
+
+    /// Principal branch, e^(w ln z)
+    pub fn powc(self, w: Self) -> Self {
+        (w * self.ln()).exp()
+    }
+}
+
+impl From<ComplexParam> for Complex {
+    fn from([re, im]: ComplexParam) -> Self {
+        Self::new(re, im)
+    }
+}
+
+impl From<Complex> for ComplexParam {
+    fn from(z: Complex) -> Self {
+        [z.re, z.im]
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let denom = rhs.re * rhs.re + rhs.im * rhs.im;
+        Self::new(
+            (self.re * rhs.re + self.im * rhs.im) / denom,
+            (self.im * rhs.re - self.re * rhs.im) / denom,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+// f(x, n) = (x mod n) / n
+pub fn norm_mod_n(x: f32, n: f32) -> f32 {
+    mod_n(x, n) / n
+}
+
+pub fn mod_n(x: f32, n: f32) -> f32 {
+    let y = x - (x / n).floor() * n;
+    if y < 0.0 {
+        y + n
+    } else {
+        y
+    }
+}
+
+// f(r, t, n) = (r^n)*e^(i*n*t)
+pub fn z_n(r: f32, t: f32, n: f32) -> Complex {
+    Complex::new(r.powf(n) * (n * t).cos(), r.powf(n) * (n * t).sin())
+}
+
+pub fn ln_z(r: f32, t: f32) -> Complex {
+    Complex::new(r.ln(), t)
+}
+
+// f(x, y) = e^(x + iy)
+pub fn e_z(x: f32, y: f32) -> Complex {
+    Complex::new(x.exp() * y.cos(), x.exp() * y.sin())
+}
+
+// Converts rectangular to polar
+pub fn xy_to_rt(x: f32, y: f32) -> [f32; 2] {
+    [(x * x + y * y).powf(0.5), y.atan2(x)]
+}
+
+pub fn cos_z(x: f32, y: f32) -> Complex {
+    Complex::new(x.cos() * y.cosh(), -x.sin() * y.sinh())
+}
+
+pub fn sin_z(x: f32, y: f32) -> Complex {
+    Complex::new(x.sin() * y.cosh(), x.cos() * y.sinh())
+}
+
+pub fn tan_z(x: f32, y: f32) -> Complex {
+    let u = x.cos() * x.sin();
+    let v = y.cosh() * y.sinh();
+    let phi = x.cos().powi(2) * y.cosh().powi(2) + x.sin().powi(2) * y.sinh().powi(2);
+    Complex::new(u / phi, v / phi)
+}
+
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let cmin = r.min(g).min(b);
+    let cmax = r.max(g).max(b);
+    let delta = cmax - cmin;
+
+    let mut hue = 0.0;
+    if delta != 0.0 {
+        hue = if cmax == r {
+            (g - b) / delta
+        } else if cmax == g {
+            2.0 + (b - r) / delta
+        } else {
+            4.0 + (r - g) / delta
+        };
+        hue /= 6.0;
+        if hue < 0.0 {
+            hue += 1.0;
+        }
+    }
+
+    let saturation = if cmax != 0.0 { delta / cmax } else { 0.0 };
+    [hue, saturation, cmax]
+}
+
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let c = v * s;
+    let hs = h * 6.0;
+    let x = c * (1.0 - (mod_n(hs, 2.0) - 1.0).abs());
+
+    let [r, g, b] = if hs < 1.0 {
+        [c, x, 0.0]
+    } else if hs < 2.0 {
+        [x, c, 0.0]
+    } else if hs < 3.0 {
+        [0.0, c, x]
+    } else if hs < 4.0 {
+        [0.0, x, c]
+    } else if hs < 5.0 {
+        [x, 0.0, c]
+    } else {
+        [c, 0.0, x]
+    };
+
+    let m = v - c;
+    [r + m, g + m, b + m]
+}
+
+impl ComplexFunction {
+    /// Evaluates the function exactly as the compiled shader would
+    pub fn eval(&self, z: Complex) -> Complex {
+        match self {
+            Self::Power(n) => z.powf(*n),
+            Self::Exp => z.exp(),
+            Self::Log => z.ln(),
+            Self::Sin => z.sin(),
+            Self::Cos => z.cos(),
+            Self::Tan => z.tan(),
+            Self::Mobius { a, b, c, d } => {
+                (Complex::from(*a) * z + Complex::from(*b))
+                    / (Complex::from(*c) * z + Complex::from(*d))
+            }
+            Self::Polynomial(coefficients) => coefficients
+                .iter()
+                .rev()
+                .fold(Complex::default(), |w, c| w * z + Complex::from(*c)),
+            Self::Expression(expr) => expr.eval(z),
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression the same way `to_wgsl` compiles it
+    pub fn eval(&self, z: Complex) -> Complex {
+        match self {
+            Self::Z => z,
+            Self::Const(c) => Complex::from(*c),
+            Self::Neg(a) => -a.eval(z),
+            Self::Binary(op, a, b) => match op {
+                BinOp::Add => a.eval(z) + b.eval(z),
+                BinOp::Sub => a.eval(z) - b.eval(z),
+                BinOp::Mul => a.eval(z) * b.eval(z),
+                BinOp::Div => a.eval(z) / b.eval(z),
+                BinOp::Pow => match b.as_ref() {
+                    Self::Const([n, im]) if *im == 0.0 => a.eval(z).powf(*n),
+                    _ => a.eval(z).powc(b.eval(z)),
+                },
+            },
+            Self::Call(func, a) => {
+                let a = a.eval(z);
+                match func {
+                    Func::Exp => a.exp(),
+                    Func::Ln => a.ln(),
+                    Func::Sin => a.sin(),
+                    Func::Cos => a.cos(),
+                    Func::Tan => a.tan(),
+                    Func::Sqrt => a.powf(0.5),
+                }
+            }
+        }
+    }
+}
+
+/// Mirror of `fs_main`, returns the linear color of the pixel at `frag_coord`
+pub fn shade(
+    function: &ComplexFunction,
+    camera: &CameraUniform,
+    frag_coord: [f32; 2],
+    width: f32,
+    height: f32,
+) -> [f32; 3] {
+    let n = 1.0;
+
+    let mut uv = [0.0; 2];
+    for z in pixel_samples(camera, frag_coord, width, height) {
+        let out = function.eval(z);
+        uv[0] += norm_mod_n(out.re, n);
+        uv[1] += norm_mod_n(out.im, n);
+    }
+    let [u, v] = uv.map(|x| x / 9.0);
+
+    let color = [
+        u.powf(2.0) * v.powf(0.0),
+        u.powf(0.0) * v.powf(2.0),
+        u.powf(0.0) * v.powf(0.0),
+    ];
+    let hsv = rgb_to_hsv(color);
+    hsv_to_rgb([mod_n(hsv[0], 1.0), hsv[1], hsv[2]])
+}
+
+/// The points of the plane `fs_main` averages over for one pixel, a 3 by 3 grid
+pub fn pixel_samples(
+    camera: &CameraUniform,
+    frag_coord: [f32; 2],
+    width: f32,
+    height: f32,
+) -> impl Iterator<Item = Complex> + '_ {
+    let factor = 1.0 / 3.0;
+    (-1..=1).flat_map(move |i| {
+        (-1..=1).map(move |j| {
+            let sample = [
+                frag_coord[0] + factor * i as f32,
+                frag_coord[1] + factor * j as f32,
+            ];
+            let [x, y] = camera.screen_to_plane(sample, [width, height]);
+            Complex::new(x, y)
+        })
+    })
+}
+
+/// Software fallback for `ComplexGrapher`, produces the same pixels the GPU writes
+/// into an sRGB render target
+pub fn render(
+    function: &ComplexFunction,
+    camera: &CameraUniform,
+    width: u32,
+    height: u32,
+) -> FrameCapture {
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for py in 0..height {
+        for px in 0..width {
+            // Fragment coordinates sit at pixel centers
+            let frag_coord = [px as f32 + 0.5, py as f32 + 0.5];
+            let rgb = shade(function, camera, frag_coord, width as f32, height as f32);
+            pixels.extend(rgb.map(linear_to_srgb));
+            pixels.push(255);
+        }
+    }
+
+    FrameCapture {
+        width,
+        height,
+        pixels,
+    }
+}
+
+fn linear_to_srgb(x: f32) -> u8 {
+    let x = x.clamp(0.0, 1.0);
+    let srgb = if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::complex::camera::Camera2D;
+    use std::f32::consts::PI;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_close(a: Complex, b: Complex) {
+        let diff = (a - b).norm();
+        assert!(diff < EPSILON * (1.0 + b.norm()), "{:?} != {:?}", a, b);
+    }
+
+    fn samples() -> Vec<Complex> {
+        let mut samples = vec![];
+        for x in -4..=4 {
+            for y in -4..=4 {
+                samples.push(Complex::new(x as f32 * 0.37 + 0.01, y as f32 * 0.29 - 0.02));
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_exp_and_log_identities() {
+        assert_close(Complex::new(0.0, PI).exp(), Complex::new(-1.0, 0.0));
+        samples().into_iter().for_each(|z| {
+            assert_close(z.ln().exp(), z);
+            assert_close(
+                (z + Complex::new(1.0, 0.5)).exp(),
+                z.exp() * Complex::new(1.0, 0.5).exp(),
+            );
+        });
+    }
+
+    #[test]
+    fn test_trig_identities() {
+        let one = Complex::new(1.0, 0.0);
+        samples().into_iter().for_each(|z| {
+            let (s, c) = (z.sin(), z.cos());
+            assert_close(s * s + c * c, one);
+            // Verifies tan_z against sin_z / cos_z
+            assert_close(z.tan(), s / c);
+            // sin(z) = (e^iz - e^-iz) / 2i
+            let iz = Complex::I * z;
+            assert_close(
+                s,
+                (iz.exp() - (-iz).exp()) / (Complex::I * Complex::new(2.0, 0.0)),
+            );
+        });
+    }
+
+    #[test]
+    fn test_powers() {
+        samples().into_iter().for_each(|z| {
+            assert_close(z.powf(2.0), z * z);
+            assert_close(z.powf(-2.0), one_over(z * z));
+            assert_close(z.powf(0.5) * z.powf(0.5), z);
+            assert_close(z.powc(Complex::new(3.0, 0.0)), z * z * z);
+        });
+
+        let [r, t] = xy_to_rt(-1.0, 1.0);
+        assert!((r - 2f32.sqrt()).abs() < EPSILON);
+        assert!((t - 3.0 * PI / 4.0).abs() < EPSILON);
+    }
+
+    fn one_over(z: Complex) -> Complex {
+        Complex::new(1.0, 0.0) / z
+    }
+
+    #[test]
+    fn test_norm_mod_n() {
+        assert!((norm_mod_n(2.5, 1.0) - 0.5).abs() < EPSILON);
+        assert!((norm_mod_n(-0.25, 1.0) - 0.75).abs() < EPSILON);
+        assert!((norm_mod_n(7.0, 4.0) - 0.75).abs() < EPSILON);
+        assert!((mod_n(-5.0, 3.0) - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_expression_matches_builtin() {
+        let mobius = ComplexFunction::Mobius {
+            a: [1.0, 0.0],
+            b: [1.0, 0.0],
+            c: [1.0, 0.0],
+            d: [0.0, -1.0],
+        };
+        let expr = ComplexFunction::Expression(Expr::parse("(z + 1)/(z - i)").unwrap());
+        let polynomial = ComplexFunction::Polynomial(vec![[1.0, 0.0], [0.0, 2.0], [1.0, 0.0]]);
+        let poly_expr = ComplexFunction::Expression(Expr::parse("z^2 + 2iz + 1").unwrap());
+
+        samples().into_iter().for_each(|z| {
+            assert_close(expr.eval(z), mobius.eval(z));
+            assert_close(poly_expr.eval(z), polynomial.eval(z));
+        });
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        [[1.0, 0.0, 0.0], [0.2, 0.4, 0.6], [0.9, 0.9, 0.1]]
+            .into_iter()
+            .for_each(|rgb| {
+                let back = hsv_to_rgb(rgb_to_hsv(rgb));
+                rgb.iter()
+                    .zip(back)
+                    .for_each(|(a, b)| assert!((a - b).abs() < EPSILON));
+            });
+    }
+
+    /// Whether a sample of the pixel lands so close to a jump in the coloring, at
+    /// a pole, a branch cut or where a component crosses an integer, that rounding
+    /// can put the GPU and the CPU on different sides of it
+    fn near_jump(function: &ComplexFunction, camera: &CameraUniform, frag_coord: [f32; 2]) -> bool {
+        const TOLERANCE: f32 = 1e-5;
+        let (width, height) = (64.0, 48.0);
+        // A thousandth of a pixel in the plane
+        let step = 0.002 * camera.scale / width;
+        let fractions = |z: Complex| {
+            let out = function.eval(z);
+            [out.re, out.im].map(|x| (x, norm_mod_n(x, 1.0)))
+        };
+        pixel_samples(camera, frag_coord, width, height).any(|z| {
+            let here = fractions(z);
+            let on_integer = here.iter().any(|&(x, fraction)| {
+                !x.is_finite() || fraction.min(1.0 - fraction) < TOLERANCE * x.abs().max(1.0)
+            });
+            let jumps = [[step, 0.0], [-step, 0.0], [0.0, step], [0.0, -step]]
+                .into_iter()
+                .any(|[dx, dy]| {
+                    let there = fractions(z + Complex::new(dx, dy));
+                    here.iter()
+                        .zip(there)
+                        .any(|((_, a), (_, b))| (a - b).abs() > 0.1)
+                });
+            on_integer || jumps
+        })
+    }
+
+    #[test]
+    fn test_gpu_parity() {
+        use crate::complex::ComplexGrapher;
+        use crate::engine::test_engine;
+
+        let (width, height) = (64, 48);
+        let functions = [
+            ComplexFunction::default(),
+            ComplexFunction::Exp,
+            ComplexFunction::Log,
+            ComplexFunction::Tan,
+            ComplexFunction::Expression(Expr::parse("(z^2 + 1)/(z - i)").unwrap()),
+        ];
+
+        functions.into_iter().for_each(|function| {
+            let Some(mut engine) = test_engine(width, height) else {
+                return;
+            };
+            let mut grapher = ComplexGrapher::with_function(&engine, function.clone());
+            grapher.set_show_caption(false);
+            engine.add_engine_object(Box::new(grapher));
+            engine.render_frame().unwrap();
+
+            let gpu = engine.capture_frame().unwrap();
+            let camera = Camera2D::new(function.scale()).uniform();
+            let cpu = render(&function, &camera, width, height);
+
+            let mismatched = gpu
+                .pixels
+                .chunks(4)
+                .zip(cpu.pixels.chunks(4))
+                .enumerate()
+                .filter(|(_, (a, b))| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > 8))
+                .filter(|(i, _)| {
+                    let frag_coord = [
+                        (i % width as usize) as f32 + 0.5,
+                        (i / width as usize) as f32 + 0.5,
+                    ];
+                    !near_jump(&function, &camera, frag_coord)
+                })
+                .count();
+            assert_eq!(
+                mismatched, 0,
+                "{:?}: {} pixels away from any jump differ",
+                function, mismatched
+            );
+        });
+    }
+
+    #[test]
+    fn test_software_render() {
+        let function = ComplexFunction::default();
+        let camera = Camera2D::new(function.scale()).uniform();
+        let capture = render(&function, &camera, 16, 8);
+        assert_eq!(capture.pixels.len(), 16 * 8 * 4);
+        assert!(capture.pixels.chunks(4).all(|pixel| pixel[3] == 255));
+    }
+}
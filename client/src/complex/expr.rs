@@ -0,0 +1,586 @@
+//! Parses user typed expressions in `z` such as `(z^2 + 1)/(z - i)` or `exp(sin(z))`
+//! and compiles them into WGSL using the complex helpers in `complex.wgsl`.
+//!
+//! Grammar, lowest to highest precedence:
+//! ```text
+//! expr    := term (('+' | '-') term)*
+//! term    := unary (('*' | '/') unary | unary)*   // juxtaposition multiplies, `2z`
+//! unary   := '-' unary | power
+//! power   := primary ('^' unary)?                 // right associative
+//! primary := number | 'z' | 'i' | 'e' | 'pi' | func '(' expr ')' | '(' expr ')'
+//! ```
+
+use std::fmt;
+
+use super::function::latex_complex;
+use super::ComplexParam;
+use crate::text::math;
+
+/// Every name the parser understands, constants and functions
+const NAMES: &[&str] = &[
+    "z", "i", "e", "pi", "exp", "ln", "log", "sin", "cos", "tan", "sqrt",
+];
+
+/// Deeper expressions are rejected so the generated shader stays reasonable. A
+/// level here nests at most two levels of LaTeX, so captions always typeset.
+const MAX_DEPTH: usize = math::MAX_DEPTH / 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Func {
+    Exp,
+    Ln,
+    Sin,
+    Cos,
+    Tan,
+    Sqrt,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exp" => Some(Self::Exp),
+            "ln" | "log" => Some(Self::Ln),
+            "sin" => Some(Self::Sin),
+            "cos" => Some(Self::Cos),
+            "tan" => Some(Self::Tan),
+            "sqrt" => Some(Self::Sqrt),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Exp => "exp",
+            Self::Ln => "ln",
+            Self::Sin => "sin",
+            Self::Cos => "cos",
+            Self::Tan => "tan",
+            Self::Sqrt => "sqrt",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinOp {
+    fn symbol(&self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
+            Self::Pow => '^',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Z,
+    Const(ComplexParam),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(Func, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprError {
+    Empty,
+    UnexpectedChar(char, usize),
+    UnexpectedToken(String, usize),
+    UnexpectedEnd,
+    UnknownIdentifier(String, usize),
+    ExpectedCall(String, usize),
+    TooDeep,
+    NonFinite(usize),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "expression is empty"),
+            Self::UnexpectedChar(c, at) => write!(f, "unexpected character '{}' at {}", c, at),
+            Self::UnexpectedToken(token, at) => write!(f, "unexpected '{}' at {}", token, at),
+            Self::UnexpectedEnd => write!(f, "expression ended unexpectedly"),
+            Self::UnknownIdentifier(name, at) => write!(f, "unknown name '{}' at {}", name, at),
+            Self::ExpectedCall(name, at) => write!(f, "expected '(' after '{}' at {}", name, at),
+            Self::TooDeep => write!(f, "expression is nested too deeply"),
+            Self::NonFinite(at) => write!(f, "number at {} is too large", at),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(ExprError::Empty);
+        }
+
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((token, at)) => Err(ExprError::UnexpectedToken(token.to_string(), at)),
+        }
+    }
+
+    /// A WGSL expression of type `vec2<f32>` in terms of `z`
+    pub fn to_wgsl(&self) -> String {
+        match self {
+            Self::Z => "z".to_string(),
+            Self::Const([re, im]) => format!("vec2({:?}, {:?})", re, im),
+            Self::Neg(a) => format!("(-{})", a.to_wgsl()),
+            Self::Binary(op, a, b) => match op {
+                BinOp::Add => format!("({} + {})", a.to_wgsl(), b.to_wgsl()),
+                BinOp::Sub => format!("({} - {})", a.to_wgsl(), b.to_wgsl()),
+                BinOp::Mul => format!("c_mul({}, {})", a.to_wgsl(), b.to_wgsl()),
+                BinOp::Div => format!("c_div({}, {})", a.to_wgsl(), b.to_wgsl()),
+                BinOp::Pow => match b.as_ref() {
+                    // Real exponents avoid the log and behave better at the origin
+                    Self::Const([n, im]) if *im == 0.0 => {
+                        format!("c_pow_real({}, {:?})", a.to_wgsl(), n)
+                    }
+                    _ => format!("c_pow({}, {})", a.to_wgsl(), b.to_wgsl()),
+                },
+            },
+            Self::Call(func, a) => match func {
+                Func::Sqrt => format!("c_pow_real({}, 0.5)", a.to_wgsl()),
+                Func::Exp => format!("c_exp({})", a.to_wgsl()),
+                Func::Ln => format!("c_ln({})", a.to_wgsl()),
+                Func::Sin => format!("c_sin({})", a.to_wgsl()),
+                Func::Cos => format!("c_cos({})", a.to_wgsl()),
+                Func::Tan => format!("c_tan({})", a.to_wgsl()),
+            },
+        }
+    }
+
+    /// The expression in the LaTeX subset understood by `text::math`
+    pub fn to_latex(&self) -> String {
+        match self {
+            Self::Z => "z".to_string(),
+            Self::Const(c) => latex_complex(*c),
+            Self::Neg(a) => format!("-{}", a.latex_factor()),
+            Self::Binary(op, a, b) => match op {
+                BinOp::Add => format!("{} + {}", a.to_latex(), b.to_latex()),
+                BinOp::Sub => format!("{} - {}", a.to_latex(), b.latex_factor()),
+                BinOp::Mul => {
+                    let right = b.latex_factor();
+                    // Juxtaposition would run two numbers together
+                    let separator = if right.starts_with(|c: char| c.is_ascii_digit()) {
+                        " \\cdot "
+                    } else {
+                        " "
+                    };
+                    format!("{}{}{}", a.latex_operand(BinOp::Mul), separator, right)
+                }
+                BinOp::Div => format!("\\frac{{{}}}{{{}}}", a.to_latex(), b.to_latex()),
+                BinOp::Pow => format!("{}^{{{}}}", a.latex_operand(BinOp::Pow), b.to_latex()),
+            },
+            Self::Call(Func::Sqrt, a) => format!("\\sqrt{{{}}}", a.to_latex()),
+            Self::Call(Func::Exp, a) => format!("e^{{{}}}", a.to_latex()),
+            Self::Call(func, a) => format!("\\{}({})", func.name(), a.to_latex()),
+        }
+    }
+
+    /// LaTeX for this expression as an operand of `op`, parenthesized when it binds looser
+    fn latex_operand(&self, op: BinOp) -> String {
+        let needs_parens = match self {
+            Self::Binary(BinOp::Add | BinOp::Sub, ..) => true,
+            Self::Const([re, im]) if *re != 0.0 && *im != 0.0 => true,
+            Self::Const([re, im]) => op == BinOp::Pow && (*re < 0.0 || *im != 0.0),
+            Self::Binary(..) | Self::Neg(_) | Self::Call(Func::Exp | Func::Sqrt, _) => {
+                op == BinOp::Pow
+            }
+            Self::Z | Self::Call(..) => false,
+        };
+        if needs_parens {
+            format!("({})", self.to_latex())
+        } else {
+            self.to_latex()
+        }
+    }
+
+    /// LaTeX for this expression following a product or a minus sign, where a
+    /// leading sign of its own would read as subtraction
+    fn latex_factor(&self) -> String {
+        let operand = self.latex_operand(BinOp::Mul);
+        if operand.starts_with('-') {
+            format!("({})", operand)
+        } else {
+            operand
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Z => write!(f, "z"),
+            Self::Const([re, im]) => {
+                if *im == 0.0 {
+                    write!(f, "{}", re)
+                } else if *re == 0.0 {
+                    write!(f, "{}i", im)
+                } else {
+                    write!(f, "({} + {}i)", re, im)
+                }
+            }
+            Self::Neg(a) => write!(f, "-({})", a),
+            Self::Binary(op, a, b) => write!(f, "({} {} {})", a, op.symbol(), b),
+            Self::Call(func, a) => write!(f, "{}({})", func.name(), a),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Ident(name) => write!(f, "{}", name),
+            Self::Op(c) => write!(f, "{}", c),
+            Self::Open => write!(f, "("),
+            Self::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ExprError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<f32>()
+                    .map_err(|_| ExprError::UnexpectedToken(text.clone(), start))?;
+                if !value.is_finite() {
+                    return Err(ExprError::NonFinite(start));
+                }
+                tokens.push((Token::Number(value), start));
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut end = i;
+                while end < chars.len() && chars[end].is_ascii_alphabetic() {
+                    end += 1;
+                }
+                let word: String = chars[i..end].iter().collect::<String>().to_lowercase();
+                // Letters run together multiply, `2iz` is `2 * i * z`, so split off
+                // the longest known name rather than taking the whole word
+                let name = NAMES
+                    .iter()
+                    .filter(|name| word.starts_with(*name))
+                    .max_by_key(|name| name.len())
+                    .ok_or(ExprError::UnknownIdentifier(word, start))?;
+                tokens.push((Token::Ident(name.to_string()), start));
+                i += name.len();
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push((Token::Op(c), start));
+                i += 1;
+            }
+            '(' => {
+                tokens.push((Token::Open, start));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::Close, start));
+                i += 1;
+            }
+            _ => return Err(ExprError::UnexpectedChar(c, start)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(&Token, usize)> {
+        self.tokens.get(self.position).map(|(t, at)| (t, *at))
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), ExprError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or(ExprError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some((Token::Op(c), _)) if ops.contains(c) => {
+                let c = *c;
+                self.position += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExprError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ExprError::TooDeep);
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, ExprError> {
+        self.enter()?;
+        let mut lhs = self.term()?;
+        // Each operator nests the terms before it one level deeper
+        let mut folds = 0;
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            self.enter()?;
+            folds += 1;
+            let rhs = self.term()?;
+            let op = if op == '+' { BinOp::Add } else { BinOp::Sub };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth -= folds + 1;
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, ExprError> {
+        let mut lhs = self.unary()?;
+        let mut folds = 0;
+        loop {
+            let op = match self.eat_op(&['*', '/']) {
+                Some('/') => BinOp::Div,
+                Some(_) => BinOp::Mul,
+                // Implicit multiplication, `2z` or `z(z + 1)`
+                None => match self.peek() {
+                    Some((Token::Number(_), _))
+                    | Some((Token::Ident(_), _))
+                    | Some((Token::Open, _)) => BinOp::Mul,
+                    _ => break,
+                },
+            };
+            self.enter()?;
+            folds += 1;
+            let rhs = self.unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth -= folds;
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExprError> {
+        if self.eat_op(&['-']).is_some() {
+            self.enter()?;
+            let inner = self.unary()?;
+            self.depth -= 1;
+            return Ok(match inner {
+                Expr::Const([re, im]) => Expr::Const([-re, -im]),
+                inner => Expr::Neg(Box::new(inner)),
+            });
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, ExprError> {
+        let base = self.primary()?;
+        if self.eat_op(&['^']).is_some() {
+            self.enter()?;
+            let exponent = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        let (token, at) = self.next()?;
+        match token {
+            Token::Number(n) => Ok(Expr::Const([n, 0.0])),
+            Token::Open => {
+                let inner = self.expr()?;
+                match self.next()? {
+                    (Token::Close, _) => Ok(inner),
+                    (token, at) => Err(ExprError::UnexpectedToken(token.to_string(), at)),
+                }
+            }
+            Token::Ident(name) => match name.as_str() {
+                "z" => Ok(Expr::Z),
+                "i" => Ok(Expr::Const([0.0, 1.0])),
+                "e" => Ok(Expr::Const([std::f32::consts::E, 0.0])),
+                "pi" => Ok(Expr::Const([std::f32::consts::PI, 0.0])),
+                _ => match Func::from_name(&name) {
+                    Some(func) => match self.peek() {
+                        Some((Token::Open, _)) => {
+                            self.position += 1;
+                            let arg = self.expr()?;
+                            match self.next()? {
+                                (Token::Close, _) => Ok(Expr::Call(func, Box::new(arg))),
+                                (token, at) => {
+                                    Err(ExprError::UnexpectedToken(token.to_string(), at))
+                                }
+                            }
+                        }
+                        _ => Err(ExprError::ExpectedCall(name, at)),
+                    },
+                    None => Err(ExprError::UnknownIdentifier(name, at)),
+                },
+            },
+            token => Err(ExprError::UnexpectedToken(token.to_string(), at)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(op: BinOp, a: Expr, b: Expr) -> Expr {
+        Expr::Binary(op, Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr = Expr::parse("(z^2 + 1)/(z - i)").unwrap();
+        let expected = binary(
+            BinOp::Div,
+            binary(
+                BinOp::Add,
+                binary(BinOp::Pow, Expr::Z, Expr::Const([2.0, 0.0])),
+                Expr::Const([1.0, 0.0]),
+            ),
+            binary(BinOp::Sub, Expr::Z, Expr::Const([0.0, 1.0])),
+        );
+        assert_eq!(expr, expected);
+
+        // Unary minus binds looser than ^ and exponents are right associative
+        assert_eq!(
+            Expr::parse("-z^2^3").unwrap().to_string(),
+            "-((z ^ (2 ^ 3)))"
+        );
+        assert_eq!(
+            Expr::parse("2z(z+1)").unwrap().to_string(),
+            "((2 * z) * (z + 1))"
+        );
+        assert_eq!(Expr::parse("z^-2").unwrap().to_string(), "(z ^ -2)");
+        assert_eq!(Expr::parse("2iz").unwrap().to_string(), "((2 * 1i) * z)");
+        assert_eq!(Expr::parse("piz").unwrap().to_string(), "(3.1415927 * z)");
+    }
+
+    #[test]
+    fn test_parse_functions() {
+        let expr = Expr::parse("exp(sin(z))").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Call(
+                Func::Exp,
+                Box::new(Expr::Call(Func::Sin, Box::new(Expr::Z)))
+            )
+        );
+        assert_eq!(expr.to_wgsl(), "c_exp(c_sin(z))");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Expr::parse("  "), Err(ExprError::Empty));
+        assert_eq!(Expr::parse("z +"), Err(ExprError::UnexpectedEnd));
+        assert_eq!(Expr::parse("z $ 2"), Err(ExprError::UnexpectedChar('$', 2)));
+        assert_eq!(
+            Expr::parse("foo(z)"),
+            Err(ExprError::UnknownIdentifier("foo".to_string(), 0))
+        );
+        assert_eq!(
+            Expr::parse("sin z"),
+            Err(ExprError::ExpectedCall("sin".to_string(), 0))
+        );
+        assert_eq!(Expr::parse("(z + 1"), Err(ExprError::UnexpectedEnd));
+        assert_eq!(
+            Expr::parse("z)"),
+            Err(ExprError::UnexpectedToken(")".to_string(), 1))
+        );
+        let deep = format!("{}z{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(Expr::parse(&deep), Err(ExprError::TooDeep));
+        // Long flat chains nest just as deep
+        let long = vec!["z"; 10_000].join("+");
+        assert_eq!(Expr::parse(&long), Err(ExprError::TooDeep));
+        assert_eq!(Expr::parse(&"z".repeat(10_000)), Err(ExprError::TooDeep));
+        assert!(Expr::parse(&vec!["z"; 40].join("+")).is_ok());
+    }
+
+    #[test]
+    fn test_deepest_expressions_typeset() {
+        let font = crate::text::Font::bundled();
+        let shapes: [fn(String) -> String; 6] = [
+            |inner| format!("1/({inner})"),
+            |inner| format!("sqrt({inner})"),
+            |inner| format!("exp({inner})"),
+            |inner| format!("z^({inner})"),
+            |inner| format!("-({inner})"),
+            |inner| format!("z + ({inner})*z"),
+        ];
+        for shape in shapes {
+            // Wrap until the parser gives up, the last accepted one must still typeset
+            let mut source = "z".to_string();
+            let mut deepest = None;
+            while let Ok(expr) = Expr::parse(&source) {
+                deepest = Some(expr);
+                source = shape(source);
+            }
+            let latex = deepest.unwrap().to_latex();
+            assert!(math::layout(&font, &latex, 20.0).is_ok(), "{}", latex);
+        }
+    }
+
+    #[test]
+    fn test_wgsl_emission() {
+        let expr = Expr::parse("(z^2 + 1)/(z - i)").unwrap();
+        assert_eq!(
+            expr.to_wgsl(),
+            "c_div((c_pow_real(z, 2.0) + vec2(1.0, 0.0)), (z - vec2(0.0, 1.0)))"
+        );
+        assert_eq!(
+            Expr::parse("z^-2").unwrap().to_wgsl(),
+            "c_pow_real(z, -2.0)"
+        );
+        assert_eq!(
+            Expr::parse("z^i").unwrap().to_wgsl(),
+            "c_pow(z, vec2(0.0, 1.0))"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use super::expr::Expr;
+
+/// Complex numbers are passed to the shader as `[re, im]`
+pub type ComplexParam = [f32; 2];
+
+/// Functions the grapher knows how to plot, each one is compiled into the
+/// `f(z)` slot of `complex.wgsl` when the render pipeline is built
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComplexFunction {
+    /// z^n for a real n
+    Power(f32),
+    Exp,
+    Log,
+    Sin,
+    Cos,
+    Tan,
+    /// (az + b) / (cz + d)
+    Mobius {
+        a: ComplexParam,
+        b: ComplexParam,
+        c: ComplexParam,
+        d: ComplexParam,
+    },
+    /// Coefficients in increasing degree, c0 + c1 z + c2 z^2 + ...
+    Polynomial(Vec<ComplexParam>),
+    /// A user typed expression, see `complex::expr`
+    Expression(Expr),
+}
+
+impl Default for ComplexFunction {
+    fn default() -> Self {
+        Self::Power(-2.0)
+    }
+}
+
+impl ComplexFunction {
+    /// WGSL statements making up the body of `fn f(z: vec2<f32>) -> vec2<f32>`
+    pub fn wgsl_body(&self) -> String {
+        match self {
+            Self::Power(n) => format!(
+                "let rt = xy_to_rt(z.x, z.y);\n\treturn z_n(rt.x, rt.y, {});",
+                float(*n)
+            ),
+            Self::Exp => "return e_z(z.x, z.y);".to_string(),
+            Self::Log => "let rt = xy_to_rt(z.x, z.y);\n\treturn ln_z(rt.x, rt.y);".to_string(),
+            Self::Sin => "return sin_z(z.x, z.y);".to_string(),
+            Self::Cos => "return cos_z(z.x, z.y);".to_string(),
+            Self::Tan => "return tan_z(z.x, z.y);".to_string(),
+            Self::Mobius { a, b, c, d } => format!(
+                "return c_div(c_mul({}, z) + {}, c_mul({}, z) + {});",
+                complex(a),
+                complex(b),
+                complex(c),
+                complex(d)
+            ),
+            Self::Polynomial(coefficients) => {
+                // Horner's method, starting from the highest degree coefficient
+                let mut body = String::from("var w = vec2<f32>();");
+                coefficients.iter().rev().for_each(|c| {
+                    body.push_str(&format!("\n\tw = c_mul(w, z) + {};", complex(c)));
+                });
+                body.push_str("\n\treturn w;");
+                body
+            }
+            Self::Expression(expr) => format!("return {};", expr.to_wgsl()),
+        }
+    }
+
+    /// `f(z) = ...` in the LaTeX subset understood by `text::math`, for captions
+    pub fn latex(&self) -> String {
+        let body = match self {
+            Self::Power(n) => format!("z^{{{}}}", latex_real(*n)),
+            Self::Exp => "e^{z}".to_string(),
+            Self::Log => "\\ln z".to_string(),
+            Self::Sin => "\\sin z".to_string(),
+            Self::Cos => "\\cos z".to_string(),
+            Self::Tan => "\\tan z".to_string(),
+            Self::Mobius { a, b, c, d } => {
+                format!("\\frac{{{}}}{{{}}}", latex_linear(a, b), latex_linear(c, d))
+            }
+            Self::Polynomial(coefficients) => {
+                let terms: Vec<_> = coefficients
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(_, c)| **c != [0.0, 0.0])
+                    .map(|(k, c)| latex_term(c, k))
+                    .collect();
+                latex_sum(&terms)
+            }
+            Self::Expression(expr) => expr.to_latex(),
+        };
+        format!("f(z) = {}", body)
+    }
+
+    /// Half width of the region of the plane that shows the function best
+    pub fn scale(&self) -> f32 {
+        match self {
+            Self::Power(n) if *n != 0.0 => 10f32.powf(1.0 / n),
+            Self::Exp | Self::Sin | Self::Cos | Self::Tan => std::f32::consts::PI,
+            _ => 2.0,
+        }
+    }
+
+    /// Fills in the templated parts of `complex.wgsl` for this function
+    pub fn shader_source(&self) -> String {
+        include_str!("./complex.wgsl").replace("{{function}}", &self.wgsl_body())
+    }
+}
+
+/// Formats a float so WGSL never mistakes it for an integer literal
+fn float(x: f32) -> String {
+    format!("{:?}", x)
+}
+
+fn complex(c: &ComplexParam) -> String {
+    format!("vec2({}, {})", float(c[0]), float(c[1]))
+}
+
+/// Shortest form of a real for display, `2` rather than `2.0`
+fn latex_real(x: f32) -> String {
+    format!("{}", x)
+}
+
+/// A complex constant for display, `2`, `-i`, `3i` or `1 + 2i`
+pub(super) fn latex_complex([re, im]: ComplexParam) -> String {
+    let imaginary = |im: f32| match im {
+        1.0 => "i".to_string(),
+        -1.0 => "-i".to_string(),
+        im => format!("{}i", latex_real(im)),
+    };
+    match (re, im) {
+        (re, 0.0) => latex_real(re),
+        (0.0, im) => imaginary(im),
+        (re, im) if im < 0.0 => format!("{} - {}", latex_real(re), imaginary(-im)),
+        (re, im) => format!("{} + {}", latex_real(re), imaginary(im)),
+    }
+}
+
+/// `c z^k` with unit coefficients and powers left out
+fn latex_term(c: &ComplexParam, k: usize) -> String {
+    let power = match (k, *c) {
+        (0, [re, im]) if re != 0.0 && im != 0.0 => return format!("({})", latex_complex(*c)),
+        (0, _) => return latex_complex(*c),
+        (1, _) => "z".to_string(),
+        (k, _) => format!("z^{{{}}}", k),
+    };
+    match *c {
+        [1.0, 0.0] => power,
+        [-1.0, 0.0] => format!("-{}", power),
+        [_, 0.0] | [0.0, _] => format!("{}{}", latex_complex(*c), power),
+        _ => format!("({}){}", latex_complex(*c), power),
+    }
+}
+
+/// `az + b` for the Möbius numerator and denominator
+fn latex_linear(a: &ComplexParam, b: &ComplexParam) -> String {
+    let terms: Vec<_> = [(a, 1), (b, 0)]
+        .into_iter()
+        .filter(|(c, _)| **c != [0.0, 0.0])
+        .map(|(c, k)| latex_term(c, k))
+        .collect();
+    latex_sum(&terms)
+}
+
+/// Joins terms with `+`, or `-` when a term is negative
+fn latex_sum(terms: &[String]) -> String {
+    let Some((first, rest)) = terms.split_first() else {
+        return "0".to_string();
+    };
+    let mut sum = first.clone();
+    for term in rest {
+        match term.strip_prefix('-') {
+            Some(negated) => sum.push_str(&format!(" - {}", negated)),
+            None => sum.push_str(&format!(" + {}", term)),
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_functions_compile() {
+        let functions = [
+            ComplexFunction::Power(-2.0),
+            ComplexFunction::Power(3.0),
+            ComplexFunction::Exp,
+            ComplexFunction::Log,
+            ComplexFunction::Sin,
+            ComplexFunction::Cos,
+            ComplexFunction::Tan,
+            ComplexFunction::Mobius {
+                a: [1.0, 0.0],
+                b: [0.0, 1.0],
+                c: [1.0, 0.0],
+                d: [0.0, -1.0],
+            },
+            ComplexFunction::Polynomial(vec![[1.0, 0.0], [0.0, 0.0], [1.0, 0.0]]),
+            ComplexFunction::Polynomial(vec![]),
+            ComplexFunction::Expression(Expr::parse("(z^2 + 1)/(z - i)").unwrap()),
+            ComplexFunction::Expression(Expr::parse("exp(sin(z)) - z^i + sqrt(2pi)").unwrap()),
+            ComplexFunction::Expression(Expr::parse("-ln(-z)").unwrap()),
+        ];
+
+        functions
+            .iter()
+            .for_each(|function| crate::engine::validate_shader(&function.shader_source()));
+    }
+
+    #[test]
+    fn test_latex_captions() {
+        let font = crate::text::Font::bundled();
+        let cases = [
+            (ComplexFunction::Power(-2.0), "f(z) = z^{-2}"),
+            (ComplexFunction::Sin, "f(z) = \\sin z"),
+            (
+                ComplexFunction::Mobius {
+                    a: [1.0, 0.0],
+                    b: [0.0, 0.0],
+                    c: [2.0, 0.0],
+                    d: [0.0, -1.0],
+                },
+                "f(z) = \\frac{z}{2z - i}",
+            ),
+            (
+                ComplexFunction::Polynomial(vec![[-1.0, 2.0], [0.0, 0.0], [-3.0, 0.0]]),
+                "f(z) = -3z^{2} + (-1 + 2i)",
+            ),
+            (
+                ComplexFunction::Expression(Expr::parse("(z^2 + 1)/(z - i) - 2sqrt(z)").unwrap()),
+                "f(z) = \\frac{z^{2} + 1}{z - i} - 2 \\sqrt{z}",
+            ),
+            (
+                ComplexFunction::Expression(Expr::parse("exp(-z)^2 * 3").unwrap()),
+                "f(z) = (e^{-z})^{2} \\cdot 3",
+            ),
+            (
+                ComplexFunction::Expression(Expr::parse("-2z * -2 - -z * -i").unwrap()),
+                "f(z) = -2 z (-2) - (-z (-i))",
+            ),
+        ];
+
+        for (function, expected) in cases {
+            let latex = function.latex();
+            assert_eq!(latex, expected);
+            crate::text::math::layout(&font, &latex, 20.0).unwrap();
+        }
+    }
+}
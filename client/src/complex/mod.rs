@@ -1,7 +1,25 @@
+mod camera;
+pub mod cpu;
+pub mod expr;
+mod function;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+use winit::event::TouchPhase;
 
-use crate::engine::{Engine, EngineObject, RenderData};
+use crate::engine::{
+    overlay_depth_state, Engine, EngineObject, EventResponse, InputEvent, PointerButton,
+    RenderData, UpdateContext,
+};
+use crate::text::{HAlign, Text, TextRenderer, VAlign};
 use crate::types::Vertex2;
+pub use camera::{Camera2D, CameraUniform};
+pub use cpu::Complex;
+use expr::{Expr, ExprError};
+pub use function::{ComplexFunction, ComplexParam};
 
 #[rustfmt::skip]
 const SCREEN: &[Vertex2] = &[
@@ -13,58 +31,107 @@ const SCREEN: &[Vertex2] = &[
 
 const SCREEN_INDICES: &[u16] = &[0, 3, 2, 2, 1, 0];
 
+/// Zoom factor applied per line scrolled
+const ZOOM_PER_LINE: f32 = 1.1;
+/// Radians rotated per pixel dragged with the right mouse button
+const ROTATION_PER_PIXEL: f32 = 0.005;
+/// Distance of the formula caption from the bottom left corner, in pixels
+const CAPTION_MARGIN: f32 = 16.0;
+const CAPTION_SIZE: f32 = 28.0;
+
+/// Handle for changing the graphed function from other objects, usually the
+/// scene's panel. The grapher switches to it on its next update, keeping the view.
+#[derive(Clone, Default)]
+pub struct ComplexControls(Rc<RefCell<Option<ComplexFunction>>>);
+
+impl ComplexControls {
+    pub fn set_function(&self, function: ComplexFunction) {
+        *self.0.borrow_mut() = Some(function);
+    }
+
+    /// Parses a user typed expression in `z` and graphs it if it parses
+    pub fn set_expression(&self, input: &str) -> Result<(), ExprError> {
+        self.set_function(ComplexFunction::Expression(Expr::parse(input)?));
+        Ok(())
+    }
+}
+
 pub struct ComplexGrapher {
+    function: ComplexFunction,
+    controls: ComplexControls,
+    camera: Camera2D,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_dirty: bool,
+    dragging: Option<PointerButton>,
+    touch: Option<[f32; 2]>,
+    size: [f32; 2],
+    render_pipeline_layout: wgpu::PipelineLayout,
     render_pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
+    caption: TextRenderer,
+    caption_dirty: bool,
+    show_caption: bool,
 }
 
 impl ComplexGrapher {
     pub fn new(engine: &Engine) -> Self {
+        Self::with_function(engine, ComplexFunction::default())
+    }
+
+    pub fn with_function(engine: &Engine, function: ComplexFunction) -> Self {
         let device = engine.device();
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./complex.wgsl"));
+
+        let camera = Camera2D::new(function.scale());
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Complex Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera.uniform()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("complex_camera_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("complex_camera_bind_group"),
+        });
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Complex Layout"),
-                bind_group_layouts: &[engine.uniform_bind_group()],
+                bind_group_layouts: &[engine.uniform_bind_group(), &camera_bind_group_layout],
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Complex Graph Descriptor"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex2::desc()],
-            },
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: engine.sample_count(),
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: *engine.surface_format(),
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
+        let format = engine.surface_format();
+        let sample_count = engine.sample_count();
+        let render_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            format,
+            sample_count,
+            &function,
+        );
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Clear Screen"),
@@ -78,22 +145,225 @@ impl ComplexGrapher {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        let size = engine.size();
+
         Self {
+            function,
+            controls: ComplexControls::default(),
+            camera,
+            camera_buffer,
+            camera_bind_group,
+            camera_dirty: false,
+            dragging: None,
+            touch: None,
+            size: [size.width as f32, size.height as f32],
+            render_pipeline_layout,
             render_pipeline,
+            format,
+            sample_count,
             vertex_buffer,
             index_buffer,
+            caption: TextRenderer::new(engine),
+            caption_dirty: true,
+            show_caption: true,
         }
     }
+
+    pub fn function(&self) -> &ComplexFunction {
+        &self.function
+    }
+
+    pub fn controls(&self) -> ComplexControls {
+        self.controls.clone()
+    }
+
+    /// Switches the graphed function, recompiling the shader with the new `f(z)`
+    pub fn set_function(&mut self, device: &wgpu::Device, function: ComplexFunction) {
+        self.render_pipeline = create_pipeline(
+            device,
+            &self.render_pipeline_layout,
+            self.format,
+            self.sample_count,
+            &function,
+        );
+        self.camera.base_scale = function.scale();
+        self.camera_dirty = true;
+        self.caption_dirty = true;
+        self.function = function;
+    }
+
+    /// Whether `f(z) = ...` is typeset in the bottom left corner
+    pub fn set_show_caption(&mut self, show: bool) {
+        self.show_caption = show;
+    }
+
+    pub fn camera(&self) -> &Camera2D {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera2D {
+        self.camera_dirty = true;
+        &mut self.camera
+    }
+
+    pub fn reset_camera(&mut self) {
+        self.camera = Camera2D::new(self.function.scale());
+        self.camera_dirty = true;
+    }
+
+    /// Parses and graphs a user typed expression in `z`, leaving the current
+    /// function in place if it doesn't parse
+    pub fn set_expression(&mut self, device: &wgpu::Device, input: &str) -> Result<(), ExprError> {
+        let expr = Expr::parse(input)?;
+        self.set_function(device, ComplexFunction::Expression(expr));
+        Ok(())
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    function: &ComplexFunction,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("complex.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(function.shader_source().into()),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Complex Graph Descriptor"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex2::desc()],
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(overlay_depth_state()),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    })
 }
 
 impl EngineObject for ComplexGrapher {
-    fn render(&self) -> Option<RenderData> {
+    fn update(&mut self, context: &UpdateContext) {
+        let requested = self.controls.0.borrow_mut().take();
+        if let Some(function) = requested {
+            self.set_function(context.device, function);
+        }
+        if self.camera_dirty {
+            let uniform = self.camera.uniform();
+            context
+                .queue
+                .write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[uniform]));
+            self.camera_dirty = false;
+        }
+        if self.caption_dirty {
+            let caption = Text::math(
+                self.function.latex(),
+                [CAPTION_MARGIN, self.size[1] - CAPTION_MARGIN],
+            )
+            .with_size(CAPTION_SIZE)
+            .with_align(HAlign::Left, VAlign::Bottom);
+            self.caption
+                .prepare(context.device, context.queue, &[&[caption]]);
+            self.caption_dirty = false;
+        }
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = [size.width as f32, size.height as f32];
+        self.caption_dirty = true;
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match *event {
+            InputEvent::PointerMoved { delta, .. } => match self.dragging {
+                Some(PointerButton::Left) => self.camera.pan(delta, self.size),
+                Some(PointerButton::Right) => self.camera.rotate(delta[0] * ROTATION_PER_PIXEL),
+                _ => return EventResponse::Ignored,
+            },
+            InputEvent::PointerButton {
+                button, pressed, ..
+            } => {
+                self.dragging = pressed.then_some(button);
+                return EventResponse::Consumed;
+            }
+            InputEvent::PointerLeft => {
+                self.dragging = None;
+                return EventResponse::Ignored;
+            }
+            InputEvent::Scroll { delta, position } => {
+                self.camera
+                    .zoom_at(ZOOM_PER_LINE.powf(delta[1]), position, self.size);
+            }
+            // Single finger drags pan like the left mouse button
+            InputEvent::Touch {
+                phase, position, ..
+            } => match phase {
+                TouchPhase::Started => {
+                    self.touch = Some(position);
+                    return EventResponse::Consumed;
+                }
+                TouchPhase::Moved => {
+                    let Some(last) = self.touch.replace(position) else {
+                        return EventResponse::Ignored;
+                    };
+                    let delta = [position[0] - last[0], position[1] - last[1]];
+                    self.camera.pan(delta, self.size);
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    self.touch = None;
+                    return EventResponse::Consumed;
+                }
+            },
+            _ => return EventResponse::Ignored,
+        }
+        self.camera_dirty = true;
+        EventResponse::Consumed
+    }
+
+    fn render(&self) -> Option<RenderData<'_>> {
         Some(RenderData {
             render_pipeline: &self.render_pipeline,
-            vertex_buffer: &self.vertex_buffer,
+            bind_groups: vec![&self.camera_bind_group],
+            vertex_buffers: vec![&self.vertex_buffer],
+            instance_buffer: None,
             index_buffer: Some(&self.index_buffer),
+            index_format: wgpu::IndexFormat::Uint16,
             num_vertices: 4,
             num_indices: 6,
+            instances: 0..1,
         })
     }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        self.render()
+            .into_iter()
+            .chain(self.caption.render_data(0).filter(|_| self.show_caption))
+            .collect()
+    }
 }
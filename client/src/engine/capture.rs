@@ -0,0 +1,199 @@
+#[allow(unused_imports)]
+use tracing::{error, warn};
+use wgpu::TextureFormat;
+
+/// A rendered frame read back from the GPU, stored as tightly packed RGBA8 rows
+pub struct FrameCapture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl FrameCapture {
+    /// Builds a capture from a mapped copy buffer whose rows are padded to
+    /// `padded_bytes_per_row`, swapping channels if the source format was BGRA
+    pub fn from_padded(
+        data: &[u8],
+        width: u32,
+        height: u32,
+        padded_bytes_per_row: u32,
+        format: TextureFormat,
+    ) -> Self {
+        let bytes_per_row = (width * 4) as usize;
+        let mut pixels = Vec::with_capacity(bytes_per_row * height as usize);
+        data.chunks(padded_bytes_per_row as usize)
+            .take(height as usize)
+            .for_each(|row| pixels.extend_from_slice(&row[..bytes_per_row]));
+
+        if is_bgra(format) {
+            pixels
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut bytes = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(bytes)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let bytes = self.to_png().map_err(std::io::Error::other)?;
+        std::fs::write(path, bytes)
+    }
+}
+
+/// A rendered texture copied into a buffer the CPU can map, waiting to be read back
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Readback {
+    buffer: wgpu::Buffer,
+    extent: wgpu::Extent3d,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Readback {
+    /// Encodes a copy of `texture`, which needs `COPY_SRC` usage.
+    /// Returns `None` if its format can't be encoded as 8 bit RGBA.
+    pub fn encode(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Option<Self> {
+        let format = texture.format();
+        if !is_capturable(format) {
+            warn!("Can't capture frames with format {:?}", format);
+            return None;
+        }
+
+        let extent = texture.size();
+        let padded_bytes_per_row = padded_bytes_per_row(extent.width);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Buffer"),
+            size: (padded_bytes_per_row * extent.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            extent,
+        );
+
+        Some(Self {
+            buffer,
+            extent,
+            padded_bytes_per_row,
+            format,
+        })
+    }
+
+    /// Waits for the copy, so the encoder must have been submitted, and reads the pixels out
+    pub fn read(self, device: &wgpu::Device) -> Option<FrameCapture> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        if let Err(e) = receiver.recv().ok()? {
+            error!("Failed to map capture buffer: {:?}", e);
+            return None;
+        }
+
+        let capture = FrameCapture::from_padded(
+            &slice.get_mapped_range(),
+            self.extent.width,
+            self.extent.height,
+            self.padded_bytes_per_row,
+            self.format,
+        );
+        self.buffer.unmap();
+        Some(capture)
+    }
+}
+
+/// Formats that can be read back and encoded as 8 bit RGBA
+pub fn is_capturable(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb
+    )
+}
+
+fn is_bgra(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    )
+}
+
+/// Rows copied out of a texture must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+pub fn padded_bytes_per_row(width: u32) -> u32 {
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (width * 4).div_ceil(alignment) * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding_and_swizzle() {
+        let padded = padded_bytes_per_row(2);
+        assert_eq!(padded, 256);
+
+        let mut data = vec![0u8; (padded * 2) as usize];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[256..264].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+
+        let capture = FrameCapture::from_padded(&data, 2, 2, padded, TextureFormat::Bgra8Unorm);
+        assert_eq!(
+            capture.pixels,
+            [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let capture = FrameCapture {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let bytes = capture.to_png().unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(&buf[..info.buffer_size()], capture.pixels.as_slice());
+    }
+}
@@ -0,0 +1,211 @@
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
+    VirtualKeyCode, WindowEvent,
+};
+
+/// Pixels treated as one line when a device reports scrolling in pixels
+const PIXELS_PER_LINE: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+/// Window input normalized into something objects can react to.
+/// Positions are in physical pixels from the top left of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    PointerMoved {
+        position: [f32; 2],
+        delta: [f32; 2],
+    },
+    PointerButton {
+        button: PointerButton,
+        pressed: bool,
+        position: [f32; 2],
+    },
+    PointerLeft,
+    /// Scroll amount in lines, positive y scrolls up
+    Scroll {
+        delta: [f32; 2],
+        position: [f32; 2],
+    },
+    Key {
+        key: VirtualKeyCode,
+        pressed: bool,
+        modifiers: ModifiersState,
+    },
+    Text(char),
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        position: [f32; 2],
+    },
+}
+
+impl InputEvent {
+    /// Releases and the pointer leaving end drags, so every object should see them
+    /// even after one has consumed them, or the others would keep dragging
+    pub fn ends_drag(&self) -> bool {
+        matches!(
+            self,
+            Self::PointerButton { pressed: false, .. }
+                | Self::PointerLeft
+                | Self::Touch {
+                    phase: TouchPhase::Ended | TouchPhase::Cancelled,
+                    ..
+                }
+        )
+    }
+}
+
+/// Whether an object used an event, consumed events aren't passed any further
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventResponse {
+    Consumed,
+    Ignored,
+}
+
+/// Tracks the state needed to turn winit events into `InputEvent`s
+#[derive(Default)]
+pub struct InputState {
+    /// Where the pointer was last seen, `None` until it moves over the window
+    cursor: Option<[f32; 2]>,
+    modifiers: ModifiersState,
+}
+
+impl InputState {
+    pub fn translate(&mut self, event: &WindowEvent) -> Option<InputEvent> {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = [position.x as f32, position.y as f32];
+                // The first move after entering the window has nothing to be relative to
+                let last = self.cursor.replace(position).unwrap_or(position);
+                let delta = [position[0] - last[0], position[1] - last[1]];
+                Some(InputEvent::PointerMoved { position, delta })
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                Some(InputEvent::PointerLeft)
+            }
+            WindowEvent::MouseInput { state, button, .. } => Some(InputEvent::PointerButton {
+                button: match button {
+                    MouseButton::Left => PointerButton::Left,
+                    MouseButton::Right => PointerButton::Right,
+                    MouseButton::Middle => PointerButton::Middle,
+                    MouseButton::Other(id) => PointerButton::Other(*id),
+                },
+                pressed: *state == ElementState::Pressed,
+                position: self.cursor.unwrap_or_default(),
+            }),
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [*x, *y],
+                    MouseScrollDelta::PixelDelta(position) => [
+                        position.x as f32 / PIXELS_PER_LINE,
+                        position.y as f32 / PIXELS_PER_LINE,
+                    ],
+                };
+                Some(InputEvent::Scroll {
+                    delta,
+                    position: self.cursor.unwrap_or_default(),
+                })
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+                None
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => Some(InputEvent::Key {
+                key: *key,
+                pressed: *state == ElementState::Pressed,
+                modifiers: self.modifiers,
+            }),
+            WindowEvent::ReceivedCharacter(c) if !c.is_control() => Some(InputEvent::Text(*c)),
+            WindowEvent::Touch(touch) => Some(InputEvent::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                position: [touch.location.x as f32, touch.location.y as f32],
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, TouchPhase};
+
+    #[test]
+    fn test_translate_pointer_events() {
+        let device_id = unsafe { DeviceId::dummy() };
+        let mut input = InputState::default();
+
+        #[allow(deprecated)]
+        let moved = |x, y| WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(x, y),
+            modifiers: ModifiersState::empty(),
+        };
+
+        // Nothing to measure the first move from
+        assert_eq!(
+            input.translate(&moved(10.0, 20.0)),
+            Some(InputEvent::PointerMoved {
+                position: [10.0, 20.0],
+                delta: [0.0, 0.0],
+            })
+        );
+        assert_eq!(
+            input.translate(&moved(15.0, 18.0)),
+            Some(InputEvent::PointerMoved {
+                position: [15.0, 18.0],
+                delta: [5.0, -2.0],
+            })
+        );
+
+        #[allow(deprecated)]
+        let scroll = WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 80.0)),
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::empty(),
+        };
+        assert_eq!(
+            input.translate(&scroll),
+            Some(InputEvent::Scroll {
+                delta: [0.0, 2.0],
+                position: [15.0, 18.0],
+            })
+        );
+
+        let left = WindowEvent::CursorLeft { device_id };
+        assert_eq!(input.translate(&left), Some(InputEvent::PointerLeft));
+        assert!(InputEvent::PointerLeft.ends_drag());
+        assert_eq!(
+            input.translate(&moved(300.0, 40.0)),
+            Some(InputEvent::PointerMoved {
+                position: [300.0, 40.0],
+                delta: [0.0, 0.0],
+            })
+        );
+
+        assert_eq!(input.translate(&WindowEvent::ReceivedCharacter('\r')), None);
+        assert_eq!(
+            input.translate(&WindowEvent::ReceivedCharacter('z')),
+            Some(InputEvent::Text('z'))
+        );
+    }
+}
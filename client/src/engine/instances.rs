@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use bytemuck::Pod;
+
+/// A vertex buffer of per-instance data that grows as more instances are written
+pub struct InstanceBuffer<T: Pod> {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    len: usize,
+    label: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> InstanceBuffer<T> {
+    pub fn new(device: &wgpu::Device, capacity: usize, label: &'static str) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            buffer: create_buffer(device, capacity * size_of::<T>(), label),
+            capacity,
+            len: 0,
+            label,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the contents, reallocating at double the size if it doesn't fit
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, instances: &[T]) {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = create_buffer(device, self.capacity * size_of::<T>(), self.label);
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(instances));
+        self.len = instances.len();
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Range to pass as `RenderData::instances`
+    pub fn instances(&self) -> std::ops::Range<u32> {
+        0..self.len as u32
+    }
+}
+
+fn create_buffer(device: &wgpu::Device, size: usize, label: &'static str) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: size as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
@@ -3,10 +3,28 @@ const WIDTH: u32 = 1800;
 #[cfg(target_arch = "wasm32")]
 const HEIGHT: u32 = 1000;
 
+mod capture;
+mod input;
+mod instances;
+mod scene;
+mod target;
 mod time;
 mod uniforms;
 
+use crate::text::{Font, TextResources};
+pub use capture::FrameCapture;
+#[cfg(not(target_arch = "wasm32"))]
+use capture::Readback;
+use input::InputState;
+pub use input::{EventResponse, InputEvent, PointerButton};
+pub use instances::InstanceBuffer;
+use scene::{FadeOverlay, SceneCommand, SceneStack};
+pub use scene::{Scene, SceneController, Transition};
+use std::cell::OnceCell;
 use std::iter;
+use std::ops::Range;
+use std::rc::Rc;
+use target::{OffscreenSurface, RenderTarget, WindowSurface};
 use time::Time;
 #[allow(unused_imports)]
 use tracing::{error, info, warn};
@@ -19,67 +37,33 @@ use winit::{
     window::{Window, WindowBuilder, WindowId},
 };
 
-pub struct EngineConfig {
-    pub msaa: Option<u32>,
-}
-
-struct Surface {
-    surface: wgpu::Surface,
-    multi_sampled_texture: Option<wgpu::TextureView>,
-    config: wgpu::SurfaceConfiguration,
-}
-
-impl Surface {
-    fn new(
-        surface: wgpu::Surface,
-        adapter: &Adapter,
-        size: PhysicalSize<u32>,
-        device: &Device,
-        engine_config: &EngineConfig,
-    ) -> Self {
-        let capabilities = surface.get_capabilities(adapter);
-        let surface_format = capabilities
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.describe().srgb)
-            .unwrap_or(capabilities.formats[0]);
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: capabilities.present_modes[0],
-            alpha_mode: capabilities.alpha_modes[0],
-            view_formats: vec![],
-        };
-        surface.configure(&device.device, &config);
-
-        let multi_sampled_texture = match engine_config.msaa {
-            Some(sample_count) => Some(create_multisampled_framebuffer(
-                &device.device,
-                &config,
-                sample_count,
-            )),
-            None => None,
-        };
-
-        Self {
-            surface,
-            multi_sampled_texture,
-            config,
-        }
+/// Format of the depth buffer every render target carries
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// Depth state for things drawn on top in order, like the UI and text.
+/// They ignore and leave the depth buffer alone.
+pub fn overlay_depth_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
     }
+}
 
-    fn resize(&mut self, new_size: &PhysicalSize<u32>, device: &Device) {
-        self.config.width = new_size.width;
-        self.config.height = new_size.height;
-        self.surface.configure(&device.device, &self.config);
+/// Depth state for geometry that hides what's behind it.
+/// Later draws win ties so flat shapes on the same plane stack in draw order.
+pub fn solid_depth_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::LessEqual,
+        ..overlay_depth_state()
     }
+}
 
-    fn get_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
-        self.surface.get_current_texture()
-    }
+pub struct EngineConfig {
+    pub msaa: Option<u32>,
 }
 
 struct Device {
@@ -121,15 +105,20 @@ impl Device {
 }
 
 pub struct Engine {
-    window: Window,
+    window: Option<Window>,
     event_loop: Option<EventLoop<()>>,
-    surface: Surface,
+    surface: Box<dyn RenderTarget>,
     device: Device,
     engine_objects: Vec<Box<dyn EngineObject>>,
+    scenes: SceneStack,
+    input: InputState,
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_requested: bool,
     time: Time,
     uniform_buffer: UniformBuffer,
     size: winit::dpi::PhysicalSize<u32>,
     config: EngineConfig,
+    text_resources: OnceCell<Rc<TextResources>>,
 }
 
 impl Engine {
@@ -139,38 +128,90 @@ impl Engine {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
         let surface = unsafe { instance.create_surface(&window).unwrap() };
-        let adapter = create_adapter(&surface, &instance).await;
+        let adapter = create_adapter(Some(&surface), &instance).await;
         let device = Device::new(&adapter).await;
         let engine_objects = vec![];
-        let surface = Surface::new(surface, &adapter, size, &device, &config);
+        let surface = Box::new(WindowSurface::new(
+            surface, &adapter, size, &device, &config,
+        ));
         let time = Time::new();
-        let uniform_buffer = UniformBuffer::new(&device.device, &window);
+        let uniform_buffer = UniformBuffer::new(&device.device, size);
 
         Self {
-            window,
+            window: Some(window),
             event_loop,
             surface,
             device,
             engine_objects,
+            scenes: SceneStack::default(),
+            input: InputState::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_requested: false,
             time,
             uniform_buffer,
             size,
             config,
+            text_resources: OnceCell::new(),
         }
     }
 
+    /// Creates an engine that renders into an offscreen texture instead of a window.
+    /// Falls back to a software adapter when no hardware adapter is available,
+    /// frames are produced by calling `render_frame` rather than `run`.
+    pub async fn new_headless(config: EngineConfig, width: u32, height: u32) -> Self {
+        Self::try_new_headless(config, width, height)
+            .await
+            .expect("Found no adapter, not even a software one")
+    }
+
+    /// Like `new_headless`, but gives up when there is no adapter at all
+    pub async fn try_new_headless(config: EngineConfig, width: u32, height: u32) -> Option<Self> {
+        let size = PhysicalSize::new(width, height);
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = find_adapter(None, &instance).await?;
+        let device = Device::new(&adapter).await;
+        let engine_objects = vec![];
+        let surface = Box::new(OffscreenSurface::new(size, &device, &config));
+        let time = Time::new();
+        let uniform_buffer = UniformBuffer::new(&device.device, size);
+
+        Some(Self {
+            window: None,
+            event_loop: None,
+            surface,
+            device,
+            engine_objects,
+            scenes: SceneStack::default(),
+            input: InputState::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_requested: false,
+            time,
+            uniform_buffer,
+            size,
+            config,
+            text_resources: OnceCell::new(),
+        })
+    }
+
     fn resize(&mut self, new_size: &winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = *new_size;
             self.surface.resize(new_size, &self.device);
             self.uniform_buffer.update_width(self.size.width as f32);
             self.uniform_buffer.update_height(self.size.height as f32);
+            self.scenes
+                .all_mut()
+                .chain(self.engine_objects.iter_mut())
+                .for_each(|object| object.resize(*new_size));
         }
     }
 
     pub fn run(mut self) {
         // Need to move event_loop out of self to allow self to move with Copying
-        let event_loop = self.event_loop.take().unwrap();
+        let event_loop = self
+            .event_loop
+            .take()
+            .expect("Headless engines have no event loop, use render_frame instead");
         event_loop.run(move |event, _, control_flow| match event {
             Event::WindowEvent { window_id, event } => {
                 self.handle_window_event(window_id, &event, control_flow);
@@ -179,23 +220,43 @@ impl Engine {
                 self.handle_redraw_requested(window_id, control_flow);
             }
             Event::MainEventsCleared => {
-                self.window.request_redraw();
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
             }
+            // The event loop never returns so Drop won't run, stop objects here instead
+            Event::LoopDestroyed => self.stop_engine_objects(),
             _ => (),
         });
     }
 
-    pub fn add_engine_object(&mut self, object: Box<dyn EngineObject>) {
+    /// Adds an object that stays across scene changes, drawn on top of every scene.
+    /// Its `start` is called first.
+    pub fn add_engine_object(&mut self, mut object: Box<dyn EngineObject>) {
+        object.start(self);
         self.engine_objects.push(object);
     }
 
+    /// Handle for pushing, popping and replacing scenes
+    pub fn scenes(&self) -> SceneController {
+        self.scenes.controller().clone()
+    }
+
+    /// Stops and drops every object, including those of every scene on the stack
+    pub fn stop_engine_objects(&mut self) {
+        self.scenes.stop_all();
+        self.engine_objects
+            .drain(..)
+            .for_each(|mut object| object.stop());
+    }
+
     fn handle_window_event(
         &mut self,
         window_id: WindowId,
         event: &WindowEvent,
         control_flow: &mut ControlFlow,
     ) {
-        if window_id == self.window.id() {
+        if self.is_window(window_id) {
             match event {
                 WindowEvent::Resized(physical_size) => {
                     self.resize(physical_size);
@@ -213,13 +274,85 @@ impl Engine {
                         },
                     ..
                 } => *control_flow = ControlFlow::Exit,
+                #[cfg(not(target_arch = "wasm32"))]
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F12),
+                            ..
+                        },
+                    ..
+                } => {
+                    if !self.surface.can_capture() {
+                        warn!("Screenshots aren't supported on this graphics backend");
+                    } else {
+                        // Saved from the next frame before it's presented
+                        self.screenshot_requested = true;
+                        if let Some(window) = &self.window {
+                            window.request_redraw();
+                        }
+                    }
+                }
                 _ => (),
             }
+
+            if let Some(input) = self.input.translate(event) {
+                self.dispatch_input(&input);
+            }
+        }
+    }
+
+    /// Objects drawn last are on top, so they get the first chance to consume input.
+    /// Scenes ignore input while a transition is running. Events that end drags reach
+    /// every object whether or not they're consumed.
+    fn dispatch_input(&mut self, event: &InputEvent) {
+        let broadcast = event.ends_drag();
+        for object in self.engine_objects.iter_mut().rev() {
+            if object.input(event) == EventResponse::Consumed && !broadcast {
+                return;
+            }
+        }
+        if self.scenes.is_fading() && !broadcast {
+            return;
+        }
+        for object in self.scenes.active_mut().rev() {
+            if object.input(event) == EventResponse::Consumed && !broadcast {
+                return;
+            }
         }
     }
 
+    /// Applies the scene changes that are due this frame
+    fn update_scenes(&mut self, delta_time: f32) {
+        for command in self.scenes.advance(delta_time) {
+            match command {
+                SceneCommand::Push(scene) => self.enter_scene(scene),
+                SceneCommand::Pop => {
+                    if !self.scenes.pop() {
+                        warn!("Tried to pop a scene with none on the stack");
+                    }
+                }
+                SceneCommand::Replace(scene) => {
+                    self.scenes.pop();
+                    self.enter_scene(scene);
+                }
+            }
+        }
+        if self.scenes.needs_overlay() {
+            let overlay = FadeOverlay::new(self);
+            self.scenes.set_overlay(overlay);
+        }
+    }
+
+    fn enter_scene(&mut self, mut scene: Box<dyn Scene>) {
+        let mut objects = scene.build(self, self.scenes.controller());
+        objects.iter_mut().for_each(|object| object.start(self));
+        self.scenes.push(scene, objects);
+    }
+
     fn handle_redraw_requested(&mut self, window_id: WindowId, control_flow: &mut ControlFlow) {
-        if window_id == self.window.id() {
+        if self.is_window(window_id) {
             let delta_time = self.time.tick();
             self.update(delta_time);
             match self.render() {
@@ -231,90 +364,198 @@ impl Engine {
         }
     }
 
+    fn is_window(&self, window_id: WindowId) -> bool {
+        self.window
+            .as_ref()
+            .is_some_and(|window| window.id() == window_id)
+    }
+
+    /// Advances time and draws a single frame, used to drive headless engines
+    pub fn render_frame(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let delta_time = self.time.tick();
+        self.update(delta_time);
+        self.render()
+    }
+
     fn update(&mut self, delta_time: f32) {
+        self.update_scenes(delta_time);
+
         self.uniform_buffer.update_run_time(self.time.run_time());
         self.uniform_buffer.update_delta_time(delta_time);
         self.uniform_buffer.write(&mut self.device.queue);
-        self.engine_objects
-            .iter_mut()
-            .for_each(|object| object.update());
+
+        let context = UpdateContext {
+            delta_time,
+            run_time: self.time.run_time(),
+            size: self.size,
+            device: &self.device.device,
+            queue: &self.device.queue,
+        };
+        self.scenes
+            .active_mut()
+            .chain(self.engine_objects.iter_mut())
+            .for_each(|object| object.update(&context));
+        self.scenes.update_overlay(&context);
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        #[cfg(not(target_arch = "wasm32"))]
+        let screenshot_requested = std::mem::take(&mut self.screenshot_requested);
 
+        let output = self.surface.get_frame()?;
         let mut encoder = self.device.create_encoder();
+        self.encode_render_pass(&mut encoder, &output.view);
+        #[cfg(not(target_arch = "wasm32"))]
+        let screenshot = match output.texture().or(self.surface.texture()) {
+            Some(texture) if screenshot_requested => {
+                Readback::encode(&self.device.device, &mut encoder, texture)
+            }
+            _ => None,
+        };
+        self.device.submit(iter::once(encoder.finish()));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(readback) = screenshot {
+            self.save_screenshot(readback);
+        }
+        output.present();
+        Ok(())
+    }
 
-        {
-            let color_attachment = match &self.surface.multi_sampled_texture {
-                Some(multi_sampled_texture) => wgpu::RenderPassColorAttachment {
-                    view: multi_sampled_texture,
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: false,
-                    },
+    fn encode_render_pass(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let color_attachment = match self.surface.multi_sampled_texture() {
+            Some(multi_sampled_texture) => wgpu::RenderPassColorAttachment {
+                view: multi_sampled_texture,
+                resolve_target: Some(view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: false,
                 },
-                None => wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: false,
-                    },
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
                 },
-            };
-
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Main Render Pass"),
-                color_attachments: &[Some(color_attachment)],
-                depth_stencil_attachment: None,
-            });
-
-            render_pass.set_bind_group(0, &self.uniform_buffer.bind_group, &[]);
-
-            self.engine_objects.iter().for_each(|object| {
-                if let Some(render_data) = object.render() {
-                    render_pass.set_pipeline(render_data.render_pipeline);
-                    render_pass.set_vertex_buffer(0, render_data.vertex_buffer.slice(..));
-                    match render_data.index_buffer {
-                        None => {
-                            render_pass.draw(0..render_data.num_vertices, 0..0);
-                        }
-                        Some(index_buffer) => {
-                            render_pass.set_index_buffer(
-                                index_buffer.slice(..),
-                                wgpu::IndexFormat::Uint16,
-                            );
-                            render_pass.draw_indexed(0..render_data.num_indices, 0, 0..1);
-                        }
+            },
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Main Render Pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: self.surface.depth_texture(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: false,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        render_pass.set_bind_group(0, &self.uniform_buffer.bind_group, &[]);
+
+        let objects = self
+            .scenes
+            .visible()
+            .chain(self.engine_objects.iter().map(|object| object.as_ref()))
+            .chain(self.scenes.overlay());
+        objects.for_each(|object| {
+            for render_data in object.render_layers() {
+                render_pass.set_pipeline(render_data.render_pipeline);
+                render_data
+                    .bind_groups
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, bind_group)| {
+                        render_pass.set_bind_group(i as u32 + 1, bind_group, &[]);
+                    });
+                render_data
+                    .vertex_buffers
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, vertex_buffer)| {
+                        render_pass.set_vertex_buffer(i as u32, vertex_buffer.slice(..));
+                    });
+                if let Some(instance_buffer) = render_data.instance_buffer {
+                    let slot = render_data.vertex_buffers.len() as u32;
+                    render_pass.set_vertex_buffer(slot, instance_buffer.slice(..));
+                }
+                match render_data.index_buffer {
+                    None => {
+                        render_pass.draw(0..render_data.num_vertices, render_data.instances);
+                    }
+                    Some(index_buffer) => {
+                        render_pass
+                            .set_index_buffer(index_buffer.slice(..), render_data.index_format);
+                        render_pass.draw_indexed(
+                            0..render_data.num_indices,
+                            0,
+                            render_data.instances,
+                        );
                     }
                 }
-            })
-        }
+            }
+        })
+    }
 
+    /// Copies the last rendered frame back to the CPU, straight from the offscreen target.
+    /// Window frames are gone once presented, so this returns `None` for them along with
+    /// targets whose format can't be encoded as 8 bit RGBA. Use F12 to save those instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn capture_frame(&self) -> Option<FrameCapture> {
+        let texture = self.surface.texture()?;
+        let mut encoder = self.device.create_encoder();
+        let readback = Readback::encode(&self.device.device, &mut encoder, texture)?;
         self.device.submit(iter::once(encoder.finish()));
-        output.present();
-        Ok(())
+        readback.read(&self.device.device)
     }
 
-    pub fn sample_count(&self) -> u32 {
-        if let Some(sample_count) = self.config.msaa {
-            sample_count
-        } else {
-            1
+    /// Saves a captured frame as a timestamped PNG in the working directory
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_screenshot(&self, readback: Readback) {
+        let Some(capture) = readback.read(&self.device.device) else {
+            return;
+        };
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let path = format!("screenshot_{}.png", timestamp);
+        match capture.save_png(&path) {
+            Ok(_) => info!("Saved screenshot to {}", path),
+            Err(e) => error!("Failed to save screenshot: {}", e),
         }
     }
 
+    pub fn sample_count(&self) -> u32 {
+        self.config.msaa.unwrap_or(1)
+    }
+
     pub fn device(&self) -> &wgpu::Device {
         &self.device.device
     }
 
-    pub fn surface_format(&self) -> &TextureFormat {
-        &self.surface.config.format
+    pub fn surface_format(&self) -> TextureFormat {
+        self.surface.format()
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    /// The texture frames are rendered into, `None` when rendering to a window
+    pub fn target_texture(&self) -> Option<&wgpu::Texture> {
+        self.surface.texture()
+    }
+
+    /// The glyph atlas every `TextRenderer` draws from, created on first use
+    pub fn text_resources(&self) -> Rc<TextResources> {
+        self.text_resources
+            .get_or_init(|| Rc::new(TextResources::new(self, Font::bundled())))
+            .clone()
     }
 
     pub fn uniform_bind_group(&self) -> &BindGroupLayout {
@@ -322,24 +563,81 @@ impl Engine {
     }
 }
 
+impl Drop for Engine {
+    fn drop(&mut self) {
+        self.stop_engine_objects();
+    }
+}
+
+/// Per frame state handed to `EngineObject::update`
+pub struct UpdateContext<'a> {
+    pub delta_time: f32,
+    pub run_time: f32,
+    pub size: PhysicalSize<u32>,
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+}
+
 /// Contains all the methods the engine will call on EngineObject
 /// Notice that none of the methods are required, that allows for
 /// flexibility with that don't need to render and such.
 pub trait EngineObject {
+    /// Called once when the object is added to the engine
     fn start(&mut self, _engine: &Engine) {}
-    fn update(&mut self) {}
-    fn render(&self) -> Option<RenderData> {
+    fn update(&mut self, _context: &UpdateContext) {}
+    /// Called once when the object is removed or the engine shuts down
+    fn stop(&mut self) {}
+    fn resize(&mut self, _size: PhysicalSize<u32>) {}
+    /// Called for every input event until one of the objects consumes it
+    fn input(&mut self, _event: &InputEvent) -> EventResponse {
+        EventResponse::Ignored
+    }
+    fn render(&self) -> Option<RenderData<'_>> {
         None
     }
+    /// Objects that need several draw calls override this instead, they are drawn in order
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        self.render().into_iter().collect()
+    }
 }
 
 /// Contains all the data that the engine requires to draw an object
 pub struct RenderData<'a> {
     pub render_pipeline: &'a wgpu::RenderPipeline,
-    pub vertex_buffer: &'a wgpu::Buffer,
+    /// Object specific bind groups, bound in order from slot 1 since slot 0 holds the engine uniforms
+    pub bind_groups: Vec<&'a wgpu::BindGroup>,
+    /// Bound in order from slot 0
+    pub vertex_buffers: Vec<&'a wgpu::Buffer>,
+    /// Per-instance data, bound in the slot after the last vertex buffer
+    pub instance_buffer: Option<&'a wgpu::Buffer>,
     pub index_buffer: Option<&'a wgpu::Buffer>,
+    pub index_format: wgpu::IndexFormat,
     pub num_vertices: u32,
     pub num_indices: u32,
+    pub instances: Range<u32>,
+}
+
+/// A small offscreen engine for tests, or `None` on machines without any adapter
+#[cfg(test)]
+pub fn test_engine(width: u32, height: u32) -> Option<Engine> {
+    let config = EngineConfig { msaa: None };
+    let engine = pollster::block_on(Engine::try_new_headless(config, width, height));
+    if engine.is_none() {
+        warn!("Skipping a test that renders, no adapter");
+    }
+    engine
+}
+
+/// Panics unless `source` parses and validates as WGSL
+#[cfg(test)]
+pub fn validate_shader(source: &str) {
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .unwrap();
 }
 
 fn create_window() -> (Window, EventLoop<()>) {
@@ -366,38 +664,33 @@ fn create_window() -> (Window, EventLoop<()>) {
     (window, event_loop)
 }
 
-fn create_multisampled_framebuffer(
-    device: &wgpu::Device,
-    config: &wgpu::SurfaceConfiguration,
-    sample_count: u32,
-) -> wgpu::TextureView {
-    let multisampled_texture_extent = wgpu::Extent3d {
-        width: config.width,
-        height: config.height,
-        depth_or_array_layers: 1,
-    };
-    let multisampled_frame_descriptor = &wgpu::TextureDescriptor {
-        size: multisampled_texture_extent,
-        mip_level_count: 1,
-        sample_count,
-        dimension: wgpu::TextureDimension::D2,
-        format: config.format,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        label: None,
-        view_formats: &[],
-    };
-    device
-        .create_texture(multisampled_frame_descriptor)
-        .create_view(&wgpu::TextureViewDescriptor::default())
+async fn create_adapter(surface: Option<&wgpu::Surface>, instance: &Instance) -> Adapter {
+    find_adapter(surface, instance)
+        .await
+        .expect("Found no adapter, not even a software one")
 }
 
-async fn create_adapter(surface: &wgpu::Surface, instance: &Instance) -> Adapter {
-    instance
+/// A hardware adapter if there is one, otherwise a software one
+async fn find_adapter(surface: Option<&wgpu::Surface>, instance: &Instance) -> Option<Adapter> {
+    let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(surface),
+            compatible_surface: surface,
             force_fallback_adapter: false,
         })
-        .await
-        .unwrap()
+        .await;
+
+    match adapter {
+        Some(adapter) => Some(adapter),
+        None => {
+            warn!("No hardware adapter found, falling back to software adapter");
+            instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::LowPower,
+                    compatible_surface: surface,
+                    force_fallback_adapter: true,
+                })
+                .await
+        }
+    }
 }
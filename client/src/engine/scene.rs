@@ -0,0 +1,437 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use wgpu::util::DeviceExt;
+
+use super::{overlay_depth_state, Engine, EngineObject, RenderData, UpdateContext};
+
+/// A screen of the app such as a menu or a visualization, made up of engine objects.
+/// Scenes live on a stack, only the top one is updated and receives input.
+pub trait Scene {
+    /// Creates the scene's objects, called every time the scene is pushed.
+    /// Objects can keep a clone of `scenes` to request transitions later.
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>>;
+
+    /// Overlays such as a pause menu keep the scenes below them drawn, though paused
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// How the screen changes when moving between scenes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Transition {
+    /// Swap scenes immediately
+    #[default]
+    Cut,
+    /// Fade to `color` over half of `duration` seconds, swap, then fade back in
+    Fade { duration: f32, color: [f32; 3] },
+}
+
+impl Transition {
+    pub fn fade(duration: f32) -> Self {
+        Self::Fade {
+            duration,
+            color: [0.0, 0.0, 0.0],
+        }
+    }
+}
+
+pub(super) enum SceneCommand {
+    Push(Box<dyn Scene>),
+    Pop,
+    Replace(Box<dyn Scene>),
+}
+
+/// Queues scene changes, they are applied by the engine at the start of the next update.
+/// Cheap to clone so it can be moved into UI callbacks.
+#[derive(Clone, Default)]
+pub struct SceneController {
+    commands: Rc<RefCell<VecDeque<(SceneCommand, Transition)>>>,
+}
+
+impl SceneController {
+    pub fn push(&self, scene: impl Scene + 'static) {
+        self.push_with(scene, Transition::Cut);
+    }
+
+    pub fn push_with(&self, scene: impl Scene + 'static, transition: Transition) {
+        self.queue(SceneCommand::Push(Box::new(scene)), transition);
+    }
+
+    /// Returns to the scene below the top one
+    pub fn pop(&self) {
+        self.pop_with(Transition::Cut);
+    }
+
+    pub fn pop_with(&self, transition: Transition) {
+        self.queue(SceneCommand::Pop, transition);
+    }
+
+    /// Swaps the top scene for another one
+    pub fn replace(&self, scene: impl Scene + 'static) {
+        self.replace_with(scene, Transition::Cut);
+    }
+
+    pub fn replace_with(&self, scene: impl Scene + 'static, transition: Transition) {
+        self.queue(SceneCommand::Replace(Box::new(scene)), transition);
+    }
+
+    fn queue(&self, command: SceneCommand, transition: Transition) {
+        self.commands.borrow_mut().push_back((command, transition));
+    }
+
+    fn next(&self) -> Option<(SceneCommand, Transition)> {
+        self.commands.borrow_mut().pop_front()
+    }
+}
+
+pub(super) struct SceneEntry {
+    scene: Box<dyn Scene>,
+    objects: Vec<Box<dyn EngineObject>>,
+}
+
+impl SceneEntry {
+    fn stop(mut self) {
+        self.objects.iter_mut().for_each(|object| object.stop());
+    }
+}
+
+/// A fade in progress, the command it wraps runs once the screen is fully covered
+struct Fade {
+    command: Option<SceneCommand>,
+    elapsed: f32,
+    duration: f32,
+    color: [f32; 3],
+}
+
+impl Fade {
+    /// Returns the command when the midpoint is reached
+    fn advance(&mut self, delta_time: f32) -> Option<SceneCommand> {
+        self.elapsed += delta_time;
+        if self.elapsed >= self.duration / 2.0 {
+            self.command.take()
+        } else {
+            None
+        }
+    }
+
+    /// Opacity of the cover, rising to 1 at the midpoint and back to 0
+    fn alpha(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        let t = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        1.0 - (2.0 * t - 1.0).abs()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.command.is_none() && self.elapsed >= self.duration
+    }
+}
+
+/// The engine's stack of scenes and any transition between them
+#[derive(Default)]
+pub(super) struct SceneStack {
+    entries: Vec<SceneEntry>,
+    controller: SceneController,
+    fade: Option<Fade>,
+    overlay: Option<FadeOverlay>,
+}
+
+impl SceneStack {
+    pub fn controller(&self) -> &SceneController {
+        &self.controller
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Moves the current fade along and takes queued commands,
+    /// returning the ones that should be applied this frame in order
+    pub fn advance(&mut self, delta_time: f32) -> Vec<SceneCommand> {
+        let mut ready = Vec::new();
+        if let Some(fade) = &mut self.fade {
+            ready.extend(fade.advance(delta_time));
+            if fade.is_finished() {
+                self.fade = None;
+            }
+        }
+
+        // Commands queued during a fade wait for it to finish
+        while self.fade.is_none() {
+            let Some((command, transition)) = self.controller.next() else {
+                break;
+            };
+            match transition {
+                Transition::Cut => ready.push(command),
+                Transition::Fade { duration, color } => {
+                    self.fade = Some(Fade {
+                        command: Some(command),
+                        elapsed: 0.0,
+                        duration,
+                        color,
+                    });
+                    // A zero length fade swaps right away
+                    ready.extend(self.fade.as_mut().and_then(|fade| fade.advance(0.0)));
+                }
+            }
+        }
+        ready
+    }
+
+    pub fn push(&mut self, scene: Box<dyn Scene>, objects: Vec<Box<dyn EngineObject>>) {
+        self.entries.push(SceneEntry { scene, objects });
+    }
+
+    /// Removes the top scene and stops its objects
+    pub fn pop(&mut self) -> bool {
+        match self.entries.pop() {
+            Some(entry) => {
+                entry.stop();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        while self.pop() {}
+    }
+
+    /// The top scene's objects, the only ones that are updated and get input
+    pub fn active_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Box<dyn EngineObject>> {
+        self.entries
+            .last_mut()
+            .into_iter()
+            .flat_map(|entry| entry.objects.iter_mut())
+    }
+
+    pub fn all_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn EngineObject>> {
+        self.entries
+            .iter_mut()
+            .flat_map(|entry| entry.objects.iter_mut())
+    }
+
+    /// Objects to draw from the bottom up, starting at the highest scene that isn't an overlay
+    pub fn visible(&self) -> impl Iterator<Item = &dyn EngineObject> {
+        let start = self
+            .entries
+            .iter()
+            .rposition(|entry| !entry.scene.is_overlay())
+            .unwrap_or(0);
+        self.entries[start..]
+            .iter()
+            .flat_map(|entry| entry.objects.iter().map(|object| object.as_ref()))
+    }
+
+    pub fn needs_overlay(&self) -> bool {
+        self.fade.is_some() && self.overlay.is_none()
+    }
+
+    pub fn set_overlay(&mut self, overlay: FadeOverlay) {
+        self.overlay = Some(overlay);
+    }
+
+    /// Updates the fade cover, hiding it once no fade is running
+    pub fn update_overlay(&mut self, context: &UpdateContext) {
+        if let Some(overlay) = &mut self.overlay {
+            overlay.color = match &self.fade {
+                Some(fade) => {
+                    let [r, g, b] = fade.color;
+                    [r, g, b, fade.alpha()]
+                }
+                None => [0.0; 4],
+            };
+            overlay.update(context);
+        }
+    }
+
+    pub fn overlay(&self) -> Option<&dyn EngineObject> {
+        self.overlay
+            .as_ref()
+            .map(|overlay| overlay as &dyn EngineObject)
+    }
+}
+
+/// Fullscreen quad of a single color drawn over everything during fades
+pub(super) struct FadeOverlay {
+    color: [f32; 4],
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+impl FadeOverlay {
+    pub fn new(engine: &Engine) -> Self {
+        let device = engine.device();
+        let color = [0.0f32; 4];
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fade Buffer"),
+            contents: bytemuck::cast_slice(&color),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("fade_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("fade_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Fade Layout"),
+            bind_group_layouts: &[engine.uniform_bind_group(), &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./fade.wgsl"));
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Fade Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(overlay_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: engine.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: engine.surface_format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            color,
+            buffer,
+            bind_group,
+            render_pipeline,
+        }
+    }
+}
+
+impl EngineObject for FadeOverlay {
+    fn update(&mut self, context: &UpdateContext) {
+        context
+            .queue
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.color));
+    }
+
+    fn render(&self) -> Option<RenderData<'_>> {
+        if self.color[3] <= 0.0 {
+            return None;
+        }
+        Some(RenderData {
+            render_pipeline: &self.render_pipeline,
+            bind_groups: vec![&self.bind_group],
+            vertex_buffers: vec![],
+            instance_buffer: None,
+            index_buffer: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            num_vertices: 3,
+            num_indices: 0,
+            instances: 0..1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Empty;
+
+    impl Scene for Empty {
+        fn build(&mut self, _: &Engine, _: &SceneController) -> Vec<Box<dyn EngineObject>> {
+            vec![]
+        }
+    }
+
+    fn kinds(commands: &[SceneCommand]) -> Vec<&'static str> {
+        commands
+            .iter()
+            .map(|command| match command {
+                SceneCommand::Push(_) => "push",
+                SceneCommand::Pop => "pop",
+                SceneCommand::Replace(_) => "replace",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cuts_apply_in_order() {
+        let mut stack = SceneStack::default();
+        let scenes = stack.controller().clone();
+        scenes.push(Empty);
+        scenes.replace(Empty);
+        scenes.pop();
+
+        assert_eq!(kinds(&stack.advance(0.0)), ["push", "replace", "pop"]);
+        assert!(stack.advance(0.0).is_empty());
+        assert!(!stack.is_fading());
+    }
+
+    #[test]
+    fn test_fade_swaps_at_midpoint() {
+        let mut stack = SceneStack::default();
+        let scenes = stack.controller().clone();
+        scenes.push_with(Empty, Transition::fade(1.0));
+        scenes.pop();
+
+        // The fade starts on the frame it's taken and the pop waits behind it
+        assert!(stack.advance(0.1).is_empty());
+        assert!(stack.advance(0.25).is_empty());
+        assert!(stack.is_fading());
+        assert!((stack.fade.as_ref().unwrap().alpha() - 0.5).abs() < 1e-5);
+
+        assert_eq!(kinds(&stack.advance(0.3)), ["push"]);
+        assert!(stack.advance(0.3).is_empty());
+        assert!((stack.fade.as_ref().unwrap().alpha() - 0.3).abs() < 1e-5);
+
+        assert_eq!(kinds(&stack.advance(0.3)), ["pop"]);
+        assert!(!stack.is_fading());
+    }
+
+    #[test]
+    fn test_shader_compiles() {
+        crate::engine::validate_shader(include_str!("fade.wgsl"));
+    }
+}
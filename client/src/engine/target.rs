@@ -0,0 +1,288 @@
+use super::{Device, DEPTH_FORMAT};
+use crate::engine::EngineConfig;
+use wgpu::{Adapter, TextureFormat};
+use winit::dpi::PhysicalSize;
+
+/// Format used for offscreen rendering, matches what most surfaces pick
+pub const OFFSCREEN_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Anything the engine can draw a frame into
+pub trait RenderTarget {
+    fn format(&self) -> TextureFormat;
+    fn resize(&mut self, new_size: &PhysicalSize<u32>, device: &Device);
+    fn multi_sampled_texture(&self) -> Option<&wgpu::TextureView>;
+    /// Depth buffer matching the size and sample count of the color target
+    fn depth_texture(&self) -> &wgpu::TextureView;
+    fn get_frame(&self) -> Result<Frame, wgpu::SurfaceError>;
+    /// The texture backing the last frame, only available on targets that can be read back
+    fn texture(&self) -> Option<&wgpu::Texture> {
+        None
+    }
+    /// Whether frames can be copied out to save screenshots
+    fn can_capture(&self) -> bool {
+        true
+    }
+}
+
+/// A single frame acquired from a render target
+pub struct Frame {
+    pub view: wgpu::TextureView,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl Frame {
+    /// The window's texture for this frame, offscreen targets expose theirs through `RenderTarget`
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        self.surface_texture
+            .as_ref()
+            .map(|surface_texture| &surface_texture.texture)
+    }
+
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+/// Whether the adapter's surfaces may be copied from. wgpu 0.15 doesn't report
+/// surface usages, so this follows its backends: DirectX 12 and Vulkan drivers
+/// offer it, Metal and GL surfaces can only be rendered to.
+fn surface_can_copy(adapter: &Adapter) -> bool {
+    matches!(
+        adapter.get_info().backend,
+        wgpu::Backend::Dx12 | wgpu::Backend::Vulkan
+    )
+}
+
+/// Renders into the surface of a winit window
+pub struct WindowSurface {
+    surface: wgpu::Surface,
+    multi_sampled_texture: Option<wgpu::TextureView>,
+    depth_texture: wgpu::TextureView,
+    config: wgpu::SurfaceConfiguration,
+    sample_count: Option<u32>,
+}
+
+impl WindowSurface {
+    pub fn new(
+        surface: wgpu::Surface,
+        adapter: &Adapter,
+        size: PhysicalSize<u32>,
+        device: &Device,
+        engine_config: &EngineConfig,
+    ) -> Self {
+        let capabilities = surface.get_capabilities(adapter);
+        let surface_format = capabilities
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.describe().srgb)
+            .unwrap_or(capabilities.formats[0]);
+        // Frames are copied out of the surface to save screenshots where the backend allows it
+        let usage = if cfg!(not(target_arch = "wasm32")) && surface_can_copy(adapter) {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        };
+        let config = wgpu::SurfaceConfiguration {
+            usage,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: capabilities.present_modes[0],
+            alpha_mode: capabilities.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device.device, &config);
+
+        let multi_sampled_texture = engine_config.msaa.map(|sample_count| {
+            create_multisampled_framebuffer(&device.device, size, config.format, sample_count)
+        });
+        let depth_texture = create_depth_texture(&device.device, size, engine_config.msaa);
+
+        Self {
+            surface,
+            multi_sampled_texture,
+            depth_texture,
+            config,
+            sample_count: engine_config.msaa,
+        }
+    }
+}
+
+impl RenderTarget for WindowSurface {
+    fn format(&self) -> TextureFormat {
+        self.config.format
+    }
+
+    fn can_capture(&self) -> bool {
+        self.config.usage.contains(wgpu::TextureUsages::COPY_SRC)
+    }
+
+    fn resize(&mut self, new_size: &PhysicalSize<u32>, device: &Device) {
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.surface.configure(&device.device, &self.config);
+        self.multi_sampled_texture = self.sample_count.map(|sample_count| {
+            create_multisampled_framebuffer(
+                &device.device,
+                *new_size,
+                self.config.format,
+                sample_count,
+            )
+        });
+        self.depth_texture = create_depth_texture(&device.device, *new_size, self.sample_count);
+    }
+
+    fn multi_sampled_texture(&self) -> Option<&wgpu::TextureView> {
+        self.multi_sampled_texture.as_ref()
+    }
+
+    fn depth_texture(&self) -> &wgpu::TextureView {
+        &self.depth_texture
+    }
+
+    fn get_frame(&self) -> Result<Frame, wgpu::SurfaceError> {
+        let surface_texture = self.surface.get_current_texture()?;
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        Ok(Frame {
+            view,
+            surface_texture: Some(surface_texture),
+        })
+    }
+}
+
+/// Renders into a plain texture, no window or display required
+pub struct OffscreenSurface {
+    texture: wgpu::Texture,
+    multi_sampled_texture: Option<wgpu::TextureView>,
+    depth_texture: wgpu::TextureView,
+    sample_count: Option<u32>,
+}
+
+impl OffscreenSurface {
+    pub fn new(size: PhysicalSize<u32>, device: &Device, engine_config: &EngineConfig) -> Self {
+        let texture = create_offscreen_texture(&device.device, size);
+        let multi_sampled_texture = engine_config.msaa.map(|sample_count| {
+            create_multisampled_framebuffer(&device.device, size, OFFSCREEN_FORMAT, sample_count)
+        });
+        let depth_texture = create_depth_texture(&device.device, size, engine_config.msaa);
+
+        Self {
+            texture,
+            multi_sampled_texture,
+            depth_texture,
+            sample_count: engine_config.msaa,
+        }
+    }
+}
+
+impl RenderTarget for OffscreenSurface {
+    fn format(&self) -> TextureFormat {
+        OFFSCREEN_FORMAT
+    }
+
+    fn resize(&mut self, new_size: &PhysicalSize<u32>, device: &Device) {
+        self.texture = create_offscreen_texture(&device.device, *new_size);
+        self.multi_sampled_texture = self.sample_count.map(|sample_count| {
+            create_multisampled_framebuffer(
+                &device.device,
+                *new_size,
+                OFFSCREEN_FORMAT,
+                sample_count,
+            )
+        });
+        self.depth_texture = create_depth_texture(&device.device, *new_size, self.sample_count);
+    }
+
+    fn multi_sampled_texture(&self) -> Option<&wgpu::TextureView> {
+        self.multi_sampled_texture.as_ref()
+    }
+
+    fn depth_texture(&self) -> &wgpu::TextureView {
+        &self.depth_texture
+    }
+
+    fn get_frame(&self) -> Result<Frame, wgpu::SurfaceError> {
+        let view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        Ok(Frame {
+            view,
+            surface_texture: None,
+        })
+    }
+
+    fn texture(&self) -> Option<&wgpu::Texture> {
+        Some(&self.texture)
+    }
+}
+
+fn create_offscreen_texture(device: &wgpu::Device, size: PhysicalSize<u32>) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: OFFSCREEN_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        label: Some("Offscreen Texture"),
+        view_formats: &[],
+    })
+}
+
+fn create_multisampled_framebuffer(
+    device: &wgpu::Device,
+    size: PhysicalSize<u32>,
+    format: TextureFormat,
+    sample_count: u32,
+) -> wgpu::TextureView {
+    let multisampled_texture_extent = wgpu::Extent3d {
+        width: size.width,
+        height: size.height,
+        depth_or_array_layers: 1,
+    };
+    let multisampled_frame_descriptor = &wgpu::TextureDescriptor {
+        size: multisampled_texture_extent,
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        label: None,
+        view_formats: &[],
+    };
+    device
+        .create_texture(multisampled_frame_descriptor)
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn create_depth_texture(
+    device: &wgpu::Device,
+    size: PhysicalSize<u32>,
+    sample_count: Option<u32>,
+) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: sample_count.unwrap_or(1),
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            label: Some("Depth Texture"),
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
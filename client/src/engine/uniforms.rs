@@ -1,5 +1,5 @@
 use wgpu::{util::DeviceExt, Device};
-use winit::window::Window;
+use winit::dpi::PhysicalSize;
 
 /// Engine level global Uniforms that can be used in any shader
 #[repr(C)]
@@ -20,7 +20,7 @@ pub struct UniformBuffer {
 }
 
 impl UniformBuffer {
-    pub fn new(device: &Device, window: &Window) -> Self {
+    pub fn new(device: &Device, size: PhysicalSize<u32>) -> Self {
         let mut uniforms = Uniforms::default();
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Uniform Buffer"),
@@ -51,7 +51,6 @@ impl UniformBuffer {
             label: Some("uniform_bind_group"),
         });
 
-        let size = window.inner_size();
         uniforms.width = size.width as f32;
         uniforms.height = size.height as f32;
 
@@ -0,0 +1,4 @@
+//! Geometry shared by the visualizations, independent of how it ends up on screen
+
+pub mod polyhedra;
+pub mod tessellate;
@@ -0,0 +1,647 @@
+//! The five Platonic solids with their combinatorics and rotational symmetry.
+//!
+//! Every solid is centered on the origin with its vertices on the unit sphere.
+//! Faces list vertex indices counter clockwise seen from outside, and the
+//! symmetry generators are rotations permuting the vertices among themselves.
+
+use std::collections::HashSet;
+use std::f32::consts::{PI, TAU};
+
+use glam::{Quat, Vec3};
+
+use crate::algebra::group::PermGroup;
+use crate::algebra::perm::Permutation;
+use crate::math_object::Mesh;
+use crate::types::Vertex3;
+
+/// Tolerance when matching positions, the coordinates involved are all of order 1
+const EPSILON: f32 = 1e-4;
+/// Largest order of a rotation of any of the solids is 5, this leaves room for rounding
+const MAX_ORDER: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Solid {
+    Tetrahedron,
+    Cube,
+    Octahedron,
+    Dodecahedron,
+    Icosahedron,
+}
+
+impl Solid {
+    pub const ALL: [Solid; 5] = [
+        Solid::Tetrahedron,
+        Solid::Cube,
+        Solid::Octahedron,
+        Solid::Dodecahedron,
+        Solid::Icosahedron,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solid::Tetrahedron => "Tetrahedron",
+            Solid::Cube => "Cube",
+            Solid::Octahedron => "Octahedron",
+            Solid::Dodecahedron => "Dodecahedron",
+            Solid::Icosahedron => "Icosahedron",
+        }
+    }
+
+    /// The solid whose vertices sit at this one's face centers
+    pub fn dual(&self) -> Solid {
+        match self {
+            Solid::Tetrahedron => Solid::Tetrahedron,
+            Solid::Cube => Solid::Octahedron,
+            Solid::Octahedron => Solid::Cube,
+            Solid::Dodecahedron => Solid::Icosahedron,
+            Solid::Icosahedron => Solid::Dodecahedron,
+        }
+    }
+
+    pub fn polyhedron(&self) -> Polyhedron {
+        Polyhedron::new(*self)
+    }
+}
+
+/// A rotation about an axis through the origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    pub axis: Vec3,
+    pub angle: f32,
+}
+
+impl Rotation {
+    pub fn new(axis: Vec3, angle: f32) -> Self {
+        Self {
+            axis: axis.normalize(),
+            angle,
+        }
+    }
+
+    /// The same rotation turning by at most half a turn, about a z axis if it's the identity
+    pub fn from_quat(rotation: Quat) -> Self {
+        let (axis, angle) = rotation.normalize().to_axis_angle();
+        if angle < EPSILON || !axis.is_finite() {
+            Self::new(Vec3::Z, 0.0)
+        } else if angle > PI + EPSILON {
+            Self::new(-axis, TAU - angle)
+        } else {
+            Self::new(axis, angle)
+        }
+    }
+
+    pub fn quat(&self) -> Quat {
+        Quat::from_axis_angle(self.axis, self.angle)
+    }
+
+    /// How many times the rotation has to be applied to get back to the identity
+    pub fn order(&self) -> u32 {
+        let turns = self.angle / TAU;
+        (1..=MAX_ORDER)
+            .find(|&k| {
+                let total = turns * k as f32;
+                (total - total.round()).abs() < EPSILON
+            })
+            .unwrap_or(MAX_ORDER)
+    }
+}
+
+/// A rotation or a reflection of space fixing the origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    Rotation(Rotation),
+    /// Mirroring across the plane through the origin with this unit normal
+    Reflection(Vec3),
+}
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry::Rotation(Rotation {
+        axis: Vec3::Z,
+        angle: 0.0,
+    });
+
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        match self {
+            Symmetry::Rotation(rotation) => rotation.quat() * point,
+            Symmetry::Reflection(normal) => point - 2.0 * point.dot(*normal) * *normal,
+        }
+    }
+}
+
+impl From<Rotation> for Symmetry {
+    fn from(rotation: Rotation) -> Self {
+        Symmetry::Rotation(rotation)
+    }
+}
+
+/// Somewhere on the surface an axis can pass through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Vertex(usize),
+    Edge(usize),
+    Face(usize),
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Feature::Vertex(i) => write!(f, "vertex {i}"),
+            Feature::Edge(i) => write!(f, "edge {i}"),
+            Feature::Face(i) => write!(f, "face {i}"),
+        }
+    }
+}
+
+/// The rotations of a solid, given by a few rotations that generate all of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetryGroup {
+    /// Which abstract group the rotations form: A4, S4 or A5
+    pub name: &'static str,
+    pub order: usize,
+    pub generators: Vec<Rotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyhedron {
+    pub solid: Solid,
+    pub vertices: Vec<Vec3>,
+    /// Vertex indices counter clockwise from outside
+    pub faces: Vec<Vec<usize>>,
+    /// Outward unit normal of each face
+    pub face_normals: Vec<Vec3>,
+    /// Pairs of vertex indices, smaller first
+    pub edges: Vec<[usize; 2]>,
+    /// Vertices sharing an edge with each vertex
+    pub vertex_neighbors: Vec<Vec<usize>>,
+    /// Faces around each vertex
+    pub vertex_faces: Vec<Vec<usize>>,
+    /// The two faces on either side of each edge
+    pub edge_faces: Vec<[usize; 2]>,
+    /// Faces sharing an edge with each face
+    pub face_neighbors: Vec<Vec<usize>>,
+    pub symmetry: SymmetryGroup,
+}
+
+impl Polyhedron {
+    pub fn new(solid: Solid) -> Self {
+        let vertices: Vec<Vec3> = corners(solid).into_iter().map(Vec3::normalize).collect();
+        let faces = find_faces(&vertices);
+        let face_normals = faces
+            .iter()
+            .map(|face| {
+                let [a, b, c] = [0, 1, 2].map(|i| vertices[face[i]]);
+                (b - a).cross(c - a).normalize()
+            })
+            .collect();
+
+        let mut edges: Vec<[usize; 2]> = faces
+            .iter()
+            .flat_map(|face| {
+                (0..face.len()).map(|i| {
+                    let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                    [a.min(b), a.max(b)]
+                })
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut vertex_neighbors = vec![Vec::new(); vertices.len()];
+        for &[a, b] in &edges {
+            vertex_neighbors[a].push(b);
+            vertex_neighbors[b].push(a);
+        }
+        let vertex_faces = (0..vertices.len())
+            .map(|v| {
+                (0..faces.len())
+                    .filter(|&f| faces[f].contains(&v))
+                    .collect()
+            })
+            .collect();
+        let edge_faces: Vec<[usize; 2]> = edges
+            .iter()
+            .map(|edge| {
+                let mut sides =
+                    (0..faces.len()).filter(|&f| edge.iter().all(|v| faces[f].contains(v)));
+                let first = sides.next().expect("Every edge borders a face");
+                [first, sides.next().expect("Every edge borders two faces")]
+            })
+            .collect();
+        let mut face_neighbors = vec![Vec::new(); faces.len()];
+        for &[a, b] in &edge_faces {
+            face_neighbors[a].push(b);
+            face_neighbors[b].push(a);
+        }
+
+        Self {
+            solid,
+            symmetry: symmetry_group(solid, &vertices, &faces),
+            vertices,
+            faces,
+            face_normals,
+            edges,
+            vertex_neighbors,
+            vertex_faces,
+            edge_faces,
+            face_neighbors,
+        }
+    }
+
+    pub fn face_center(&self, face: usize) -> Vec3 {
+        let corners = &self.faces[face];
+        corners.iter().map(|&v| self.vertices[v]).sum::<Vec3>() / corners.len() as f32
+    }
+
+    pub fn edge_midpoint(&self, edge: usize) -> Vec3 {
+        let [a, b] = self.edges[edge];
+        (self.vertices[a] + self.vertices[b]) / 2.0
+    }
+
+    /// Flat shaded triangles, face `f` owns `faces[f].len()` consecutive vertices
+    /// in the order faces are listed
+    pub fn mesh(&self) -> Mesh<Vertex3> {
+        let mut mesh = Mesh::new();
+        for face in &self.faces {
+            let corners: Vec<Vec3> = face.iter().map(|&v| self.vertices[v]).collect();
+            mesh.append(Mesh::face(&corners));
+        }
+        mesh
+    }
+
+    /// The rotations as permutations of the vertices
+    pub fn rotation_group(&self) -> PermGroup {
+        let generators = self
+            .symmetry
+            .generators
+            .iter()
+            .map(|&rotation| {
+                let images = self
+                    .vertex_permutation(rotation)
+                    .expect("Generators are symmetries");
+                Permutation::from_images(images).expect("Symmetries permute the vertices")
+            })
+            .collect();
+        PermGroup::new(self.vertices.len(), generators)
+    }
+
+    /// Where each vertex ends up under `symmetry`, `None` if it isn't a symmetry of the solid
+    pub fn vertex_permutation(&self, symmetry: impl Into<Symmetry>) -> Option<Vec<usize>> {
+        let symmetry = symmetry.into();
+        self.vertices
+            .iter()
+            .map(|&v| self.vertex_at(symmetry.apply(v)))
+            .collect()
+    }
+
+    /// The vertex, edge midpoint or face center in a direction from the center
+    pub fn feature_along(&self, direction: Vec3) -> Option<Feature> {
+        let direction = direction.try_normalize()?;
+        let along = |point: Vec3| point.normalize().distance(direction) < EPSILON;
+        (0..self.vertices.len())
+            .find(|&v| along(self.vertices[v]))
+            .map(Feature::Vertex)
+            .or_else(|| {
+                (0..self.edges.len())
+                    .find(|&e| along(self.edge_midpoint(e)))
+                    .map(Feature::Edge)
+            })
+            .or_else(|| {
+                (0..self.faces.len())
+                    .find(|&f| along(self.face_center(f)))
+                    .map(Feature::Face)
+            })
+    }
+
+    /// Every rotation of the solid, by increasing angle starting with the identity
+    pub fn rotations(&self) -> Vec<Rotation> {
+        let mut seen = HashSet::new();
+        let mut rotations = Vec::new();
+        let mut frontier = vec![Quat::IDENTITY];
+        while let Some(rotation) = frontier.pop() {
+            let rotation = Rotation::from_quat(rotation);
+            let Some(permutation) = self.vertex_permutation(rotation) else {
+                continue;
+            };
+            if !seen.insert(permutation) {
+                continue;
+            }
+            rotations.push(rotation);
+            frontier.extend(
+                self.symmetry
+                    .generators
+                    .iter()
+                    .map(|generator| generator.quat() * rotation.quat()),
+            );
+        }
+        rotations.sort_by(|a, b| a.angle.total_cmp(&b.angle));
+        rotations
+    }
+
+    /// Normals of the mirror planes, a reflection always swaps some pair of
+    /// vertices so its normal is the direction between them
+    pub fn reflections(&self) -> Vec<Vec3> {
+        let mut seen = HashSet::new();
+        let mut normals = Vec::new();
+        for (i, a) in self.vertices.iter().enumerate() {
+            for b in &self.vertices[i + 1..] {
+                let normal = (*a - *b).normalize();
+                let Some(permutation) = self.vertex_permutation(Symmetry::Reflection(normal))
+                else {
+                    continue;
+                };
+                if seen.insert(permutation) {
+                    normals.push(normal);
+                }
+            }
+        }
+        normals
+    }
+
+    /// The rotations followed by the mirror reflections. With the improper
+    /// rotations left out this isn't a group, though the reflections generate it.
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        let rotations = self.rotations().into_iter().map(Symmetry::Rotation);
+        let reflections = self.reflections().into_iter().map(Symmetry::Reflection);
+        rotations.chain(reflections).collect()
+    }
+
+    /// Index of the vertex at a position
+    pub fn vertex_at(&self, position: Vec3) -> Option<usize> {
+        self.vertices
+            .iter()
+            .position(|v| v.distance(position) < EPSILON)
+    }
+}
+
+fn corners(solid: Solid) -> Vec<Vec3> {
+    let phi = (1.0 + 5f32.sqrt()) / 2.0;
+    match solid {
+        Solid::Tetrahedron => vec![
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+        ],
+        Solid::Cube => cube_corners(),
+        Solid::Octahedron => vec![
+            Vec3::X,
+            Vec3::NEG_X,
+            Vec3::Y,
+            Vec3::NEG_Y,
+            Vec3::Z,
+            Vec3::NEG_Z,
+        ],
+        // Cyclic permutations of (0, ±phi, ±1), dual to the dodecahedron below
+        Solid::Icosahedron => cyclic([0.0, phi, 1.0]),
+        // A cube plus cyclic permutations of (0, ±1/phi, ±phi)
+        Solid::Dodecahedron => {
+            let mut corners = cube_corners();
+            corners.extend(cyclic([0.0, 1.0 / phi, phi]));
+            corners
+        }
+    }
+}
+
+fn cube_corners() -> Vec<Vec3> {
+    (0..8)
+        .map(|i| {
+            let side = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            Vec3::new(side(1), side(2), side(4))
+        })
+        .collect()
+}
+
+/// The three cyclic permutations of `(0, ±a, ±b)`
+fn cyclic([zero, a, b]: [f32; 3]) -> Vec<Vec3> {
+    let mut points = Vec::new();
+    for rotation in 0..3 {
+        for sa in [-1.0, 1.0] {
+            for sb in [-1.0, 1.0] {
+                let mut point = [zero, a * sa, b * sb];
+                point.rotate_right(rotation);
+                points.push(Vec3::from(point));
+            }
+        }
+    }
+    points
+}
+
+/// Faces of a convex polyhedron: planes through three vertices with every
+/// other vertex behind them, with corners sorted counter clockwise from outside
+fn find_faces(vertices: &[Vec3]) -> Vec<Vec<usize>> {
+    let n = vertices.len();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                let [pa, pb, pc] = [a, b, c].map(|i| vertices[i]);
+                let Some(mut normal) = (pb - pa).cross(pc - pa).try_normalize() else {
+                    continue;
+                };
+                if normal.dot(pa) < 0.0 {
+                    normal = -normal;
+                }
+                let offset = normal.dot(pa);
+                if vertices.iter().any(|v| normal.dot(*v) > offset + EPSILON) {
+                    continue;
+                }
+
+                let mut face: Vec<usize> = (0..n)
+                    .filter(|&i| (normal.dot(vertices[i]) - offset).abs() < EPSILON)
+                    .collect();
+                // Each face is found once, through its three lowest corners
+                if face[..3] != [a, b, c] {
+                    continue;
+                }
+                let center = face.iter().map(|&i| vertices[i]).sum::<Vec3>() / face.len() as f32;
+                let reference = (vertices[face[0]] - center).normalize();
+                let angle = |i: &usize| {
+                    let offset = vertices[*i] - center;
+                    let angle = normal
+                        .dot(reference.cross(offset))
+                        .atan2(reference.dot(offset));
+                    if angle < -EPSILON {
+                        angle + TAU
+                    } else {
+                        angle
+                    }
+                };
+                face.sort_by(|i, j| angle(i).total_cmp(&angle(j)));
+                faces.push(face);
+            }
+        }
+    }
+    faces
+}
+
+/// Two rotations generating the rotation group: one about a vertex and one about
+/// a face center or an edge midpoint, of orders that only the full group contains
+fn symmetry_group(solid: Solid, vertices: &[Vec3], faces: &[Vec<usize>]) -> SymmetryGroup {
+    let face_center =
+        |f: usize| faces[f].iter().map(|&v| vertices[v]).sum::<Vec3>() / faces[f].len() as f32;
+    // Turning about a face center by one corner
+    let face_turn = |f: usize| Rotation::new(face_center(f), TAU / faces[f].len() as f32);
+    // Turning about a vertex by one face
+    let vertex_turn = |v: usize| {
+        let degree = faces.iter().filter(|face| face.contains(&v)).count();
+        Rotation::new(vertices[v], TAU / degree as f32)
+    };
+
+    let (name, order, generators) = match solid {
+        // A 3-cycle and a double transposition
+        Solid::Tetrahedron => {
+            let edge_midpoint = (vertices[0] + vertices[1]) / 2.0;
+            (
+                "A4",
+                12,
+                vec![vertex_turn(0), Rotation::new(edge_midpoint, PI)],
+            )
+        }
+        // Quarter turns and turns about the long diagonals
+        Solid::Cube => ("S4", 24, vec![face_turn(0), vertex_turn(0)]),
+        Solid::Octahedron => ("S4", 24, vec![vertex_turn(0), face_turn(0)]),
+        // Fifth turns and third turns
+        Solid::Dodecahedron => ("A5", 60, vec![face_turn(0), vertex_turn(0)]),
+        Solid::Icosahedron => ("A5", 60, vec![vertex_turn(0), face_turn(0)]),
+    };
+    SymmetryGroup {
+        name,
+        order,
+        generators,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combinatorics() {
+        // (vertices, edges, faces, corners per face, faces per vertex)
+        let expected = [
+            (Solid::Tetrahedron, 4, 6, 4, 3, 3),
+            (Solid::Cube, 8, 12, 6, 4, 3),
+            (Solid::Octahedron, 6, 12, 8, 3, 4),
+            (Solid::Dodecahedron, 20, 30, 12, 5, 3),
+            (Solid::Icosahedron, 12, 30, 20, 3, 5),
+        ];
+        for (solid, v, e, f, p, q) in expected {
+            let poly = solid.polyhedron();
+            assert_eq!(
+                (poly.vertices.len(), poly.edges.len(), poly.faces.len()),
+                (v, e, f),
+                "{}",
+                solid.name()
+            );
+            assert!(poly.faces.iter().all(|face| face.len() == p));
+            assert!(poly.vertex_faces.iter().all(|faces| faces.len() == q));
+            assert!(poly.vertex_neighbors.iter().all(|n| n.len() == q));
+            assert!(poly.face_neighbors.iter().all(|n| n.len() == p));
+
+            // Every edge has the same length and faces wind outward
+            let length = |[a, b]: [usize; 2]| poly.vertices[a].distance(poly.vertices[b]);
+            let first = length(poly.edges[0]);
+            assert!(poly
+                .edges
+                .iter()
+                .all(|&edge| (length(edge) - first).abs() < EPSILON));
+            for (face, normal) in poly.faces.iter().zip(&poly.face_normals) {
+                assert!(normal.dot(poly.vertices[face[0]]) > 0.0);
+            }
+
+            let mesh = poly.mesh();
+            assert_eq!(mesh.vertices.len(), f * p);
+            assert_eq!(mesh.triangle_count(), f * (p - 2));
+            assert!(mesh
+                .vertices
+                .iter()
+                .all(|vertex| Vec3::from(vertex.normal).dot(Vec3::from(vertex.pos)) > 0.0));
+        }
+    }
+
+    #[test]
+    fn test_generators_span_rotation_group() {
+        for solid in Solid::ALL {
+            let poly = solid.polyhedron();
+            let generators: Vec<Vec<usize>> = poly
+                .symmetry
+                .generators
+                .iter()
+                .map(|rotation| {
+                    poly.vertex_permutation(*rotation)
+                        .expect("Generators are symmetries")
+                })
+                .collect();
+
+            // Close the generators under composition
+            let identity: Vec<usize> = (0..poly.vertices.len()).collect();
+            let mut group = HashSet::from([identity.clone()]);
+            let mut frontier = vec![identity];
+            while let Some(element) = frontier.pop() {
+                for generator in &generators {
+                    let product: Vec<usize> = element.iter().map(|&i| generator[i]).collect();
+                    if group.insert(product.clone()) {
+                        frontier.push(product);
+                    }
+                }
+            }
+            assert_eq!(group.len(), poly.symmetry.order, "{}", solid.name());
+        }
+    }
+
+    #[test]
+    fn test_full_symmetry_group() {
+        // (rotation orders, mirror planes)
+        let expected = [
+            (Solid::Tetrahedron, [1, 3, 8, 0, 0, 0], 6),
+            (Solid::Cube, [1, 9, 8, 6, 0, 0], 9),
+            (Solid::Octahedron, [1, 9, 8, 6, 0, 0], 9),
+            (Solid::Dodecahedron, [1, 15, 20, 0, 24, 0], 15),
+            (Solid::Icosahedron, [1, 15, 20, 0, 24, 0], 15),
+        ];
+        for (solid, orders, mirrors) in expected {
+            let poly = solid.polyhedron();
+            let rotations = poly.rotations();
+            assert_eq!(rotations.len(), poly.symmetry.order);
+            assert_eq!(rotations[0].angle, 0.0);
+
+            let mut counts = [0; 6];
+            for rotation in &rotations {
+                counts[rotation.order() as usize - 1] += 1;
+                assert!(rotation.angle <= PI + EPSILON);
+                if rotation.angle > 0.0 {
+                    assert!(poly.feature_along(rotation.axis).is_some());
+                }
+            }
+            assert_eq!(counts, orders, "{}", solid.name());
+            assert_eq!(poly.reflections().len(), mirrors, "{}", solid.name());
+
+            let symmetries = poly.symmetries();
+            assert_eq!(symmetries.len(), poly.symmetry.order + mirrors);
+            assert!(symmetries
+                .iter()
+                .all(|symmetry| poly.vertex_permutation(*symmetry).is_some()));
+        }
+    }
+
+    #[test]
+    fn test_duals() {
+        for solid in Solid::ALL {
+            let poly = solid.polyhedron();
+            let dual = solid.dual().polyhedron();
+            assert_eq!(poly.faces.len(), dual.vertices.len());
+            assert_eq!(poly.symmetry.name, dual.symmetry.name);
+            // Face centers point the same way as the dual's vertices, the
+            // tetrahedron's own face centers sit opposite its vertices
+            let flip = if solid == Solid::Tetrahedron {
+                -1.0
+            } else {
+                1.0
+            };
+            for face in 0..poly.faces.len() {
+                let center = poly.face_center(face).normalize() * flip;
+                assert!(dual.vertex_at(center).is_some(), "{}", solid.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Turns 2D shapes into indexed `Vertex2` triangle meshes: thick polylines with
+//! joins and caps, circles and arcs, filled polygons and arrows.
+//!
+//! Strokes are built from one quad per segment plus wedges filling the outside
+//! of each corner, so translucent strokes darken slightly where pieces overlap.
+//! Every triangle is emitted counter clockwise.
+
+use std::f32::consts::{PI, TAU};
+
+use glam::Vec2;
+
+use crate::math_object::Mesh;
+use crate::types::Vertex2;
+
+/// Segments shorter than this are dropped as duplicate points
+const EPSILON: f32 = 1e-6;
+
+/// How the outside of a corner between two segments is filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Extends the edges until they meet, falling back to `Bevel` past the miter limit
+    Miter,
+    Bevel,
+    Round,
+}
+
+/// How the ends of an open polyline are finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends flush with the first and last points
+    Butt,
+    /// Extends half the width past the ends
+    Square,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter allowed, as a multiple of half the width
+    pub miter_limit: f32,
+    /// Largest distance allowed between a round edge and its true curve
+    pub tolerance: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 0.01,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            tolerance: 0.001,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// Size of an arrowhead, `length` along the arrow and `width` across its base
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrowHead {
+    pub length: f32,
+    pub width: f32,
+}
+
+/// Number of straight segments needed to approximate an arc within `tolerance`
+pub fn arc_segments(radius: f32, sweep: f32, tolerance: f32) -> u32 {
+    let radius = radius.abs();
+    if radius <= tolerance {
+        return 1;
+    }
+    // The sagitta of a chord spanning `step` radians is r (1 - cos(step / 2))
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep.abs() / step).ceil() as u32).clamp(1, 1024)
+}
+
+/// Points along an arc starting at angle `start` and turning counter clockwise by `sweep`
+pub fn arc_points(center: Vec2, radius: f32, start: f32, sweep: f32, tolerance: f32) -> Vec<Vec2> {
+    let segments = arc_segments(radius, sweep, tolerance);
+    (0..=segments)
+        .map(|i| center + Vec2::from_angle(start + sweep * i as f32 / segments as f32) * radius)
+        .collect()
+}
+
+pub fn stroke_segment(a: Vec2, b: Vec2, style: &StrokeStyle) -> Mesh<Vertex2> {
+    stroke_polyline(&[a, b], false, style)
+}
+
+/// Strokes the path through `points`, joining the last point back to the first when `closed`
+pub fn stroke_polyline(points: &[Vec2], closed: bool, style: &StrokeStyle) -> Mesh<Vertex2> {
+    let mut mesh = Mesh::new();
+    let mut points = points.to_vec();
+    points.dedup_by(|a, b| a.distance(*b) < EPSILON);
+    if closed && points.len() > 2 && points[0].distance(points[points.len() - 1]) < EPSILON {
+        points.pop();
+    }
+    let closed = closed && points.len() > 2;
+    if points.len() < 2 || style.width <= 0.0 {
+        return mesh;
+    }
+
+    let half = style.width / 2.0;
+    let segment_count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+
+    for i in 0..segment_count {
+        let (a, b) = segment(i);
+        let normal = (b - a).normalize().perp() * half;
+        push_quad(&mut mesh, [a - normal, b - normal, b + normal, a + normal]);
+    }
+
+    // Corner i sits between segment i - 1 and segment i
+    let corners = if closed {
+        0..points.len()
+    } else {
+        1..points.len() - 1
+    };
+    for i in corners {
+        let previous = segment((i + segment_count - 1) % segment_count);
+        let next = segment(i);
+        let incoming = (previous.1 - previous.0).normalize();
+        let outgoing = (next.1 - next.0).normalize();
+        push_join(&mut mesh, points[i], incoming, outgoing, style);
+    }
+
+    if !closed {
+        let last = points.len() - 1;
+        let start_direction = (points[0] - points[1]).normalize();
+        let end_direction = (points[last] - points[last - 1]).normalize();
+        push_cap(&mut mesh, points[0], start_direction, style);
+        push_cap(&mut mesh, points[last], end_direction, style);
+    }
+    mesh
+}
+
+/// An outline of an arc, see `arc_points`
+pub fn stroke_arc(
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    sweep: f32,
+    style: &StrokeStyle,
+) -> Mesh<Vertex2> {
+    let closed = sweep.abs() >= TAU;
+    let mut points = arc_points(
+        center,
+        radius,
+        start,
+        sweep.clamp(-TAU, TAU),
+        style.tolerance,
+    );
+    if closed {
+        points.pop();
+    }
+    stroke_polyline(&points, closed, style)
+}
+
+pub fn stroke_circle(center: Vec2, radius: f32, style: &StrokeStyle) -> Mesh<Vertex2> {
+    stroke_arc(center, radius, 0.0, TAU, style)
+}
+
+pub fn fill_circle(center: Vec2, radius: f32, tolerance: f32) -> Mesh<Vertex2> {
+    let segments = arc_segments(radius, TAU, tolerance).max(3);
+    let points: Vec<Vec2> = (0..segments)
+        .map(|i| center + Vec2::from_angle(TAU * i as f32 / segments as f32) * radius)
+        .collect();
+    fill_convex(&points)
+}
+
+/// A pie slice from angle `start` turning counter clockwise by `sweep`
+pub fn fill_sector(
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) -> Mesh<Vertex2> {
+    let mut points = vec![center];
+    points.extend(arc_points(center, radius, start, sweep, tolerance));
+    fill_convex(&points)
+}
+
+/// Fills a simple polygon, convex or not, given its corners in either winding order
+pub fn fill_polygon(points: &[Vec2]) -> Mesh<Vertex2> {
+    let mut mesh = Mesh::new();
+    if points.len() < 3 {
+        return mesh;
+    }
+    mesh.vertices = points.iter().map(|p| Vertex2::new(p.to_array())).collect();
+
+    // Ear clipping on a counter clockwise list of the remaining corners
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let [a, b, c] = [(i + n - 1) % n, i, (i + 1) % n].map(|j| remaining[j]);
+            let [pa, pb, pc] = [a, b, c].map(|j| points[j]);
+            (pb - pa).perp_dot(pc - pb) > EPSILON
+                && remaining
+                    .iter()
+                    .filter(|&&j| j != a && j != b && j != c)
+                    .all(|&j| !in_triangle(points[j], pa, pb, pc))
+        });
+        // Self intersecting or degenerate input has no ears left, clip anyway
+        let i = ear.unwrap_or(0);
+        let [a, b, c] = [(i + n - 1) % n, i, (i + 1) % n].map(|j| remaining[j] as u32);
+        mesh.indices.extend_from_slice(&[a, b, c]);
+        remaining.remove(i);
+    }
+    mesh.indices
+        .extend(remaining.iter().map(|&index| index as u32));
+    mesh
+}
+
+/// A triangle with its point at `tip`, facing along `direction`
+pub fn arrowhead(tip: Vec2, direction: Vec2, head: ArrowHead) -> Mesh<Vertex2> {
+    let mut mesh = Mesh::new();
+    let Some(direction) = direction.try_normalize() else {
+        return mesh;
+    };
+    let base = tip - direction * head.length;
+    let side = direction.perp() * head.width / 2.0;
+    push_triangle(&mut mesh, tip, base + side, base - side);
+    mesh
+}
+
+/// A stroked shaft from `from` ending in an arrowhead whose tip is at `to`
+pub fn arrow(from: Vec2, to: Vec2, style: &StrokeStyle, head: ArrowHead) -> Mesh<Vertex2> {
+    let length = from.distance(to);
+    if length < EPSILON {
+        return Mesh::new();
+    }
+    let direction = (to - from) / length;
+    // The shaft stops inside the head so the butt end doesn't poke out of its sides
+    let shaft_end = to - direction * head.length.min(length) * 0.5;
+    let mut mesh = stroke_segment(from, shaft_end, &style.with_cap(LineCap::Butt));
+    mesh.append(arrowhead(to, direction, head));
+    mesh
+}
+
+fn angle(v: Vec2) -> f32 {
+    v.y.atan2(v.x)
+}
+
+fn signed_area(points: &[Vec2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Whether `p` lies inside or on the edge of the counter clockwise triangle `abc`
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0.0
+        && (c - b).perp_dot(p - b) >= 0.0
+        && (a - c).perp_dot(p - c) >= 0.0
+}
+
+fn fill_convex(points: &[Vec2]) -> Mesh<Vertex2> {
+    let mut mesh = Mesh::new();
+    for i in 1..points.len().saturating_sub(1) {
+        push_triangle(&mut mesh, points[0], points[i], points[i + 1]);
+    }
+    mesh
+}
+
+fn push_vertex(mesh: &mut Mesh<Vertex2>, position: Vec2) -> u32 {
+    mesh.vertices.push(Vertex2::new(position.to_array()));
+    mesh.vertices.len() as u32 - 1
+}
+
+/// Adds a triangle, flipping it if needed so it winds counter clockwise
+fn push_triangle(mesh: &mut Mesh<Vertex2>, a: Vec2, b: Vec2, c: Vec2) {
+    let (b, c) = if (b - a).perp_dot(c - a) < 0.0 {
+        (c, b)
+    } else {
+        (b, c)
+    };
+    let first = push_vertex(mesh, a);
+    push_vertex(mesh, b);
+    push_vertex(mesh, c);
+    mesh.indices
+        .extend_from_slice(&[first, first + 1, first + 2]);
+}
+
+/// Corners in counter clockwise order
+fn push_quad(mesh: &mut Mesh<Vertex2>, corners: [Vec2; 4]) {
+    let first = mesh.vertices.len() as u32;
+    corners.into_iter().for_each(|corner| {
+        push_vertex(mesh, corner);
+    });
+    mesh.indices
+        .extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+}
+
+/// Triangles fanning around `center` along an arc, used for round joins and caps
+fn push_fan(
+    mesh: &mut Mesh<Vertex2>,
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) {
+    let points = arc_points(center, radius, start, sweep, tolerance);
+    let first = push_vertex(mesh, center);
+    points.iter().for_each(|&point| {
+        push_vertex(mesh, point);
+    });
+    for i in 1..points.len() as u32 {
+        let (b, c) = if sweep >= 0.0 { (i, i + 1) } else { (i + 1, i) };
+        mesh.indices
+            .extend_from_slice(&[first, first + b, first + c]);
+    }
+}
+
+/// Fills the gap on the outside of the corner at `point`
+fn push_join(
+    mesh: &mut Mesh<Vertex2>,
+    point: Vec2,
+    incoming: Vec2,
+    outgoing: Vec2,
+    style: &StrokeStyle,
+) {
+    let turn = incoming.perp_dot(outgoing);
+    let straight = turn.abs() < EPSILON && incoming.dot(outgoing) > 0.0;
+    if straight {
+        return;
+    }
+
+    let half = style.width / 2.0;
+    // Turning left opens a gap on the right side and the other way around
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let before = point + incoming.perp() * half * side;
+    let after = point + outgoing.perp() * half * side;
+
+    match style.join {
+        LineJoin::Round => {
+            let start = angle(before - point);
+            let mut sweep = angle(after - point) - start;
+            // Go the short way around, which is always the outside of the corner
+            if sweep > PI {
+                sweep -= TAU;
+            } else if sweep < -PI {
+                sweep += TAU;
+            }
+            push_fan(mesh, point, half, start, sweep, style.tolerance);
+        }
+        LineJoin::Miter => {
+            let bisector = (incoming.perp() + outgoing.perp()).try_normalize();
+            let scale = bisector.map(|bisector| 1.0 / bisector.dot(incoming.perp()));
+            match bisector.zip(scale) {
+                Some((bisector, scale)) if scale <= style.miter_limit => {
+                    let tip = point + bisector * half * scale * side;
+                    push_triangle(mesh, point, before, tip);
+                    push_triangle(mesh, point, tip, after);
+                }
+                _ => push_triangle(mesh, point, before, after),
+            }
+        }
+        LineJoin::Bevel => push_triangle(mesh, point, before, after),
+    }
+}
+
+/// Finishes the end at `point`, `direction` points away from the line
+fn push_cap(mesh: &mut Mesh<Vertex2>, point: Vec2, direction: Vec2, style: &StrokeStyle) {
+    let half = style.width / 2.0;
+    let normal = direction.perp() * half;
+    match style.cap {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            let out = point + direction * half;
+            push_quad(
+                mesh,
+                [point - normal, out - normal, out + normal, point + normal],
+            );
+        }
+        LineCap::Round => {
+            let start = angle(-normal);
+            push_fan(mesh, point, half, start, PI, style.tolerance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(mesh: &Mesh<Vertex2>) -> Vec<Vec2> {
+        mesh.vertices.iter().map(|v| Vec2::from(v.pos)).collect()
+    }
+
+    fn area(mesh: &Mesh<Vertex2>) -> f32 {
+        let points = positions(mesh);
+        mesh.indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| points[t[i] as usize]);
+                (b - a).perp_dot(c - a) / 2.0
+            })
+            .sum()
+    }
+
+    fn assert_counter_clockwise(mesh: &Mesh<Vertex2>) {
+        let points = positions(mesh);
+        assert_eq!(mesh.indices.len() % 3, 0);
+        for t in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| points[t[i] as usize]);
+            assert!((b - a).perp_dot(c - a) >= -1e-6, "{:?} winds clockwise", t);
+        }
+    }
+
+    #[test]
+    fn test_segment() {
+        let mesh = stroke_segment(Vec2::ZERO, Vec2::new(1.0, 0.0), &StrokeStyle::new(0.2));
+        assert_eq!(
+            positions(&mesh),
+            [
+                Vec2::new(0.0, -0.1),
+                Vec2::new(1.0, -0.1),
+                Vec2::new(1.0, 0.1),
+                Vec2::new(0.0, 0.1),
+            ]
+        );
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+
+        let square = stroke_segment(
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            &StrokeStyle::new(0.2).with_cap(LineCap::Square),
+        );
+        assert_eq!(square.vertices.len(), 12);
+        assert!((area(&square) - 0.24).abs() < 1e-5);
+
+        // Duplicate points collapse, leaving nothing to draw
+        assert!(stroke_polyline(&[Vec2::ONE, Vec2::ONE], false, &StrokeStyle::new(0.2)).is_empty());
+    }
+
+    #[test]
+    fn test_joins() {
+        let corner = [Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0)];
+        let style = StrokeStyle::new(0.2);
+
+        // Two quads plus a miter made of two triangles reaching the outer corner
+        let miter = stroke_polyline(&corner, false, &style);
+        assert_eq!(miter.vertices.len(), 8 + 6);
+        assert_eq!(miter.triangle_count(), 6);
+        assert!(positions(&miter).contains(&Vec2::new(1.1, -0.1)));
+        assert_counter_clockwise(&miter);
+
+        let bevel = stroke_polyline(&corner, false, &style.with_join(LineJoin::Bevel));
+        assert_eq!(bevel.triangle_count(), 5);
+        assert!((area(&miter) - area(&bevel) - 0.005).abs() < 1e-5);
+
+        // A hairpin turn is far past the miter limit
+        let hairpin = [Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.01)];
+        assert_eq!(stroke_polyline(&hairpin, false, &style).triangle_count(), 5);
+
+        let round = stroke_polyline(&corner, false, &style.with_join(LineJoin::Round));
+        assert!(round.triangle_count() > 5);
+        assert_counter_clockwise(&round);
+        // The fan stays on the circle around the corner
+        let far = positions(&round)[8..]
+            .iter()
+            .map(|p| p.distance(Vec2::new(1.0, 0.0)))
+            .fold(0.0, f32::max);
+        assert!(far < 0.1 + 1e-5);
+    }
+
+    #[test]
+    fn test_closed_polyline() {
+        let square = [
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::ZERO,
+        ];
+        let mesh = stroke_polyline(
+            &square,
+            true,
+            &StrokeStyle::new(0.2).with_cap(LineCap::Round),
+        );
+        // Four sides and four mitered corners, caps are ignored when closed
+        assert_eq!(mesh.triangle_count(), 4 * 2 + 4 * 2);
+        assert_counter_clockwise(&mesh);
+        // The outline covers the band between a 1.2 and a 0.8 square, sides overlap inside
+        assert!(area(&mesh) >= 1.44 - 0.64 - 1e-5);
+    }
+
+    #[test]
+    fn test_round_shapes() {
+        let circle = fill_circle(Vec2::new(1.0, 1.0), 1.0, 0.001);
+        let segments = arc_segments(1.0, TAU, 0.001);
+        assert_eq!(circle.triangle_count(), segments as usize - 2);
+        assert!((area(&circle) - PI).abs() < 0.01);
+        assert_counter_clockwise(&circle);
+
+        // Clockwise sweeps still wind counter clockwise
+        let quarter = fill_sector(Vec2::ZERO, 1.0, 0.0, -PI / 2.0, 0.001);
+        assert!((area(&quarter) - PI / 4.0).abs() < 0.01);
+        assert_counter_clockwise(&quarter);
+
+        let ring = stroke_circle(Vec2::ZERO, 1.0, &StrokeStyle::new(0.1));
+        assert!((area(&ring) - PI * (1.05f32.powi(2) - 0.95f32.powi(2))).abs() < 0.02);
+
+        let round_cap = stroke_segment(
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            &StrokeStyle::new(0.2).with_cap(LineCap::Round),
+        );
+        assert!((area(&round_cap) - (0.2 + PI * 0.01)).abs() < 1e-3);
+        assert_counter_clockwise(&round_cap);
+    }
+
+    #[test]
+    fn test_fill_polygon() {
+        // An L shape listed clockwise, its reflex corner needs ear clipping
+        let l_shape = [
+            Vec2::ZERO,
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(2.0, 0.0),
+        ];
+        let mesh = fill_polygon(&l_shape);
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.triangle_count(), 4);
+        assert!((area(&mesh) - 3.0).abs() < 1e-5);
+        assert_counter_clockwise(&mesh);
+        assert!(fill_polygon(&l_shape[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_arrow() {
+        let head = ArrowHead {
+            length: 0.2,
+            width: 0.1,
+        };
+        let mesh = arrow(
+            Vec2::ZERO,
+            Vec2::new(0.0, 1.0),
+            &StrokeStyle::new(0.02),
+            head,
+        );
+        let points = positions(&mesh);
+        assert_eq!(mesh.triangle_count(), 3);
+        assert!(points.contains(&Vec2::new(0.0, 1.0)));
+        let top = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert_eq!(top, 1.0);
+        // The shaft ends inside the head
+        assert!(points[..4].iter().all(|p| p.y <= 0.9 + 1e-6));
+        assert_counter_clockwise(&mesh);
+    }
+}
@@ -0,0 +1,318 @@
+//! The Cayley graph of a small group, laid out by pushing elements apart and
+//! pulling generator edges together. Each generator has its own color, edges
+//! point from `g` to `g s`, and clicking an element shows it and its word.
+
+use glam::{Vec2, Vec3};
+use winit::dpi::PhysicalSize;
+use winit::event::TouchPhase;
+
+use crate::algebra::cayley::{generator_name, CayleyGraph};
+use crate::algebra::finite::FiniteGroup;
+use crate::algebra::random::Random;
+use crate::camera::{Camera, OrbitController};
+use crate::engine::{
+    Engine, EngineObject, EventResponse, InputEvent, PointerButton, RenderData, UpdateContext,
+};
+use crate::math_object::{MathObject, Mesh, Node};
+use crate::text::{HAlign, Text, TextRenderer, VAlign};
+use crate::types::{Instance3, SegmentInstance};
+
+use super::force::ForceLayout;
+use super::instanced::InstancedMeshRenderer;
+
+/// Layout steps taken per frame while the graph settles
+const STEPS_PER_FRAME: usize = 2;
+const CAMERA_DISTANCE: f32 = 3.5;
+/// Sizes in layout units, where edges are about 1 long
+const NODE_RADIUS: f32 = 0.16;
+const EDGE_RADIUS: f32 = 0.035;
+const ARROW_LENGTH: f32 = 0.22;
+const ARROW_RADIUS: f32 = 0.09;
+const SELECTED_SCALE: f32 = 1.4;
+/// Pixels the pointer may move between press and release and still click
+const CLICK_SLOP: f32 = 5.0;
+/// Pixels from an element's center that still pick it
+const PICK_RADIUS: f32 = 18.0;
+const MARGIN: f32 = 20.0;
+const LEGEND_SIZE: f32 = 20.0;
+const LABEL_SIZE: f32 = 18.0;
+
+const GENERATOR_COLORS: [[f32; 4]; 6] = [
+    [0.9, 0.35, 0.3, 1.0],
+    [0.3, 0.6, 0.95, 1.0],
+    [0.35, 0.8, 0.4, 1.0],
+    [0.95, 0.75, 0.25, 1.0],
+    [0.75, 0.45, 0.9, 1.0],
+    [0.3, 0.85, 0.85, 1.0],
+];
+const IDENTITY_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const ELEMENT_COLOR: [f32; 4] = [0.6, 0.62, 0.68, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.9, 0.45, 1.0];
+const LABEL_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.12, 0.9];
+
+/// A Cayley graph with its elements as instanced spheres and its edges as
+/// instanced tubes and cones, dragging orbits the camera
+pub struct CayleyDiagram {
+    group: FiniteGroup,
+    graph: CayleyGraph,
+    layout: ForceLayout,
+    /// Multiplies layout positions so the graph fits in the unit sphere
+    scale: f32,
+    /// Holds the camera and orbit controller, the graph itself is instanced
+    object: MathObject,
+    spheres: InstancedMeshRenderer<Instance3>,
+    tubes: InstancedMeshRenderer<SegmentInstance>,
+    arrows: InstancedMeshRenderer<SegmentInstance>,
+    text: TextRenderer,
+    selected: Option<usize>,
+    /// Where the pointer or finger went down, for telling clicks from drags
+    press: Option<[f32; 2]>,
+    size: [f32; 2],
+}
+
+impl CayleyDiagram {
+    /// The Cayley graph for the generators `group` was given by, on a plane when `flat`
+    pub fn new(engine: &Engine, group: FiniteGroup, flat: bool) -> Self {
+        let graph = CayleyGraph::new(&group, group.generators());
+        let edges = graph
+            .edges()
+            .iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect();
+        let layout = ForceLayout::new(graph.vertex_count(), edges, flat, &mut Random::new(1));
+
+        let (yaw, pitch) = if flat { (0.0, 0.0) } else { (0.5, 0.35) };
+        let camera = Camera::perspective(Vec3::new(0.0, 0.0, CAMERA_DISTANCE), Vec3::ZERO, 0.8);
+        let object = MathObject::new(engine, Node::new("root"))
+            .with_camera(camera)
+            .with_orbit(OrbitController::new(CAMERA_DISTANCE).with_angles(yaw, pitch));
+
+        let size = engine.size();
+        let (start, end) = (Vec3::ZERO, Vec3::Z);
+        Self {
+            group,
+            graph,
+            layout,
+            scale: 1.0,
+            object,
+            spheres: InstancedMeshRenderer::new(
+                engine,
+                "Sphere",
+                wgpu::include_wgsl!("./spheres.wgsl"),
+                Mesh::sphere(1.0, 12, 18),
+            ),
+            tubes: InstancedMeshRenderer::new(
+                engine,
+                "Tube",
+                wgpu::include_wgsl!("./segments.wgsl"),
+                Mesh::tube(start, end, 1.0, 8),
+            ),
+            arrows: InstancedMeshRenderer::new(
+                engine,
+                "Arrow",
+                wgpu::include_wgsl!("./segments.wgsl"),
+                Mesh::cone(start, end, 1.0, 12),
+            ),
+            text: TextRenderer::new(engine),
+            selected: None,
+            press: None,
+            size: [size.width as f32, size.height as f32],
+        }
+    }
+
+    fn position(&self, element: usize) -> Vec3 {
+        self.layout.positions()[element] * self.scale
+    }
+
+    /// Tubes for the edges and arrowheads at the ends they point to. An
+    /// involution's edges come in opposite pairs, those are drawn once without arrows.
+    fn edges(&self) -> (Vec<SegmentInstance>, Vec<SegmentInstance>) {
+        let s = self.scale;
+        let (mut tubes, mut arrows) = (Vec::new(), Vec::new());
+        for edge in self.graph.edges() {
+            let generator = self.graph.generators()[edge.generator];
+            let involution = self.group.multiply(generator, generator) == self.group.identity();
+            if edge.from == edge.to || (involution && edge.from > edge.to) {
+                continue;
+            }
+            let (a, b) = (self.position(edge.from), self.position(edge.to));
+            let color = generator_color(edge.generator);
+            tubes.push(SegmentInstance::new(
+                a.to_array(),
+                b.to_array(),
+                [EDGE_RADIUS * s],
+                color,
+            ));
+            if !involution {
+                let direction = (b - a).normalize_or_zero();
+                let tip = b - direction * NODE_RADIUS * s;
+                let base = tip - direction * ARROW_LENGTH * s;
+                arrows.push(SegmentInstance::new(
+                    base.to_array(),
+                    tip.to_array(),
+                    [ARROW_RADIUS * s],
+                    color,
+                ));
+            }
+        }
+        (tubes, arrows)
+    }
+
+    fn spheres(&self) -> Vec<Instance3> {
+        (0..self.graph.vertex_count())
+            .map(|element| {
+                let (color, scale) = if self.selected == Some(element) {
+                    (SELECTED_COLOR, SELECTED_SCALE)
+                } else if element == self.group.identity() {
+                    (IDENTITY_COLOR, 1.0)
+                } else {
+                    (ELEMENT_COLOR, 1.0)
+                };
+                Instance3::new(
+                    self.position(element).to_array(),
+                    [NODE_RADIUS * self.scale * scale],
+                    color,
+                )
+            })
+            .collect()
+    }
+
+    /// Window pixel (y down) of a point, if it's in front of the camera
+    fn project(&self, point: Vec3) -> Option<[f32; 2]> {
+        let ndc = self.object.camera().view_projection().project_point3(point);
+        (0.0..=1.0).contains(&ndc.z).then(|| {
+            [
+                (ndc.x + 1.0) / 2.0 * self.size[0],
+                (1.0 - ndc.y) / 2.0 * self.size[1],
+            ]
+        })
+    }
+
+    fn element_at(&self, pixel: [f32; 2]) -> Option<usize> {
+        let pixel = Vec2::from(pixel);
+        (0..self.graph.vertex_count())
+            .filter_map(|element| {
+                let projected = self.project(self.position(element))?;
+                Some((element, pixel.distance(Vec2::from(projected))))
+            })
+            .filter(|&(_, distance)| distance <= PICK_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(element, _)| element)
+    }
+
+    fn release(&mut self, position: [f32; 2]) {
+        let Some(press) = self.press.take() else {
+            return;
+        };
+        if Vec2::from(press).distance(Vec2::from(position)) <= CLICK_SLOP {
+            self.selected = self.element_at(position);
+        }
+    }
+
+    /// Each generator in its color at the bottom left
+    fn legend(&self) -> Vec<Text> {
+        let count = self.graph.generators().len();
+        self.graph
+            .generators()
+            .iter()
+            .enumerate()
+            .map(|(i, &generator)| {
+                let y = self.size[1] - MARGIN - (count - 1 - i) as f32 * LEGEND_SIZE * 1.3;
+                let content = format!("{} = {}", generator_name(i), self.group.element(generator));
+                Text::new(content, [MARGIN, y])
+                    .with_size(LEGEND_SIZE)
+                    .with_color(generator_color(i))
+                    .with_align(HAlign::Left, VAlign::Bottom)
+            })
+            .collect()
+    }
+
+    fn selection_label(&self) -> Option<Text> {
+        let element = self.selected?;
+        let [x, y] = self.project(self.position(element))?;
+        let content = format!(
+            "{}\nWord: {}",
+            self.group.element(element),
+            self.graph.word_string(element)
+        );
+        Some(
+            Text::new(content, [x + PICK_RADIUS, y - PICK_RADIUS])
+                .with_size(LABEL_SIZE)
+                .with_background(LABEL_BACKGROUND)
+                .with_align(HAlign::Left, VAlign::Bottom),
+        )
+    }
+}
+
+impl EngineObject for CayleyDiagram {
+    fn update(&mut self, context: &UpdateContext) {
+        if !self.layout.is_settled() {
+            for _ in 0..STEPS_PER_FRAME {
+                self.layout.step();
+            }
+            self.scale = 1.0 / self.layout.radius().max(1.0);
+        }
+        self.object.update(context);
+
+        let camera = self.object.camera();
+        let spheres = self.spheres();
+        self.spheres
+            .prepare(context.device, context.queue, camera, &spheres);
+        let (tubes, arrows) = self.edges();
+        self.tubes
+            .prepare(context.device, context.queue, camera, &tubes);
+        self.arrows
+            .prepare(context.device, context.queue, camera, &arrows);
+        let legend = self.legend();
+        let label: Vec<Text> = self.selection_label().into_iter().collect();
+        self.text
+            .prepare(context.device, context.queue, &[&legend, &label]);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = [size.width as f32, size.height as f32];
+        self.object.resize(size);
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match *event {
+            InputEvent::PointerButton {
+                button: PointerButton::Left,
+                pressed,
+                position,
+            } => {
+                if pressed {
+                    self.press = Some(position);
+                } else {
+                    self.release(position);
+                }
+            }
+            InputEvent::Touch {
+                phase, position, ..
+            } => match phase {
+                TouchPhase::Started => self.press = Some(position),
+                TouchPhase::Ended => self.release(position),
+                TouchPhase::Moved => {}
+                TouchPhase::Cancelled => self.press = None,
+            },
+            InputEvent::PointerLeft => self.press = None,
+            _ => {}
+        }
+        // Dragging still orbits the camera
+        self.object.input(event)
+    }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        let mut layers = self.object.render_layers();
+        layers.extend(self.spheres.render_data());
+        layers.extend(self.tubes.render_data());
+        layers.extend(self.arrows.render_data());
+        layers.extend(self.text.render_data(0));
+        layers.extend(self.text.render_data(1));
+        layers
+    }
+}
+
+fn generator_color(generator: usize) -> [f32; 4] {
+    GENERATOR_COLORS[generator % GENERATOR_COLORS.len()]
+}
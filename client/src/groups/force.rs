@@ -0,0 +1,130 @@
+//! Force directed graph layout in the style of Fruchterman and Reingold: every
+//! pair of vertices pushes apart, edges pull their ends together, and the
+//! distance moved per step shrinks until the graph settles.
+
+use glam::Vec3;
+
+use crate::algebra::random::Random;
+
+/// Preferred edge length
+const LENGTH: f32 = 1.0;
+/// Fraction of the step size kept after each step
+const COOLING: f32 = 0.97;
+/// Step size, relative to the edge length, below which the layout counts as settled
+const SETTLED: f32 = 0.005;
+
+pub struct ForceLayout {
+    positions: Vec<Vec3>,
+    edges: Vec<(usize, usize)>,
+    /// Whether to stay on the z = 0 plane
+    flat: bool,
+    /// Largest distance a vertex can move in one step
+    temperature: f32,
+}
+
+impl ForceLayout {
+    /// Starts from random positions, in 3D unless `flat`
+    pub fn new(count: usize, edges: Vec<(usize, usize)>, flat: bool, random: &mut Random) -> Self {
+        let dimensions = if flat { 2.0 } else { 3.0 };
+        let extent = LENGTH * (count as f32).powf(1.0 / dimensions);
+        let mut coordinate = || (random.next_f32() - 0.5) * extent;
+        let positions = (0..count)
+            .map(|_| {
+                let (x, y, z) = (coordinate(), coordinate(), coordinate());
+                Vec3::new(x, y, if flat { 0.0 } else { z })
+            })
+            .collect();
+        Self {
+            positions,
+            edges,
+            flat,
+            temperature: extent / 4.0,
+        }
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.temperature < SETTLED * LENGTH
+    }
+
+    /// Distance from the center to the farthest vertex
+    pub fn radius(&self) -> f32 {
+        self.positions
+            .iter()
+            .map(|position| position.length())
+            .fold(0.0, f32::max)
+    }
+
+    pub fn step(&mut self) {
+        let count = self.positions.len();
+        let mut forces = vec![Vec3::ZERO; count];
+        for i in 0..count {
+            for j in i + 1..count {
+                let (direction, distance) = separation(self.positions[i], self.positions[j]);
+                let push = direction * (LENGTH * LENGTH / distance);
+                forces[i] += push;
+                forces[j] -= push;
+            }
+        }
+        for &(a, b) in &self.edges {
+            let (direction, distance) = separation(self.positions[a], self.positions[b]);
+            let pull = direction * (distance * distance / LENGTH);
+            forces[a] -= pull;
+            forces[b] += pull;
+        }
+
+        for (position, force) in self.positions.iter_mut().zip(forces) {
+            *position += force.clamp_length_max(self.temperature);
+            if self.flat {
+                position.z = 0.0;
+            }
+        }
+        // Keep the graph centered on the origin so the camera can orbit it
+        let center = self.positions.iter().sum::<Vec3>() / count.max(1) as f32;
+        self.positions
+            .iter_mut()
+            .for_each(|position| *position -= center);
+        self.temperature *= COOLING;
+    }
+}
+
+/// Unit vector from `b` to `a` and the distance between them, nudged apart
+/// when they coincide so they have a direction to separate in
+fn separation(a: Vec3, b: Vec3) -> (Vec3, f32) {
+    let offset = a - b;
+    let distance = offset.length();
+    if distance < 1e-4 {
+        return (Vec3::X, 1e-4);
+    }
+    (offset / distance, distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_settles_into_a_ring() {
+        let count = 8;
+        let edges = (0..count).map(|i| (i, (i + 1) % count)).collect();
+        let mut layout = ForceLayout::new(count, edges, true, &mut Random::new(3));
+        let mut steps = 0;
+        while !layout.is_settled() {
+            layout.step();
+            steps += 1;
+        }
+        assert!(steps < 1000);
+        let positions = layout.positions();
+        assert!(positions.iter().all(|position| position.z == 0.0));
+
+        let distance = |i: usize, j: usize| positions[i].distance(positions[j]);
+        let neighbours = (0..count).map(|i| distance(i, (i + 1) % count));
+        let opposite = (0..count).map(|i| distance(i, (i + count / 2) % count));
+        let longest_edge = neighbours.fold(0.0, f32::max);
+        let shortest_diameter = opposite.fold(f32::MAX, f32::min);
+        assert!(longest_edge < shortest_diameter);
+    }
+}
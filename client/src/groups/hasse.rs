@@ -0,0 +1,377 @@
+//! The subgroup lattice drawn as a Hasse diagram: each subgroup is a circle,
+//! larger subgroups higher up, with a line from each subgroup to the ones
+//! directly containing it. Hovering a circle shows what it is.
+
+use glam::{Vec2, Vec3};
+use winit::dpi::PhysicalSize;
+use winit::event::TouchPhase;
+
+use crate::algebra::finite::FiniteGroup;
+use crate::algebra::lattice::SubgroupLattice;
+use crate::camera::Camera;
+use crate::engine::{Engine, EngineObject, EventResponse, InputEvent, RenderData, UpdateContext};
+use crate::geometry::tessellate::{stroke_segment, StrokeStyle};
+use crate::math_object::{MathObject, Mesh, Node, Transform};
+use crate::text::{HAlign, Text, TextRenderer, VAlign};
+
+/// Room kept free on the left for the control panel, in pixels
+const PANEL_WIDTH: f32 = 360.0;
+const MARGIN: f32 = 48.0;
+const MAX_RADIUS: f32 = 16.0;
+const MIN_RADIUS: f32 = 4.0;
+/// Extra pixels around a circle that still count as hovering it
+const HIT_SLOP: f32 = 4.0;
+const EDGE_WIDTH: f32 = 2.0;
+/// Passes of reordering layers by their neighbours' average position
+const SWEEPS: usize = 4;
+const TOOLTIP_SIZE: f32 = 18.0;
+/// Gap between the pointer and the tooltip
+const TOOLTIP_OFFSET: f32 = 18.0;
+
+const SUBGROUP_COLOR: [f32; 4] = [0.35, 0.6, 0.9, 1.0];
+const NORMAL_COLOR: [f32; 4] = [0.95, 0.6, 0.3, 1.0];
+const HOVER_COLOR: [f32; 4] = [1.0, 0.9, 0.45, 1.0];
+const EDGE_COLOR: [f32; 4] = [0.6, 0.6, 0.65, 0.6];
+const LABEL_COLOR: [f32; 4] = [0.08, 0.08, 0.1, 1.0];
+const TOOLTIP_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.12, 0.9];
+
+/// The subgroups of a finite group, normal ones in orange, labelled with their order
+pub struct HasseDiagram {
+    group: FiniteGroup,
+    lattice: SubgroupLattice,
+    /// Positions in the unit square from `layout`
+    layout: Vec<Vec2>,
+    /// Centers in window pixels, y down
+    positions: Vec<[f32; 2]>,
+    radius: f32,
+    object: MathObject,
+    text: TextRenderer,
+    hovered: Option<usize>,
+    pointer: [f32; 2],
+    size: [f32; 2],
+    dirty: bool,
+}
+
+impl HasseDiagram {
+    pub fn new(engine: &Engine, group: FiniteGroup, lattice: SubgroupLattice) -> Self {
+        let size = engine.size();
+        let mut root = Node::new("root")
+            .with_child(Node::new("edges").with_color(EDGE_COLOR))
+            .with_child(Node::new("highlighted edges").with_color(HOVER_COLOR));
+        for i in 0..lattice.subgroups.len() {
+            root.add_child(Node::new(node_name(i)).with_geometry(Mesh::circle(
+                Vec2::ZERO,
+                1.0,
+                32,
+            )));
+        }
+        let object = MathObject::new(engine, root).with_camera(pixel_camera(size));
+
+        let mut diagram = Self {
+            layout: layout(&lattice),
+            positions: Vec::new(),
+            radius: MAX_RADIUS,
+            group,
+            lattice,
+            object,
+            text: TextRenderer::new(engine),
+            hovered: None,
+            pointer: [0.0, 0.0],
+            size: [size.width as f32, size.height as f32],
+            dirty: true,
+        };
+        diagram.arrange();
+        diagram
+    }
+
+    pub fn lattice(&self) -> &SubgroupLattice {
+        &self.lattice
+    }
+
+    /// Fits the layout into the window right of the panel
+    fn arrange(&mut self) {
+        let [width, height] = self.size;
+        let (left, right) = (
+            PANEL_WIDTH + MARGIN,
+            (width - MARGIN).max(PANEL_WIDTH + MARGIN + 1.0),
+        );
+        let (top, bottom) = (MARGIN, (height - MARGIN).max(MARGIN + 1.0));
+        self.positions = self
+            .layout
+            .iter()
+            .map(|unit| {
+                [
+                    left + unit.x * (right - left),
+                    bottom - unit.y * (bottom - top),
+                ]
+            })
+            .collect();
+
+        let layers = self.lattice.layers();
+        let widest = layers.iter().map(Vec::len).max().unwrap_or(1) as f32;
+        let horizontal = (right - left) / widest;
+        let vertical = (bottom - top) / (layers.len() as f32 - 1.0).max(1.0);
+        self.radius = (0.3 * horizontal.min(vertical)).clamp(MIN_RADIUS, MAX_RADIUS);
+        self.dirty = true;
+    }
+
+    /// Moves the circles and redraws the lines, lighting up the hovered
+    /// subgroup and its covers
+    fn refresh(&mut self) {
+        let style = StrokeStyle::new(EDGE_WIDTH).with_tolerance(0.1);
+        let height = self.size[1];
+        let world = |[x, y]: [f32; 2]| Vec2::new(x, height - y);
+        let (mut edges, mut highlighted) = (Mesh::new(), Mesh::new());
+        for &(small, large) in &self.lattice.covers {
+            let segment = stroke_segment(
+                world(self.positions[small]),
+                world(self.positions[large]),
+                &style,
+            );
+            if self.hovered == Some(small) || self.hovered == Some(large) {
+                highlighted.append(segment);
+            } else {
+                edges.append(segment);
+            }
+        }
+
+        let root = self.object.root_mut();
+        if let Some(node) = root.find_mut("edges") {
+            node.set_geometry(edges);
+        }
+        if let Some(node) = root.find_mut("highlighted edges") {
+            node.set_geometry(highlighted);
+        }
+        for (i, subgroup) in self.lattice.subgroups.iter().enumerate() {
+            let Some(node) = root.find_mut(&node_name(i)) else {
+                continue;
+            };
+            let center = world(self.positions[i]);
+            let hovered = self.hovered == Some(i);
+            let scale = if hovered { 1.25 } else { 1.0 };
+            node.transform = Transform::from_xy(center.x, center.y).with_scale(self.radius * scale);
+            node.color = match (hovered, subgroup.normal) {
+                (true, _) => HOVER_COLOR,
+                (false, true) => NORMAL_COLOR,
+                (false, false) => SUBGROUP_COLOR,
+            };
+        }
+        self.dirty = false;
+    }
+
+    fn subgroup_at(&self, position: [f32; 2]) -> Option<usize> {
+        let pointer = Vec2::from(position);
+        self.positions
+            .iter()
+            .map(|&center| pointer.distance(Vec2::from(center)))
+            .enumerate()
+            .filter(|&(_, distance)| distance <= self.radius + HIT_SLOP)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    fn hover(&mut self, hovered: Option<usize>) {
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            self.dirty = true;
+        }
+    }
+
+    fn labels(&self) -> Vec<Text> {
+        let size = (self.radius * 1.1).clamp(9.0, 16.0);
+        self.lattice
+            .subgroups
+            .iter()
+            .zip(&self.positions)
+            .map(|(subgroup, &position)| {
+                Text::new(subgroup.order().to_string(), position)
+                    .with_size(size)
+                    .with_color(LABEL_COLOR)
+                    .with_align(HAlign::Center, VAlign::Middle)
+            })
+            .collect()
+    }
+
+    fn tooltip(&self) -> Option<Text> {
+        let subgroup = &self.lattice.subgroups[self.hovered?];
+        let generators = if subgroup.generators.is_empty() {
+            "Trivial subgroup".to_string()
+        } else {
+            let generators: Vec<String> = subgroup
+                .generators
+                .iter()
+                .map(|&g| self.group.element(g).to_string())
+                .collect();
+            format!("Generated by {}", generators.join(", "))
+        };
+        let content = format!(
+            "Order {}, index {}\n{}\n{}",
+            subgroup.order(),
+            subgroup.index,
+            if subgroup.normal {
+                "Normal"
+            } else {
+                "Not normal"
+            },
+            generators
+        );
+        // Keep the tooltip on the side of the pointer with more room
+        let [x, y] = self.pointer;
+        let (h_align, x) = if x > self.size[0] * 0.6 {
+            (HAlign::Right, x - TOOLTIP_OFFSET)
+        } else {
+            (HAlign::Left, x + TOOLTIP_OFFSET)
+        };
+        let (v_align, y) = if y > self.size[1] * 0.6 {
+            (VAlign::Bottom, y - TOOLTIP_OFFSET)
+        } else {
+            (VAlign::Top, y + TOOLTIP_OFFSET)
+        };
+        Some(
+            Text::new(content, [x, y])
+                .with_size(TOOLTIP_SIZE)
+                .with_background(TOOLTIP_BACKGROUND)
+                .with_align(h_align, v_align),
+        )
+    }
+}
+
+impl EngineObject for HasseDiagram {
+    fn update(&mut self, context: &UpdateContext) {
+        if self.dirty {
+            self.refresh();
+        }
+        self.object.update(context);
+
+        let labels = self.labels();
+        let tooltip: Vec<Text> = self.tooltip().into_iter().collect();
+        self.text
+            .prepare(context.device, context.queue, &[&labels, &tooltip]);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = [size.width as f32, size.height as f32];
+        *self.object.camera_mut() = pixel_camera(size);
+        self.object.resize(size);
+        self.arrange();
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match *event {
+            InputEvent::PointerMoved { position, .. } => {
+                self.pointer = position;
+                self.hover(self.subgroup_at(position));
+            }
+            InputEvent::PointerLeft => self.hover(None),
+            // Without a pointer to hover, tapping a subgroup shows it instead
+            InputEvent::Touch {
+                phase: TouchPhase::Started,
+                position,
+                ..
+            } => {
+                self.pointer = position;
+                self.hover(self.subgroup_at(position));
+            }
+            _ => {}
+        }
+        EventResponse::Ignored
+    }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        let mut layers = self.object.render_layers();
+        layers.extend(self.text.render_data(0));
+        layers.extend(self.text.render_data(1));
+        layers
+    }
+}
+
+/// Looks at the window with one world unit per pixel and the origin at the
+/// bottom left corner, so window pixel `(x, y)` is world `(x, height - y)`
+fn pixel_camera(size: PhysicalSize<u32>) -> Camera {
+    let (width, height) = (size.width as f32, size.height.max(1) as f32);
+    let center = Vec3::new(width / 2.0, height / 2.0, 0.0);
+    let mut camera = Camera::orthographic(center + Vec3::Z, center, height);
+    camera.set_size(size);
+    camera
+}
+
+fn node_name(subgroup: usize) -> String {
+    format!("node {subgroup}")
+}
+
+/// Positions in the unit square with y up. Layers are evenly spaced from the
+/// trivial group at the bottom to the whole group at the top, and each layer is
+/// sorted by the average position of its neighbours to keep lines from crossing.
+fn layout(lattice: &SubgroupLattice) -> Vec<Vec2> {
+    let count = lattice.subgroups.len();
+    let mut below = vec![Vec::new(); count];
+    let mut above = vec![Vec::new(); count];
+    for &(small, large) in &lattice.covers {
+        below[large].push(small);
+        above[small].push(large);
+    }
+
+    let mut layers = lattice.layers();
+    let mut x = vec![0.5; count];
+    layers.iter().for_each(|layer| spread(layer, &mut x));
+    for sweep in 0..SWEEPS {
+        // Upward sweeps follow the layer below, downward ones the layer above
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..layers.len()).collect(), &below)
+        } else {
+            ((0..layers.len().saturating_sub(1)).rev().collect(), &above)
+        };
+        for k in order {
+            let key = |i: usize| {
+                let neighbours: &Vec<usize> = &neighbours[i];
+                if neighbours.is_empty() {
+                    x[i]
+                } else {
+                    neighbours.iter().map(|&j| x[j]).sum::<f32>() / neighbours.len() as f32
+                }
+            };
+            let mut keyed: Vec<(f32, usize)> = layers[k].iter().map(|&i| (key(i), i)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[k] = keyed.into_iter().map(|(_, i)| i).collect();
+            spread(&layers[k], &mut x);
+        }
+    }
+
+    let top = (layers.len() as f32 - 1.0).max(1.0);
+    let mut positions = vec![Vec2::ZERO; count];
+    for (k, layer) in layers.iter().enumerate() {
+        for &i in layer {
+            positions[i] = Vec2::new(x[i], k as f32 / top);
+        }
+    }
+    positions
+}
+
+/// Spaces a layer evenly across the unit interval in its current order
+fn spread(layer: &[usize], x: &mut [f32]) {
+    for (slot, &i) in layer.iter().enumerate() {
+        x[i] = (slot as f32 + 0.5) / layer.len() as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::group::PermGroup;
+
+    #[test]
+    fn test_layout_goes_up_the_lattice() {
+        let group = FiniteGroup::new(&PermGroup::symmetric(4)).unwrap();
+        let lattice = SubgroupLattice::new(&group).unwrap();
+        let positions = layout(&lattice);
+        assert_eq!(positions[0], Vec2::new(0.5, 0.0));
+        assert_eq!(positions[positions.len() - 1], Vec2::new(0.5, 1.0));
+        for &(small, large) in &lattice.covers {
+            assert!(positions[small].y < positions[large].y);
+        }
+        for (i, a) in positions.iter().enumerate() {
+            assert!(a.x > 0.0 && a.x < 1.0);
+            assert!(positions[..i].iter().all(|b| a.distance(*b) > 1e-3));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Many copies of one mesh in one draw call, placed by per-instance data. The
+//! Cayley diagram draws its spheres, tubes and arrows this way.
+
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::engine::{solid_depth_state, Engine, InstanceBuffer, RenderData};
+use crate::math_object::Mesh;
+use crate::types::{Instance, Vertex3};
+
+/// Draws `mesh` once per `I`, with a shader that reads the camera from group 1
+/// and the instance after `Vertex3`. The `Camera` is copied in every frame.
+pub struct InstancedMeshRenderer<I: Instance> {
+    render_pipeline: wgpu::RenderPipeline,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+    instance_buffer: InstanceBuffer<I>,
+}
+
+impl<I: Instance> InstancedMeshRenderer<I> {
+    pub fn new(
+        engine: &Engine,
+        label: &'static str,
+        shader: wgpu::ShaderModuleDescriptor,
+        mesh: Mesh<Vertex3>,
+    ) -> Self {
+        let device = engine.device();
+        let shader = device.create_shader_module(shader);
+
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some(&format!("{label} Camera Layout")),
+        });
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Camera")),
+            contents: bytemuck::cast_slice(&[Camera::default().uniform()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some(&format!("{label} Camera Bind Group")),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{label} Layout")),
+            bind_group_layouts: &[engine.uniform_bind_group(), &camera_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{label} Pipeline")),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex3::desc(), I::layout()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(solid_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: engine.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: engine.surface_format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Vertices")),
+            contents: bytemuck::cast_slice(&mesh.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Indices")),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            render_pipeline,
+            camera_buffer,
+            camera_bind_group,
+            vertex_buffer,
+            index_buffer,
+            num_indices: mesh.indices.len() as u32,
+            instance_buffer: InstanceBuffer::new(device, 64, label),
+        }
+    }
+
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera: &Camera,
+        instances: &[I],
+    ) {
+        queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[camera.uniform()]),
+        );
+        self.instance_buffer.write(device, queue, instances);
+    }
+
+    pub fn render_data(&self) -> Option<RenderData<'_>> {
+        if self.instance_buffer.is_empty() {
+            return None;
+        }
+        Some(RenderData {
+            render_pipeline: &self.render_pipeline,
+            bind_groups: vec![&self.camera_bind_group],
+            vertex_buffers: vec![&self.vertex_buffer],
+            instance_buffer: Some(self.instance_buffer.buffer()),
+            index_buffer: Some(&self.index_buffer),
+            index_format: wgpu::IndexFormat::Uint32,
+            num_vertices: 0,
+            num_indices: self.num_indices,
+            instances: self.instance_buffer.instances(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_shaders_compile() {
+        crate::engine::validate_shader(include_str!("spheres.wgsl"));
+        crate::engine::validate_shader(include_str!("segments.wgsl"));
+    }
+}
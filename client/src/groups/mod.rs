@@ -0,0 +1,12 @@
+//! Pictures of finite groups: the subgroup lattice and, built on the same
+//! element tables, the Cayley graph and Cayley table
+
+mod cayley;
+mod force;
+mod hasse;
+mod instanced;
+mod table;
+
+pub use cayley::CayleyDiagram;
+pub use hasse::HasseDiagram;
+pub use table::{CayleyTable, Coloring, TableControls};
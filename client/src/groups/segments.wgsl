@@ -0,0 +1,63 @@
+struct Uniforms {
+    delta_time: f32,
+    run_time: f32,
+    width: f32,
+    height: f32
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+    eye: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+// The unit mesh's z axis is stretched from `start` to `end` and its x and y
+// axes are scaled by `radius` around it
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) start: vec3<f32>,
+    @location(3) end: vec3<f32>,
+    @location(4) radius: f32,
+    @location(5) color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let axis = end - start;
+    let span = length(axis);
+    let w = axis / span;
+    var helper = vec3<f32>(0.0, 1.0, 0.0);
+    if abs(w.y) > 0.9 {
+        helper = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    let u = normalize(cross(helper, w));
+    let v = cross(w, u);
+
+    let world = start + (u * position.x + v * position.y) * radius + w * (position.z * span);
+    out.position = camera.view_projection * vec4<f32>(world, 1.0);
+    out.world = world;
+    // Normals take the inverse of the stretch so cones keep their slope
+    out.normal = (u * normal.x + v * normal.y) / radius + w * (normal.z / span);
+    out.color = color;
+    return out;
+}
+
+// Lit from the camera like the solids of a MathObject
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let to_eye = normalize(camera.eye.xyz - in.world);
+    let shade = 0.35 + 0.65 * max(dot(normalize(in.normal), to_eye), 0.0);
+    return vec4<f32>(in.color.rgb * shade, in.color.a);
+}
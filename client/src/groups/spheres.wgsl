@@ -0,0 +1,49 @@
+struct Uniforms {
+    delta_time: f32,
+    run_time: f32,
+    width: f32,
+    height: f32
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+    eye: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) offset: vec3<f32>,
+    @location(3) scale: f32,
+    @location(4) color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let world = offset + position * scale;
+    out.position = camera.view_projection * vec4<f32>(world, 1.0);
+    out.world = world;
+    out.normal = normal;
+    out.color = color;
+    return out;
+}
+
+// Lit from the camera like the solids of a MathObject
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let to_eye = normalize(camera.eye.xyz - in.world);
+    let shade = 0.35 + 0.65 * max(dot(normalize(in.normal), to_eye), 0.0);
+    return vec4<f32>(in.color.rgb * shade, in.color.a);
+}
@@ -0,0 +1,440 @@
+//! The Cayley table of a small group as a grid of colored cells, row `a` and
+//! column `b` holding `a * b`. Rows and columns can be grouped by the cosets of
+//! a subgroup: for a normal subgroup the table falls into blocks that are
+//! themselves the table of the quotient group.
+
+use std::cell::RefCell;
+use std::f32::consts::TAU;
+use std::rc::Rc;
+
+use winit::dpi::PhysicalSize;
+use winit::event::TouchPhase;
+
+use crate::algebra::finite::FiniteGroup;
+use crate::engine::{Engine, EngineObject, EventResponse, InputEvent, RenderData, UpdateContext};
+use crate::text::{HAlign, Text, TextRenderer, VAlign};
+use crate::types::Instance2;
+use crate::user_interface::{Color, RectRenderer};
+
+/// Room kept free on the left for the control panel, in pixels
+const PANEL_WIDTH: f32 = 360.0;
+const MARGIN: f32 = 40.0;
+/// Cells at least this many pixels wide get a gap between them
+const GAP_MIN_CELL: f32 = 6.0;
+const SEPARATOR_WIDTH: f32 = 2.0;
+const TOOLTIP_SIZE: f32 = 18.0;
+const TOOLTIP_OFFSET: f32 = 18.0;
+
+const BACKGROUND_COLOR: Color = [0.05, 0.05, 0.06, 1.0];
+const SEPARATOR_COLOR: Color = [0.95, 0.95, 0.95, 1.0];
+const HIGHLIGHT_COLOR: Color = [1.0, 1.0, 1.0, 0.25];
+const OUTLINE_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+const TOOLTIP_BACKGROUND: Color = [0.1, 0.1, 0.12, 0.9];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Coloring {
+    /// Every element its own color
+    #[default]
+    Element,
+    /// Elements of a coset share a hue, which shows the quotient for normal subgroups
+    Coset,
+}
+
+#[derive(Default)]
+struct ControlState {
+    subgroup: Option<Vec<usize>>,
+    coloring: Coloring,
+    changed: bool,
+}
+
+/// Handle for choosing how a `CayleyTable` is ordered and colored from other
+/// objects, usually the scene's panel
+#[derive(Clone, Default)]
+pub struct TableControls(Rc<RefCell<ControlState>>);
+
+impl TableControls {
+    /// Groups rows and columns by the left cosets of the subgroup with these elements
+    pub fn show_cosets_of(&self, subgroup: Vec<usize>) {
+        let mut state = self.0.borrow_mut();
+        state.subgroup = Some(subgroup);
+        state.changed = true;
+    }
+
+    pub fn set_coloring(&self, coloring: Coloring) {
+        let mut state = self.0.borrow_mut();
+        state.coloring = coloring;
+        state.changed = true;
+    }
+}
+
+/// How the rows and columns are ordered
+#[derive(Clone, Debug, PartialEq)]
+struct Arrangement {
+    /// Elements in row and column order, coset after coset
+    order: Vec<usize>,
+    /// The coset each element is in, by element
+    coset_of: Vec<usize>,
+    /// Positions in `order` where a coset other than the first starts
+    boundaries: Vec<usize>,
+    /// Position in `order`, by element
+    position: Vec<usize>,
+}
+
+impl Arrangement {
+    fn new(group: &FiniteGroup, subgroup: &[usize]) -> Self {
+        let cosets = group.left_cosets(subgroup);
+        let mut coset_of = vec![0; group.order()];
+        let mut boundaries = Vec::new();
+        let mut order = Vec::with_capacity(group.order());
+        for (i, coset) in cosets.iter().enumerate() {
+            if i > 0 {
+                boundaries.push(order.len());
+            }
+            coset.iter().for_each(|&element| coset_of[element] = i);
+            order.extend_from_slice(coset);
+        }
+        let mut position = vec![0; group.order()];
+        order
+            .iter()
+            .enumerate()
+            .for_each(|(i, &element)| position[element] = i);
+        Self {
+            order,
+            coset_of,
+            boundaries,
+            position,
+        }
+    }
+
+    fn coset_count(&self) -> usize {
+        self.boundaries.len() + 1
+    }
+
+    fn color(&self, element: usize, coloring: Coloring) -> Color {
+        let n = self.order.len() as f32;
+        match coloring {
+            Coloring::Element => hue(self.position[element] as f32 / n, 1.0),
+            Coloring::Coset => {
+                let cosets = self.coset_count();
+                let size = self.order.len() / cosets;
+                let within = self.position[element] % size;
+                // Darker further into the coset so its elements stay distinct
+                let shade = 1.0 - 0.45 * within as f32 / size as f32;
+                hue(self.coset_of[element] as f32 / cosets as f32, shade)
+            }
+        }
+    }
+}
+
+/// A color around the hue circle at `t` in [0, 1), scaled by `shade`
+fn hue(t: f32, shade: f32) -> Color {
+    let angle = t * TAU;
+    let channel = |offset: f32| (0.55 + 0.45 * (angle - offset).cos()) * shade;
+    [
+        channel(0.0),
+        channel(TAU / 3.0),
+        channel(2.0 * TAU / 3.0),
+        1.0,
+    ]
+}
+
+/// A Cayley table in the window right of the panel. Hovering a cell shows the product.
+pub struct CayleyTable {
+    group: FiniteGroup,
+    arrangement: Arrangement,
+    coloring: Coloring,
+    controls: TableControls,
+    /// The table itself, only rebuilt when its arrangement, colors or size change
+    cells: RectRenderer,
+    /// What follows the pointer, redrawn on every move over the table
+    highlights: RectRenderer,
+    text: TextRenderer,
+    /// Row and column of the hovered cell, 0 being the header
+    hovered: Option<(usize, usize)>,
+    pointer: [f32; 2],
+    size: [f32; 2],
+    table_dirty: bool,
+    hover_dirty: bool,
+}
+
+impl CayleyTable {
+    pub fn new(engine: &Engine, group: FiniteGroup) -> Self {
+        let size = engine.size();
+        Self {
+            arrangement: Arrangement::new(&group, &[group.identity()]),
+            group,
+            coloring: Coloring::default(),
+            controls: TableControls::default(),
+            cells: RectRenderer::new(engine),
+            highlights: RectRenderer::new(engine),
+            text: TextRenderer::new(engine),
+            hovered: None,
+            pointer: [0.0, 0.0],
+            size: [size.width as f32, size.height as f32],
+            table_dirty: true,
+            hover_dirty: true,
+        }
+    }
+
+    pub fn controls(&self) -> TableControls {
+        self.controls.clone()
+    }
+
+    /// Top left corner of the header cell and the side of a cell
+    fn grid(&self) -> ([f32; 2], f32) {
+        let cells = self.group.order() as f32 + 1.0;
+        let width = self.size[0] - PANEL_WIDTH - 2.0 * MARGIN;
+        let height = self.size[1] - 2.0 * MARGIN;
+        // Whole pixels keep the grid even
+        let cell = (width.min(height) / cells).floor().max(1.0);
+        ([PANEL_WIDTH + MARGIN, MARGIN], cell)
+    }
+
+    fn cell_rect(&self, row: usize, column: usize) -> [f32; 4] {
+        let ([x, y], cell) = self.grid();
+        let gap = if cell >= GAP_MIN_CELL { 1.0 } else { 0.0 };
+        [
+            x + column as f32 * cell,
+            y + row as f32 * cell,
+            cell - gap,
+            cell - gap,
+        ]
+    }
+
+    fn cell_at(&self, [px, py]: [f32; 2]) -> Option<(usize, usize)> {
+        let ([x, y], cell) = self.grid();
+        let cells = self.group.order() + 1;
+        let (column, row) = ((px - x) / cell, (py - y) / cell);
+        if column < 0.0 || row < 0.0 {
+            return None;
+        }
+        let (row, column) = (row as usize, column as usize);
+        (row < cells && column < cells && (row, column) != (0, 0)).then_some((row, column))
+    }
+
+    /// The element heading a row or column, counting the header as 0
+    fn element(&self, index: usize) -> usize {
+        self.arrangement.order[index - 1]
+    }
+
+    /// Cells, headers and the lines between cosets
+    fn table_rects(&self) -> Vec<Instance2> {
+        let n = self.group.order();
+        let ([x, y], cell) = self.grid();
+        let side = cell * (n + 1) as f32;
+        let mut rects = vec![Instance2::new([x, y], [side, side], BACKGROUND_COLOR)];
+        for row in 0..=n {
+            for column in 0..=n {
+                let element = match (row, column) {
+                    (0, 0) => continue,
+                    (0, column) => self.element(column),
+                    (row, 0) => self.element(row),
+                    (row, column) => self.group.multiply(self.element(row), self.element(column)),
+                };
+                let [cx, cy, width, height] = self.cell_rect(row, column);
+                let color = self.arrangement.color(element, self.coloring);
+                rects.push(Instance2::new([cx, cy], [width, height], color));
+            }
+        }
+        for &boundary in &self.arrangement.boundaries {
+            let offset = (boundary + 1) as f32 * cell - SEPARATOR_WIDTH / 2.0;
+            rects.push(Instance2::new(
+                [x + offset, y],
+                [SEPARATOR_WIDTH, side],
+                SEPARATOR_COLOR,
+            ));
+            rects.push(Instance2::new(
+                [x, y + offset],
+                [side, SEPARATOR_WIDTH],
+                SEPARATOR_COLOR,
+            ));
+        }
+        rects
+    }
+
+    /// The hovered row and column lit up, and every cell holding the same product outlined
+    fn highlight_rects(&self) -> Vec<Instance2> {
+        let Some((row, column)) = self.hovered else {
+            return Vec::new();
+        };
+        let n = self.group.order();
+        let ([x, y], cell) = self.grid();
+        let side = cell * (n + 1) as f32;
+        let mut rects = vec![
+            Instance2::new([x, y + row as f32 * cell], [side, cell], HIGHLIGHT_COLOR),
+            Instance2::new([x + column as f32 * cell, y], [cell, side], HIGHLIGHT_COLOR),
+        ];
+        if row == 0 || column == 0 {
+            return rects;
+        }
+        let product = self.group.multiply(self.element(row), self.element(column));
+        let width = (cell / 8.0).clamp(1.0, 3.0);
+        for r in 1..=n {
+            // Each product appears once in every row
+            let a = self.element(r);
+            let b = self.group.multiply(self.group.inverse(a), product);
+            let c = self.arrangement.position[b] + 1;
+            let [cx, cy, w, h] = self.cell_rect(r, c);
+            rects.extend([
+                Instance2::new([cx, cy], [w, width], OUTLINE_COLOR),
+                Instance2::new([cx, cy + h - width], [w, width], OUTLINE_COLOR),
+                Instance2::new([cx, cy], [width, h], OUTLINE_COLOR),
+                Instance2::new([cx + w - width, cy], [width, h], OUTLINE_COLOR),
+            ]);
+        }
+        rects
+    }
+
+    fn tooltip(&self) -> Option<Text> {
+        let (row, column) = self.hovered?;
+        let name = |element: usize| self.group.element(element).to_string();
+        let content = match (row, column) {
+            (0, index) | (index, 0) => {
+                let element = self.element(index);
+                format!(
+                    "{}, order {}",
+                    name(element),
+                    self.group.element_order(element)
+                )
+            }
+            (row, column) => {
+                let (a, b) = (self.element(row), self.element(column));
+                format!(
+                    "{} * {} = {}",
+                    name(a),
+                    name(b),
+                    name(self.group.multiply(a, b))
+                )
+            }
+        };
+        let [x, y] = self.pointer;
+        let (h_align, x) = if x > self.size[0] * 0.6 {
+            (HAlign::Right, x - TOOLTIP_OFFSET)
+        } else {
+            (HAlign::Left, x + TOOLTIP_OFFSET)
+        };
+        Some(
+            Text::new(content, [x, y + TOOLTIP_OFFSET])
+                .with_size(TOOLTIP_SIZE)
+                .with_background(TOOLTIP_BACKGROUND)
+                .with_align(h_align, VAlign::Top),
+        )
+    }
+
+    fn hover(&mut self, position: [f32; 2]) {
+        self.pointer = position;
+        let hovered = self.cell_at(position);
+        // The tooltip follows the pointer even within a cell
+        self.hover_dirty |= hovered.is_some() || hovered != self.hovered;
+        self.hovered = hovered;
+    }
+}
+
+impl EngineObject for CayleyTable {
+    fn update(&mut self, context: &UpdateContext) {
+        let changes = {
+            let mut state = self.controls.0.borrow_mut();
+            std::mem::take(&mut state.changed).then(|| (state.subgroup.clone(), state.coloring))
+        };
+        if let Some((subgroup, coloring)) = changes {
+            if let Some(subgroup) = subgroup {
+                self.arrangement = Arrangement::new(&self.group, &subgroup);
+            }
+            self.coloring = coloring;
+            self.table_dirty = true;
+            self.hover_dirty = true;
+        }
+        if std::mem::take(&mut self.table_dirty) {
+            let table = self.table_rects();
+            self.cells.prepare(context.device, context.queue, &[&table]);
+        }
+        if !std::mem::take(&mut self.hover_dirty) {
+            return;
+        }
+
+        let highlights = self.highlight_rects();
+        self.highlights
+            .prepare(context.device, context.queue, &[&highlights]);
+        let tooltip: Vec<Text> = self.tooltip().into_iter().collect();
+        self.text
+            .prepare(context.device, context.queue, &[&tooltip]);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = [size.width as f32, size.height as f32];
+        self.table_dirty = true;
+        self.hover_dirty = true;
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match *event {
+            InputEvent::PointerMoved { position, .. } => self.hover(position),
+            InputEvent::PointerLeft => {
+                self.hover_dirty |= self.hovered.is_some();
+                self.hovered = None;
+            }
+            InputEvent::Touch {
+                phase: TouchPhase::Started,
+                position,
+                ..
+            } => self.hover(position),
+            _ => {}
+        }
+        EventResponse::Ignored
+    }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        [
+            self.cells.render_data(0),
+            self.highlights.render_data(0),
+            self.text.render_data(0),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::group::PermGroup;
+
+    /// Whether every coset block of the table holds products from a single coset
+    fn blocks_are_uniform(group: &FiniteGroup, arrangement: &Arrangement) -> bool {
+        let n = group.order();
+        let size = n / arrangement.coset_count();
+        (0..n).all(|row| {
+            (0..n).all(|column| {
+                let product =
+                    |r: usize, c: usize| group.multiply(arrangement.order[r], arrangement.order[c]);
+                let corner = product(row - row % size, column - column % size);
+                arrangement.coset_of[product(row, column)] == arrangement.coset_of[corner]
+            })
+        })
+    }
+
+    #[test]
+    fn test_normal_subgroups_give_quotient_blocks() {
+        let group = FiniteGroup::new(&PermGroup::symmetric(3)).unwrap();
+        let rotations = group.generate(&[group.generators()[1]]);
+        assert_eq!(rotations.len(), 3);
+        let arrangement = Arrangement::new(&group, &rotations);
+        assert_eq!(arrangement.boundaries, [3]);
+        assert_eq!(&arrangement.order[..3], rotations.as_slice());
+        assert!(blocks_are_uniform(&group, &arrangement));
+
+        let swap = group.generate(&[group.generators()[0]]);
+        let arrangement = Arrangement::new(&group, &swap);
+        assert_eq!(arrangement.coset_count(), 3);
+        assert!(!blocks_are_uniform(&group, &arrangement));
+
+        for element in 0..6 {
+            assert_eq!(arrangement.order[arrangement.position[element]], element);
+        }
+        let colors: Vec<Color> = (0..6)
+            .map(|element| arrangement.color(element, Coloring::Element))
+            .collect();
+        assert!((1..6).all(|i| colors[..i].iter().all(|color| *color != colors[i])));
+    }
+}
@@ -1,5 +1,13 @@
+pub mod algebra;
+pub mod camera;
 pub mod complex;
 pub mod engine;
+pub mod geometry;
+pub mod groups;
+pub mod math_object;
+pub mod scenes;
 pub mod start;
+pub mod symmetry;
+pub mod text;
 pub mod types;
 pub mod user_interface;
@@ -0,0 +1,296 @@
+use std::f32::consts::TAU;
+
+use glam::{Vec2, Vec3};
+
+use crate::types::{Vertex2, Vertex3};
+
+/// Indexed triangle list, triangles wind counter clockwise when seen from the front
+#[derive(Debug, Clone, Default)]
+pub struct Mesh<V> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<u32>,
+}
+
+impl<V> Mesh<V> {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Adds another mesh's triangles to this one
+    pub fn append(&mut self, other: Mesh<V>) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|index| index + offset));
+    }
+
+    /// Triangles fanning out from the first of `count` vertices starting at `first`
+    fn fan(&mut self, first: u32, count: u32) {
+        for i in 1..count.saturating_sub(1) {
+            self.indices
+                .extend_from_slice(&[first, first + i, first + i + 1]);
+        }
+    }
+}
+
+impl Mesh<Vertex2> {
+    pub fn triangle(a: Vec2, b: Vec2, c: Vec2) -> Self {
+        Self {
+            vertices: [a, b, c].map(vertex2).to_vec(),
+            indices: vec![0, 1, 2],
+        }
+    }
+
+    pub fn rect(min: Vec2, max: Vec2) -> Self {
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        let mut mesh = Self {
+            vertices: corners.map(vertex2).to_vec(),
+            indices: Vec::new(),
+        };
+        mesh.fan(0, 4);
+        mesh
+    }
+
+    /// A straight segment `width` wide with square ends flush with `a` and `b`
+    pub fn line(a: Vec2, b: Vec2, width: f32) -> Self {
+        let Some(direction) = (b - a).try_normalize() else {
+            return Self::new();
+        };
+        let normal = direction.perp() * width / 2.0;
+        let corners = [a - normal, b - normal, b + normal, a + normal];
+        let mut mesh = Self {
+            vertices: corners.map(vertex2).to_vec(),
+            indices: Vec::new(),
+        };
+        mesh.fan(0, 4);
+        mesh
+    }
+
+    /// Polygon with `sides` corners on a circle, the first one pointing along +x
+    pub fn regular_polygon(center: Vec2, radius: f32, sides: u32) -> Self {
+        let sides = sides.max(3);
+        let mut mesh = Self {
+            vertices: (0..sides)
+                .map(|i| vertex2(center + Vec2::from_angle(TAU * i as f32 / sides as f32) * radius))
+                .collect(),
+            indices: Vec::new(),
+        };
+        mesh.fan(0, sides);
+        mesh
+    }
+
+    pub fn circle(center: Vec2, radius: f32, segments: u32) -> Self {
+        Self::regular_polygon(center, radius, segments)
+    }
+}
+
+impl Mesh<Vertex3> {
+    pub fn triangle(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self::face(&[a, b, c])
+    }
+
+    /// A flat convex polygon with its corners counter clockwise seen from the front,
+    /// every vertex gets the face normal so it shades flat
+    pub fn face(corners: &[Vec3]) -> Self {
+        // Newell's method, robust to nearly collinear corners
+        let normal = (0..corners.len())
+            .map(|i| corners[i].cross(corners[(i + 1) % corners.len()]))
+            .sum::<Vec3>()
+            .normalize_or_zero();
+        let mut mesh = Self {
+            vertices: corners
+                .iter()
+                .map(|&corner| vertex3(corner, normal))
+                .collect(),
+            indices: Vec::new(),
+        };
+        mesh.fan(0, corners.len() as u32);
+        mesh
+    }
+
+    /// Axis aligned cube centered on the origin
+    pub fn cube(half_size: f32) -> Self {
+        // Corner i has bit 0 set for +x, bit 1 for +y and bit 2 for +z
+        let corner = |i: usize| {
+            let side = |bit: usize| if i & bit == 0 { -half_size } else { half_size };
+            Vec3::new(side(1), side(2), side(4))
+        };
+        let faces = [
+            [0, 2, 3, 1], // -z
+            [4, 5, 7, 6], // +z
+            [0, 4, 6, 2], // -x
+            [1, 3, 7, 5], // +x
+            [0, 1, 5, 4], // -y
+            [2, 6, 7, 3], // +y
+        ];
+        let mut mesh = Self::new();
+        faces
+            .iter()
+            .for_each(|face| mesh.append(Self::face(&face.map(corner))));
+        mesh
+    }
+
+    /// Latitude/longitude sphere centered on the origin with the poles on the y axis
+    pub fn sphere(radius: f32, rings: u32, segments: u32) -> Self {
+        let (rings, segments) = (rings.max(2), segments.max(3));
+        let mut mesh = Self::new();
+        for ring in 0..=rings {
+            let phi = std::f32::consts::PI * ring as f32 / rings as f32;
+            for segment in 0..=segments {
+                let theta = TAU * segment as f32 / segments as f32;
+                let direction =
+                    Vec3::new(phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin());
+                mesh.vertices.push(vertex3(direction * radius, direction));
+            }
+        }
+
+        let row = segments + 1;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let a = ring * row + segment;
+                let b = a + row;
+                mesh.indices
+                    .extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+        mesh
+    }
+
+    /// Open cylinder around the segment from `a` to `b`, shaded smooth
+    pub fn tube(a: Vec3, b: Vec3, radius: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let ring = circle_directions(b - a, segments);
+        let mut mesh = Self::new();
+        for end in [a, b] {
+            mesh.vertices.extend(
+                ring.iter()
+                    .map(|&direction| vertex3(end + direction * radius, direction)),
+            );
+        }
+        for i in 0..segments {
+            let next = (i + 1) % segments;
+            let (top, next_top) = (i + segments, next + segments);
+            mesh.indices
+                .extend_from_slice(&[i, next, top, next, next_top, top]);
+        }
+        mesh
+    }
+
+    /// Cone with its flat base centered on `base` and its point at `tip`
+    pub fn cone(base: Vec3, tip: Vec3, radius: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let axis = tip - base;
+        let ring = circle_directions(axis, segments);
+        let mut mesh = Self::new();
+        // The tip is repeated per segment so each side gets its own normal
+        for (i, &direction) in ring.iter().enumerate() {
+            let normal =
+                (direction * axis.length() + axis.normalize_or_zero() * radius).normalize_or_zero();
+            mesh.vertices
+                .push(vertex3(base + direction * radius, normal));
+            mesh.vertices.push(vertex3(tip, normal));
+            let (i, next) = (2 * i as u32, 2 * ((i as u32 + 1) % segments));
+            mesh.indices.extend_from_slice(&[i, next, i + 1]);
+        }
+        let corners: Vec<Vec3> = ring
+            .iter()
+            .rev()
+            .map(|&direction| base + direction * radius)
+            .collect();
+        mesh.append(Self::face(&corners));
+        mesh
+    }
+}
+
+/// Unit vectors around `axis`, counter clockwise seen from its tip
+fn circle_directions(axis: Vec3, segments: u32) -> Vec<Vec3> {
+    let axis = axis.normalize_or_zero();
+    let u = axis.any_orthonormal_vector();
+    let v = axis.cross(u);
+    (0..segments)
+        .map(|i| {
+            let theta = TAU * i as f32 / segments as f32;
+            u * theta.cos() + v * theta.sin()
+        })
+        .collect()
+}
+
+fn vertex2(position: Vec2) -> Vertex2 {
+    Vertex2::new(position.to_array())
+}
+
+fn vertex3(position: Vec3, normal: Vec3) -> Vertex3 {
+    Vertex3::new(position.to_array(), normal.to_array())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every non degenerate triangle should face away from the origin
+    fn assert_outward(mesh: &Mesh<Vertex3>) {
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.vertices[triangle[i] as usize].pos));
+            let normal = (b - a).cross(c - a);
+            if normal.length() > 1e-6 {
+                assert!(normal.dot(a + b + c) > 0.0, "{:?} faces inward", triangle);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flat_primitives() {
+        let circle = Mesh::circle(Vec2::new(1.0, 2.0), 0.5, 32);
+        assert_eq!(circle.vertices.len(), 32);
+        assert_eq!(circle.triangle_count(), 30);
+        assert!(circle
+            .vertices
+            .iter()
+            .all(|v| (Vec2::from(v.pos).distance(Vec2::new(1.0, 2.0)) - 0.5).abs() < 1e-5));
+
+        let line = Mesh::line(Vec2::ZERO, Vec2::new(2.0, 0.0), 0.2);
+        let ys: Vec<f32> = line.vertices.iter().map(|v| v.pos[1]).collect();
+        assert_eq!(ys, [-0.1, -0.1, 0.1, 0.1]);
+        assert!(Mesh::line(Vec2::ONE, Vec2::ONE, 0.2).is_empty());
+
+        let mut combined = Mesh::rect(Vec2::ZERO, Vec2::ONE);
+        combined.append(Mesh::<Vertex2>::triangle(Vec2::X, Vec2::Y, Vec2::ONE));
+        assert_eq!(combined.vertices.len(), 7);
+        assert_eq!(&combined.indices[6..], [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_solids_face_outward() {
+        let cube = Mesh::cube(1.0);
+        assert_eq!(cube.vertices.len(), 24);
+        assert_eq!(cube.triangle_count(), 12);
+        assert!(cube
+            .vertices
+            .iter()
+            .all(|v| Vec3::from(v.normal).dot(Vec3::from(v.pos)) > 0.0));
+        assert_outward(&cube);
+
+        let sphere = Mesh::sphere(2.0, 8, 12);
+        assert_eq!(sphere.vertices.len(), 9 * 13);
+        assert_outward(&sphere);
+
+        let tube = Mesh::tube(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.5, 8);
+        assert_eq!(tube.triangle_count(), 16);
+        assert_outward(&tube);
+
+        let cone = Mesh::cone(Vec3::new(0.0, -0.5, 0.0), Vec3::new(0.0, 0.5, 0.0), 1.0, 8);
+        assert_eq!(cone.triangle_count(), 8 + 6);
+        assert_outward(&cone);
+    }
+}
@@ -0,0 +1,375 @@
+//! Generalized math objects: a tree of nodes with transforms relative to their
+//! parents, each optionally drawing a flat or solid mesh in a single color
+
+mod mesh;
+mod node;
+mod transform;
+
+use glam::{Mat3, Mat4};
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+
+use crate::camera::{Camera, OrbitController};
+use crate::engine::{
+    solid_depth_state, Engine, EngineObject, EventResponse, InputEvent, RenderData, UpdateContext,
+};
+use crate::types::{Vertex2, Vertex3};
+pub use mesh::Mesh;
+pub use node::{Geometry, Node};
+use node::{MeshBuffers, NodeBuffers};
+pub use transform::Transform;
+
+/// What the shader sees of each node
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ModelUniform {
+    model: [[f32; 4]; 4],
+    /// Inverse transpose of the model matrix, keeps normals perpendicular under non-uniform scales
+    normal: [[f32; 4]; 4],
+    color: [f32; 4],
+}
+
+impl ModelUniform {
+    fn new(model: Mat4, color: [f32; 4]) -> Self {
+        let linear = Mat3::from_mat4(model);
+        // A node scaled flat has no inverse, its normals still point along the model's
+        let normal = if linear.determinant().abs() > f32::EPSILON {
+            linear.inverse().transpose()
+        } else {
+            linear
+        };
+        Self {
+            model: model.to_cols_array_2d(),
+            normal: Mat4::from_mat3(normal).to_cols_array_2d(),
+            color,
+        }
+    }
+}
+
+type Animation = Box<dyn FnMut(&mut Node, &UpdateContext)>;
+
+/// Draws a scene graph of `Node`s, parents before their children.
+/// The default camera looks at the z = 0 plane with the window two units tall.
+pub struct MathObject {
+    root: Node,
+    animation: Option<Animation>,
+    camera: Camera,
+    orbit: Option<OrbitController>,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    model_layout: wgpu::BindGroupLayout,
+    flat_pipeline: wgpu::RenderPipeline,
+    solid_pipeline: wgpu::RenderPipeline,
+}
+
+impl MathObject {
+    pub fn new(engine: &Engine, root: Node) -> Self {
+        let device = engine.device();
+        let model_layout = create_uniform_layout(device, "math_object_model_layout");
+        let camera_layout = create_uniform_layout(device, "math_object_camera_layout");
+
+        let mut camera = Camera::default();
+        camera.set_size(engine.size());
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Math Object Camera"),
+            contents: bytemuck::cast_slice(&[camera.uniform()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("math_object_camera_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Math Object Layout"),
+            bind_group_layouts: &[engine.uniform_bind_group(), &model_layout, &camera_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shader.wgsl"));
+        let flat_pipeline = create_pipeline(
+            engine,
+            &layout,
+            &shader,
+            ("vs_flat", "fs_flat"),
+            Vertex2::desc(),
+        );
+        let solid_pipeline = create_pipeline(
+            engine,
+            &layout,
+            &shader,
+            ("vs_solid", "fs_solid"),
+            Vertex3::desc(),
+        );
+
+        Self {
+            root,
+            animation: None,
+            camera,
+            orbit: None,
+            camera_buffer,
+            camera_bind_group,
+            model_layout,
+            flat_pipeline,
+            solid_pipeline,
+        }
+    }
+
+    /// Runs `animation` on the root every frame before the transforms are uploaded
+    pub fn with_animation(
+        mut self,
+        animation: impl FnMut(&mut Node, &UpdateContext) + 'static,
+    ) -> Self {
+        self.animation = Some(Box::new(animation));
+        self
+    }
+
+    /// Replaces the camera, keeping the window's aspect ratio
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        let aspect = self.camera.aspect;
+        self.camera = Camera { aspect, ..camera };
+        self
+    }
+
+    /// Lets the mouse circle the camera around the scene
+    pub fn with_orbit(mut self, orbit: OrbitController) -> Self {
+        self.orbit = Some(orbit);
+        self
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn orbit_mut(&mut self) -> Option<&mut OrbitController> {
+        self.orbit.as_mut()
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut Node {
+        &mut self.root
+    }
+}
+
+fn create_uniform_layout(device: &wgpu::Device, label: &str) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+        label: Some(label),
+    })
+}
+
+fn create_pipeline(
+    engine: &Engine,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    (vertex_entry, fragment_entry): (&str, &str),
+    vertex_layout: wgpu::VertexBufferLayout,
+) -> wgpu::RenderPipeline {
+    engine
+        .device()
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Math Object Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: vertex_entry,
+                buffers: &[vertex_layout],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // A mirroring transform flips the winding, so nothing is culled
+                // and the depth test hides the far side of solids instead
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(solid_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: engine.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: fragment_entry,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: engine.surface_format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+}
+
+fn upload_geometry(device: &wgpu::Device, geometry: &Geometry) -> MeshBuffers {
+    let (vertices, indices) = match geometry {
+        Geometry::Flat(mesh) => (bytemuck::cast_slice(&mesh.vertices), &mesh.indices),
+        Geometry::Solid(mesh) => (bytemuck::cast_slice(&mesh.vertices), &mesh.indices),
+    };
+    MeshBuffers {
+        vertices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Math Object Vertices"),
+            contents: vertices,
+            usage: wgpu::BufferUsages::VERTEX,
+        }),
+        indices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Math Object Indices"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        }),
+        num_indices: indices.len() as u32,
+    }
+}
+
+impl EngineObject for MathObject {
+    fn update(&mut self, context: &UpdateContext) {
+        if let Some(animation) = &mut self.animation {
+            animation(&mut self.root, context);
+        }
+        if let Some(orbit) = &self.orbit {
+            orbit.apply(&mut self.camera);
+        }
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[self.camera.uniform()]),
+        );
+
+        let layout = &self.model_layout;
+        self.root.visit_mut(Mat4::IDENTITY, &mut |node, model| {
+            let uniform = ModelUniform::new(model, node.color);
+            let buffers = node.buffers.get_or_insert_with(|| {
+                let uniform =
+                    context
+                        .device
+                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Math Object Model"),
+                            contents: bytemuck::cast_slice(&[uniform]),
+                            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                        });
+                let bind_group = context
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        layout,
+                        entries: &[wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: uniform.as_entire_binding(),
+                        }],
+                        label: Some("math_object_model_bind_group"),
+                    });
+                NodeBuffers {
+                    uniform,
+                    bind_group,
+                    mesh: None,
+                }
+            });
+            context
+                .queue
+                .write_buffer(&buffers.uniform, 0, bytemuck::cast_slice(&[uniform]));
+
+            if let Some(geometry) = node.take_dirty_geometry() {
+                let mesh = upload_geometry(context.device, geometry);
+                if let Some(buffers) = &mut node.buffers {
+                    buffers.mesh = (mesh.num_indices > 0).then_some(mesh);
+                }
+            }
+        });
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.camera.set_size(size);
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        match &mut self.orbit {
+            Some(orbit) => orbit.input(event),
+            None => EventResponse::Ignored,
+        }
+    }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        let mut layers = Vec::new();
+        self.collect(&self.root, &mut layers);
+        layers
+    }
+}
+
+impl MathObject {
+    /// Draw calls for a node and its children, skipping hidden subtrees
+    fn collect<'a>(&'a self, node: &'a Node, layers: &mut Vec<RenderData<'a>>) {
+        if !node.visible {
+            return;
+        }
+        if let (Some(geometry), Some(buffers)) = (node.geometry(), &node.buffers) {
+            if let Some(mesh) = &buffers.mesh {
+                let render_pipeline = match geometry {
+                    Geometry::Flat(_) => &self.flat_pipeline,
+                    Geometry::Solid(_) => &self.solid_pipeline,
+                };
+                layers.push(RenderData {
+                    render_pipeline,
+                    bind_groups: vec![&buffers.bind_group, &self.camera_bind_group],
+                    vertex_buffers: vec![&mesh.vertices],
+                    instance_buffer: None,
+                    index_buffer: Some(&mesh.indices),
+                    index_format: wgpu::IndexFormat::Uint32,
+                    num_vertices: 0,
+                    num_indices: mesh.num_indices,
+                    instances: 0..1,
+                });
+            }
+        }
+        node.children()
+            .iter()
+            .for_each(|child| self.collect(child, layers));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{Vec3, Vec4};
+
+    #[test]
+    fn test_normals_stay_perpendicular() {
+        // A slope stretched along x gets shallower, so its normal must tip towards y
+        let model = Mat4::from_scale(Vec3::new(4.0, 1.0, 1.0));
+        let uniform = ModelUniform::new(model, [1.0; 4]);
+        let normal = Mat4::from_cols_array_2d(&uniform.normal) * Vec4::new(1.0, 1.0, 0.0, 0.0);
+        let along = model * Vec4::new(1.0, -1.0, 0.0, 0.0);
+        assert!(normal.dot(along).abs() < 1e-6);
+
+        // Flattened nodes keep their model matrix rather than dividing by zero
+        let flat = ModelUniform::new(Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0)), [1.0; 4]);
+        assert!(flat.normal.iter().flatten().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_shader_compiles() {
+        crate::engine::validate_shader(include_str!("shader.wgsl"));
+    }
+}
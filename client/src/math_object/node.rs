@@ -0,0 +1,182 @@
+use glam::Mat4;
+
+use super::mesh::Mesh;
+use super::transform::Transform;
+use crate::types::{Vertex2, Vertex3};
+
+/// The mesh drawn by a node, flat meshes lie on the z = 0 plane
+#[derive(Debug, Clone)]
+pub enum Geometry {
+    Flat(Mesh<Vertex2>),
+    Solid(Mesh<Vertex3>),
+}
+
+impl From<Mesh<Vertex2>> for Geometry {
+    fn from(mesh: Mesh<Vertex2>) -> Self {
+        Self::Flat(mesh)
+    }
+}
+
+impl From<Mesh<Vertex3>> for Geometry {
+    fn from(mesh: Mesh<Vertex3>) -> Self {
+        Self::Solid(mesh)
+    }
+}
+
+/// GPU side of a node, created the first time the node is updated
+pub(super) struct NodeBuffers {
+    pub uniform: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    pub mesh: Option<MeshBuffers>,
+}
+
+pub(super) struct MeshBuffers {
+    pub vertices: wgpu::Buffer,
+    pub indices: wgpu::Buffer,
+    pub num_indices: u32,
+}
+
+/// A node of a `MathObject`'s scene graph. Its transform is relative to its
+/// parent, so moving a node moves everything below it.
+pub struct Node {
+    pub name: String,
+    pub transform: Transform,
+    pub color: [f32; 4],
+    /// Hidden nodes hide their children too
+    pub visible: bool,
+    geometry: Option<Geometry>,
+    geometry_dirty: bool,
+    children: Vec<Node>,
+    pub(super) buffers: Option<NodeBuffers>,
+}
+
+impl Node {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            transform: Transform::IDENTITY,
+            color: [1.0, 1.0, 1.0, 1.0],
+            visible: true,
+            geometry: None,
+            geometry_dirty: false,
+            children: Vec::new(),
+            buffers: None,
+        }
+    }
+
+    pub fn with_geometry(mut self, geometry: impl Into<Geometry>) -> Self {
+        self.set_geometry(geometry);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn geometry(&self) -> Option<&Geometry> {
+        self.geometry.as_ref()
+    }
+
+    /// Replaces the mesh, it is uploaded again on the next update
+    pub fn set_geometry(&mut self, geometry: impl Into<Geometry>) {
+        self.geometry = Some(geometry.into());
+        self.geometry_dirty = true;
+    }
+
+    pub fn add_child(&mut self, child: Node) {
+        self.children.push(child);
+    }
+
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [Node] {
+        &mut self.children
+    }
+
+    /// Depth first search for a node by name, including this one
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(name))
+    }
+
+    /// Calls `f` with every node and its model matrix, parents before their children
+    pub fn visit(&self, parent: Mat4, f: &mut impl FnMut(&Node, Mat4)) {
+        let model = parent * self.transform.matrix();
+        f(self, model);
+        self.children.iter().for_each(|child| child.visit(model, f));
+    }
+
+    pub(super) fn visit_mut(&mut self, parent: Mat4, f: &mut impl FnMut(&mut Node, Mat4)) {
+        let model = parent * self.transform.matrix();
+        f(self, model);
+        self.children
+            .iter_mut()
+            .for_each(|child| child.visit_mut(model, f));
+    }
+
+    /// Returns the geometry if it changed since the last call
+    pub(super) fn take_dirty_geometry(&mut self) -> Option<&Geometry> {
+        if std::mem::take(&mut self.geometry_dirty) {
+            self.geometry.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn test_children_inherit_transforms() {
+        let moon = Node::new("moon").with_transform(Transform::from_xy(1.0, 0.0));
+        let planet = Node::new("planet")
+            .with_transform(Transform::from_xy(2.0, 0.0).with_scale(0.5))
+            .with_child(moon);
+        let mut sun = Node::new("sun").with_child(planet);
+        sun.transform
+            .rotate(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+
+        let mut positions = Vec::new();
+        sun.visit(Mat4::IDENTITY, &mut |node, model| {
+            positions.push((node.name.clone(), model.transform_point3(Vec3::ZERO)));
+        });
+
+        let names: Vec<&str> = positions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["sun", "planet", "moon"]);
+        // A quarter turn of the sun carries the planet to +y, the moon sits half a unit further out
+        assert!(positions[1].1.abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-5));
+        assert!(positions[2].1.abs_diff_eq(Vec3::new(0.0, 2.5, 0.0), 1e-5));
+
+        sun.find_mut("moon").unwrap().visible = false;
+        assert!(!sun.find("moon").unwrap().visible);
+        assert!(sun.find("comet").is_none());
+    }
+}
@@ -0,0 +1,73 @@
+struct Uniforms {
+    delta_time: f32,
+    run_time: f32,
+    width: f32,
+    height: f32
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct Model {
+    matrix: mat4x4<f32>,
+    normal: mat4x4<f32>,
+    color: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> model: Model;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+    eye: vec4<f32>,
+};
+
+@group(2) @binding(0)
+var<uniform> camera: Camera;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+// Normals go through the inverse transpose so they stay perpendicular to stretched faces
+fn project(local: vec4<f32>, normal: vec3<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    let world = model.matrix * local;
+    out.position = camera.view_projection * world;
+    out.world = world.xyz;
+    out.normal = (model.normal * vec4<f32>(normal, 0.0)).xyz;
+    return out;
+}
+
+@vertex
+fn vs_flat(@location(0) position: vec2<f32>) -> VertexOutput {
+    return project(vec4<f32>(position, 0.0, 1.0), vec3<f32>(0.0, 0.0, 1.0));
+}
+
+@vertex
+fn vs_solid(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+) -> VertexOutput {
+    return project(vec4<f32>(position, 1.0), normal);
+}
+
+@fragment
+fn fs_flat(in: VertexOutput) -> @location(0) vec4<f32> {
+    return model.color;
+}
+
+// The light sits at the camera so whatever side is being looked at is lit
+@fragment
+fn fs_solid(in: VertexOutput) -> @location(0) vec4<f32> {
+    let to_eye = normalize(camera.eye.xyz - in.world);
+    var normal = normalize(in.normal);
+    // Seen from behind, light the inside of the face instead
+    if (dot(normal, to_eye) < 0.0) {
+        normal = -normal;
+    }
+    let shade = 0.35 + 0.65 * max(dot(normal, to_eye), 0.0);
+    return vec4<f32>(model.color.rgb * shade, model.color.a);
+}
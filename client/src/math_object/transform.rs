@@ -0,0 +1,72 @@
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+/// Position, rotation and scale of a node relative to its parent.
+/// Applied as scale, then rotation, then translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        position: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_position(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// A position on the z = 0 plane, where 2D objects live
+    pub fn from_xy(x: f32, y: f32) -> Self {
+        Self::from_position(Vec3::new(x, y, 0.0))
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = Vec3::splat(scale);
+        self
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        self.position += offset;
+    }
+
+    pub fn translate_xy(&mut self, offset: Vec2) {
+        self.translate(offset.extend(0.0));
+    }
+
+    /// Rotates on top of the current rotation, around the node's own origin
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    /// Counter clockwise rotation in the xy plane, in radians
+    pub fn rotate_z(&mut self, angle: f32) {
+        self.rotate(Quat::from_rotation_z(angle));
+    }
+
+    pub fn scale_by(&mut self, factor: f32) {
+        self.scale *= factor;
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::algebra::finite::FiniteGroup;
+use crate::algebra::group::PermGroup;
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::groups::CayleyDiagram;
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+use super::presets::{GroupPicker, PRESETS};
+
+/// The Cayley graph of a preset group or one given by generators, in the plane
+/// or in space. Changing either replaces the scene.
+pub struct CayleyScene {
+    group: PermGroup,
+    flat: bool,
+}
+
+impl Default for CayleyScene {
+    fn default() -> Self {
+        Self {
+            group: PRESETS[0].1(),
+            flat: true,
+        }
+    }
+}
+
+impl Scene for CayleyScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let (diagram, mut error) = match FiniteGroup::new(&self.group) {
+            Ok(group) => (Some(CayleyDiagram::new(engine, group, self.flat)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let mut picker = GroupPicker::new(&self.group);
+        let mut three_d = !self.flat;
+        let flat = self.flat;
+        let group = self.group.clone();
+        let scenes = scenes.clone();
+
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Cayley graph", Rect::new(20.0, 20.0, 320.0, 270.0), |ui| {
+                match picker.show(ui) {
+                    Ok(Some(group)) => scenes.replace(CayleyScene { group, flat }),
+                    Ok(None) => {}
+                    Err(e) => error = Some(e.to_string()),
+                }
+                if ui.checkbox("3D layout", &mut three_d) {
+                    scenes.replace(CayleyScene {
+                        group: group.clone(),
+                        flat: !three_d,
+                    });
+                }
+                match &error {
+                    Some(error) => ui.label(error),
+                    None => ui.label("Click an element to see its word"),
+                }
+                if ui.button("Back") {
+                    scenes.pop_with(fade());
+                }
+            });
+        });
+
+        let mut objects: Vec<Box<dyn EngineObject>> = Vec::new();
+        if let Some(diagram) = diagram {
+            objects.push(Box::new(diagram));
+        }
+        objects.push(Box::new(ui));
+        objects
+    }
+}
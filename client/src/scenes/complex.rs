@@ -0,0 +1,77 @@
+use crate::complex::{ComplexFunction, ComplexGrapher};
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+
+const PRESETS: &[(&str, ComplexFunction)] = &[
+    ("z^-2", ComplexFunction::Power(-2.0)),
+    ("e^z", ComplexFunction::Exp),
+    ("ln z", ComplexFunction::Log),
+    ("sin z", ComplexFunction::Sin),
+    ("cos z", ComplexFunction::Cos),
+    ("tan z", ComplexFunction::Tan),
+];
+
+/// Domain coloring of a complex function with a panel for picking it.
+/// Choosing another function graphs it in place, keeping the view.
+#[derive(Default)]
+pub struct ComplexScene {
+    function: ComplexFunction,
+    expression: String,
+}
+
+impl ComplexScene {
+    pub fn new(function: ComplexFunction) -> Self {
+        Self {
+            function,
+            expression: String::new(),
+        }
+    }
+}
+
+impl Scene for ComplexScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let grapher = ComplexGrapher::with_function(engine, self.function.clone());
+        let controls = grapher.controls();
+
+        let mut names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+        names.push("Expression");
+        let mut selected = PRESETS
+            .iter()
+            .position(|(_, function)| *function == self.function)
+            .unwrap_or(PRESETS.len());
+        let mut expression = self.expression.clone();
+        let mut error = None;
+        let scenes = scenes.clone();
+
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Function", Rect::new(20.0, 20.0, 260.0, 230.0), |ui| {
+                if ui.dropdown("Function", &mut selected, &names) {
+                    if let Some((_, function)) = PRESETS.get(selected) {
+                        controls.set_function(function.clone());
+                        error = None;
+                    }
+                }
+                ui.text_input("Expression", &mut expression);
+                if ui.button("Plot") {
+                    match controls.set_expression(&expression) {
+                        Ok(()) => {
+                            selected = PRESETS.len();
+                            error = None;
+                        }
+                        Err(e) => error = Some(e.to_string()),
+                    }
+                }
+                if let Some(error) = &error {
+                    ui.label(error);
+                }
+                if ui.button("Back") {
+                    scenes.pop_with(fade());
+                }
+            });
+        });
+
+        vec![Box::new(grapher), Box::new(ui)]
+    }
+}
@@ -0,0 +1,85 @@
+use crate::algebra::finite::FiniteGroup;
+use crate::algebra::group::PermGroup;
+use crate::algebra::lattice::SubgroupLattice;
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::groups::HasseDiagram;
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+use super::presets::{GroupPicker, PRESETS};
+
+/// The subgroup lattice of a preset group or one given by generators.
+/// Picking another group replaces the scene.
+pub struct LatticeScene {
+    group: PermGroup,
+}
+
+impl Default for LatticeScene {
+    fn default() -> Self {
+        Self {
+            group: PRESETS[0].1(),
+        }
+    }
+}
+
+impl Scene for LatticeScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let lattice = FiniteGroup::new(&self.group)
+            .map_err(|e| e.to_string())
+            .and_then(|group| match SubgroupLattice::new(&group) {
+                Ok(lattice) => Ok((group, lattice)),
+                Err(e) => Err(e.to_string()),
+            });
+        let (diagram, summary, mut error) = match lattice {
+            Ok((group, lattice)) => {
+                let normal = lattice.subgroups.iter().filter(|s| s.normal).count();
+                let summary = format!(
+                    "Order {}: {} subgroups, {} normal",
+                    group.order(),
+                    lattice.subgroups.len(),
+                    normal
+                );
+                (
+                    Some(HasseDiagram::new(engine, group, lattice)),
+                    summary,
+                    None,
+                )
+            }
+            Err(error) => (None, String::new(), Some(error)),
+        };
+
+        let mut picker = GroupPicker::new(&self.group);
+        let scenes = scenes.clone();
+
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel(
+                "Subgroup lattice",
+                Rect::new(20.0, 20.0, 320.0, 270.0),
+                |ui| {
+                    match picker.show(ui) {
+                        Ok(Some(group)) => scenes.replace(LatticeScene { group }),
+                        Ok(None) => {}
+                        Err(e) => error = Some(e.to_string()),
+                    }
+                    match &error {
+                        Some(error) => ui.label(error),
+                        None => {
+                            ui.label(&summary);
+                            ui.label("Normal subgroups are orange");
+                        }
+                    }
+                    if ui.button("Back") {
+                        scenes.pop_with(fade());
+                    }
+                },
+            );
+        });
+
+        let mut objects: Vec<Box<dyn EngineObject>> = Vec::new();
+        if let Some(diagram) = diagram {
+            objects.push(Box::new(diagram));
+        }
+        objects.push(Box::new(ui));
+        objects
+    }
+}
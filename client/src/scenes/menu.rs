@@ -0,0 +1,48 @@
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::user_interface::{Rect, UserInterface};
+
+use super::{
+    fade, CayleyScene, ComplexScene, LatticeScene, ShapesScene, SymmetryScene, TableScene,
+};
+
+const WIDTH: f32 = 300.0;
+const HEIGHT: f32 = 276.0;
+
+/// Lists the visualizations, each one is pushed on top of the menu
+pub struct MainMenu;
+
+impl Scene for MainMenu {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let size = engine.size();
+        let rect = Rect::new(
+            (size.width as f32 - WIDTH) / 2.0,
+            (size.height as f32 - HEIGHT) / 2.0,
+            WIDTH,
+            HEIGHT,
+        );
+        let scenes = scenes.clone();
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Visualizations", rect, |ui| {
+                if ui.button("Complex functions") {
+                    scenes.push_with(ComplexScene::default(), fade());
+                }
+                if ui.button("Transforms") {
+                    scenes.push_with(ShapesScene, fade());
+                }
+                if ui.button("Symmetries of solids") {
+                    scenes.push_with(SymmetryScene::default(), fade());
+                }
+                if ui.button("Subgroup lattices") {
+                    scenes.push_with(LatticeScene::default(), fade());
+                }
+                if ui.button("Cayley graphs") {
+                    scenes.push_with(CayleyScene::default(), fade());
+                }
+                if ui.button("Cayley tables") {
+                    scenes.push_with(TableScene::default(), fade());
+                }
+            });
+        });
+        vec![Box::new(ui)]
+    }
+}
@@ -0,0 +1,27 @@
+//! The screens of the app, each one a `Scene` on the engine's scene stack
+
+mod cayley;
+mod complex;
+mod lattice;
+mod menu;
+mod presets;
+mod shapes;
+mod symmetry;
+mod table;
+
+pub use cayley::CayleyScene;
+pub use complex::ComplexScene;
+pub use lattice::LatticeScene;
+pub use menu::MainMenu;
+pub use shapes::ShapesScene;
+pub use symmetry::SymmetryScene;
+pub use table::TableScene;
+
+use crate::engine::Transition;
+
+/// Used when moving between the menu and a visualization
+const FADE: f32 = 0.4;
+
+fn fade() -> Transition {
+    Transition::fade(FADE)
+}
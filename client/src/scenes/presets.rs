@@ -0,0 +1,171 @@
+//! Groups offered by the group theory scenes, and reading and writing the
+//! generators that define them
+
+use crate::algebra::abelian::AbelianGroup;
+use crate::algebra::group::PermGroup;
+use crate::algebra::perm::{PermError, Permutation};
+use crate::user_interface::Ui;
+
+/// Typed generators may only move the points below this, larger groups are
+/// too big to draw anyway and a huge point would allocate a huge permutation
+const MAX_DEGREE: usize = 32;
+
+/// A name and a way to build the group, which can't be done in a constant
+pub type Preset = (&'static str, fn() -> PermGroup);
+
+pub const PRESETS: &[Preset] = &[
+    ("Z/12", || abelian(&[12])),
+    ("Z/2 × Z/4", || abelian(&[2, 4])),
+    ("Z/2 × Z/2 × Z/2", || abelian(&[2, 2, 2])),
+    ("S3", || PermGroup::symmetric(3)),
+    ("D4", || PermGroup::dihedral(4)),
+    ("D6", || PermGroup::dihedral(6)),
+    ("A4", || PermGroup::alternating(4)),
+    ("S4", || PermGroup::symmetric(4)),
+    ("A5", || PermGroup::alternating(5)),
+];
+
+fn abelian(factors: &[u64]) -> PermGroup {
+    AbelianGroup::product(factors)
+        .perm_group()
+        .expect("Nonzero factors give a finite group")
+}
+
+/// Permutations in cycle or one-line notation separated by semicolons,
+/// of the points below `MAX_DEGREE`
+pub fn parse_generators(text: &str) -> Result<PermGroup, PermError> {
+    let generators = text
+        .split(';')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| Permutation::parse_within(part, MAX_DEGREE))
+        .collect::<Result<Vec<Permutation>, _>>()?;
+    Ok(PermGroup::new(1, generators))
+}
+
+pub fn format_generators(group: &PermGroup) -> String {
+    let generators: Vec<String> = group.generators().iter().map(|g| g.to_string()).collect();
+    generators.join("; ")
+}
+
+/// Dropdown entries: the presets followed by "Custom"
+pub fn preset_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+    names.push("Custom");
+    names
+}
+
+/// The preset called `name`
+pub fn preset(name: &str) -> Option<PermGroup> {
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, build)| build())
+}
+
+/// Position of the preset `group` was built from, or of "Custom"
+pub fn preset_index(group: &PermGroup) -> usize {
+    let generators = format_generators(group);
+    PRESETS
+        .iter()
+        .position(|(_, preset)| format_generators(&preset()) == generators)
+        .unwrap_or(PRESETS.len())
+}
+
+/// The "Group" dropdown and "Generators" field shared by the group theory scenes
+pub struct GroupPicker {
+    names: Vec<&'static str>,
+    selected: usize,
+    generators: String,
+}
+
+impl GroupPicker {
+    /// Starts out showing `group`
+    pub fn new(group: &PermGroup) -> Self {
+        Self {
+            names: preset_names(),
+            selected: preset_index(group),
+            generators: format_generators(group),
+        }
+    }
+
+    /// Draws the picker, returning the group to show next if a preset was
+    /// picked or "Show" was pressed, or why the typed generators are invalid
+    pub fn show(&mut self, ui: &mut Ui) -> Result<Option<PermGroup>, PermError> {
+        if ui.dropdown("Group", &mut self.selected, &self.names) {
+            if let Some((_, preset)) = PRESETS.get(self.selected) {
+                return Ok(Some(preset()));
+            }
+        }
+        ui.text_input("Generators", &mut self.generators);
+        if ui.button("Show") {
+            return parse_generators(&self.generators).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators_round_trip() {
+        for (i, (name, preset)) in PRESETS.iter().enumerate() {
+            let group = preset();
+            let parsed = parse_generators(&format_generators(&group)).unwrap();
+            assert_eq!(parsed.order(), group.order(), "{}", name);
+            assert_eq!(preset_index(&parsed), i);
+        }
+        assert_eq!(parse_generators("(0 1); [1 2 0]").unwrap().order(), 6);
+        assert_eq!(parse_generators(" ; ").unwrap().order(), 1);
+        assert!(parse_generators("(0 1; (2 3)").is_err());
+        assert_eq!(
+            parse_generators("(0 1); (0 100000000)").err(),
+            Some(PermError::OutOfRange(100000000))
+        );
+        assert!(parse_generators("(0 31)").is_ok());
+        assert!(parse_generators("(0 32)").is_err());
+        assert_eq!(preset_index(&PermGroup::cyclic(5)), PRESETS.len());
+        assert_eq!(preset("S3").map(|group| group.order()), Some(6));
+        assert!(preset("S7").is_none());
+    }
+
+    #[test]
+    fn test_group_picker_shows_typed_generators() {
+        use crate::engine::{InputEvent, PointerButton};
+        use crate::user_interface::Rect;
+
+        let mut ui = Ui::new();
+        let mut picker = GroupPicker::new(&PermGroup::symmetric(3));
+        let frame = |ui: &mut Ui, picker: &mut GroupPicker| {
+            ui.begin_frame();
+            let mut shown = Ok(None);
+            ui.panel("Test", Rect::new(10.0, 10.0, 200.0, 300.0), |ui| {
+                shown = picker.show(ui);
+            });
+            ui.end_frame();
+            shown
+        };
+        // Press and release on the third row, "Show"
+        let click = |ui: &mut Ui| {
+            for pressed in [true, false] {
+                ui.handle_input(&InputEvent::PointerButton {
+                    button: PointerButton::Left,
+                    pressed,
+                    position: [30.0, 114.0],
+                });
+            }
+        };
+        assert!(frame(&mut ui, &mut picker).unwrap().is_none());
+
+        picker.generators = "(0 1".to_string();
+        click(&mut ui);
+        assert!(frame(&mut ui, &mut picker).is_err());
+
+        picker.generators = "(0 1 2 3)".to_string();
+        click(&mut ui);
+        let group = frame(&mut ui, &mut picker).unwrap().unwrap();
+        assert_eq!(group.order(), 4);
+    }
+}
@@ -0,0 +1,74 @@
+use std::f32::consts::TAU;
+
+use glam::{Quat, Vec2, Vec3};
+
+use crate::camera::{Camera, OrbitController};
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::geometry::tessellate::{stroke_circle, StrokeStyle};
+use crate::math_object::{MathObject, Mesh, Node, Transform};
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+
+/// Nested transforms: a moon orbiting a planet orbiting a sun, next to a spinning cube.
+/// Dragging circles the camera around them.
+pub struct ShapesScene;
+
+impl Scene for ShapesScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let moon = Node::new("moon")
+            .with_geometry(Mesh::circle(Vec2::ZERO, 0.04, 24))
+            .with_transform(Transform::from_xy(0.15, 0.0))
+            .with_color([0.8, 0.8, 0.85, 1.0]);
+        let planet = Node::new("planet")
+            .with_geometry(Mesh::circle(Vec2::ZERO, 0.08, 32))
+            .with_transform(Transform::from_xy(0.5, 0.0))
+            .with_color([0.3, 0.5, 0.9, 1.0])
+            .with_child(moon);
+        let path = Node::new("path")
+            .with_geometry(stroke_circle(Vec2::ZERO, 0.5, &StrokeStyle::new(0.006)))
+            .with_color([1.0, 1.0, 1.0, 0.3]);
+        let orbit = Node::new("orbit").with_child(planet);
+        let sun = Node::new("sun")
+            .with_geometry(Mesh::regular_polygon(Vec2::ZERO, 0.2, 64))
+            .with_transform(Transform::from_xy(-0.6, 0.0))
+            .with_color([1.0, 0.8, 0.2, 1.0])
+            .with_child(path)
+            .with_child(orbit);
+        let cube = Node::new("cube")
+            .with_geometry(Mesh::cube(0.25))
+            .with_transform(Transform::from_xy(0.8, 0.0))
+            .with_color([0.9, 0.3, 0.4, 1.0]);
+        let root = Node::new("root").with_child(sun).with_child(cube);
+
+        let camera = Camera::perspective(Vec3::new(0.0, 0.0, 3.0), Vec3::ZERO, 0.8);
+        let shapes = MathObject::new(engine, root)
+            .with_camera(camera)
+            .with_orbit(OrbitController::new(3.0))
+            .with_animation(|root, context| {
+                let dt = context.delta_time;
+                if let Some(orbit) = root.find_mut("orbit") {
+                    orbit.transform.rotate_z(TAU * 0.1 * dt);
+                }
+                if let Some(planet) = root.find_mut("planet") {
+                    planet.transform.rotate_z(TAU * 0.4 * dt);
+                }
+                if let Some(cube) = root.find_mut("cube") {
+                    let axis = Vec3::new(1.0, 1.0, 0.0).normalize();
+                    cube.transform
+                        .rotate(Quat::from_axis_angle(axis, TAU * 0.15 * dt));
+                }
+            });
+
+        let scenes = scenes.clone();
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Transforms", Rect::new(20.0, 20.0, 200.0, 70.0), |ui| {
+                if ui.button("Back") {
+                    scenes.pop_with(fade());
+                }
+            });
+        });
+
+        vec![Box::new(shapes), Box::new(ui)]
+    }
+}
@@ -0,0 +1,152 @@
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::geometry::polyhedra::{Feature, Polyhedron, Solid, Symmetry};
+use crate::symmetry::SymmetryViewer;
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+
+/// Symmetries of the same kind, such as the third turns about vertices
+struct Class {
+    name: String,
+    elements: Vec<(String, Symmetry)>,
+}
+
+/// Groups the rotations by angle and what their axis passes through, and the
+/// reflections into one class
+fn classes(polyhedron: &Polyhedron) -> Vec<Class> {
+    let mut classes: Vec<Class> = Vec::new();
+    let mut add = |name: String, element: String, symmetry: Symmetry| match classes
+        .iter_mut()
+        .find(|class| class.name == name)
+    {
+        Some(class) => class.elements.push((element, symmetry)),
+        None => classes.push(Class {
+            name,
+            elements: vec![(element, symmetry)],
+        }),
+    };
+
+    for rotation in polyhedron.rotations() {
+        let Some(feature) = polyhedron
+            .feature_along(rotation.axis)
+            .filter(|_| rotation.angle > 0.0)
+        else {
+            add("Identity".into(), "Identity".into(), rotation.into());
+            continue;
+        };
+        let kind = match feature {
+            Feature::Vertex(_) => "a vertex",
+            Feature::Edge(_) => "an edge",
+            Feature::Face(_) => "a face",
+        };
+        let name = format!("{:.0}° about {}", rotation.angle.to_degrees(), kind);
+        add(name, format!("About {feature}"), rotation.into());
+    }
+    for (i, normal) in polyhedron.reflections().into_iter().enumerate() {
+        add(
+            "Reflections".into(),
+            format!("Mirror {i}"),
+            Symmetry::Reflection(normal),
+        );
+    }
+    classes
+}
+
+/// A Platonic solid whose rotations and reflections can be played one at a time.
+/// Choosing another solid replaces the scene.
+#[derive(Default)]
+pub struct SymmetryScene {
+    solid: usize,
+}
+
+impl Scene for SymmetryScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let solid = Solid::ALL[self.solid];
+        let viewer = SymmetryViewer::new(engine, solid);
+        let controls = viewer.controls();
+
+        let classes = classes(viewer.polyhedron());
+        let class_names: Vec<String> = classes.iter().map(|class| class.name.clone()).collect();
+        let element_names: Vec<Vec<String>> = classes
+            .iter()
+            .map(|class| {
+                class
+                    .elements
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .collect();
+        let solid_names: Vec<&str> = Solid::ALL.iter().map(Solid::name).collect();
+        let group = &viewer.polyhedron().symmetry;
+        let title = format!(
+            "{} rotations: {}, order {}",
+            solid.name(),
+            group.name,
+            group.order
+        );
+        let mut selected_solid = self.solid;
+        let (mut class, mut element) = (0, 0);
+        let scenes = scenes.clone();
+
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Symmetries", Rect::new(20.0, 20.0, 320.0, 300.0), |ui| {
+                if ui.dropdown("Solid", &mut selected_solid, &solid_names) {
+                    scenes.replace(SymmetryScene {
+                        solid: selected_solid,
+                    });
+                }
+                ui.label(&title);
+                let names: Vec<&str> = class_names.iter().map(String::as_str).collect();
+                if ui.dropdown("Kind", &mut class, &names) {
+                    element = 0;
+                }
+                let names: Vec<&str> = element_names[class].iter().map(String::as_str).collect();
+                ui.dropdown("Element", &mut element, &names);
+                let label = if controls.is_playing() {
+                    "Playing..."
+                } else {
+                    "Play"
+                };
+                if ui.button(label) {
+                    controls.play(classes[class].elements[element].1);
+                }
+                if let Some(permutation) = controls.last_permutation() {
+                    ui.label(&format!("Vertices: {}", permutation));
+                }
+                if ui.button("Back") {
+                    scenes.pop_with(fade());
+                }
+            });
+        });
+
+        vec![Box::new(viewer), Box::new(ui)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classes_cover_the_symmetries() {
+        let polyhedron = Solid::Cube.polyhedron();
+        let classes = classes(&polyhedron);
+        let sizes: Vec<(&str, usize)> = classes
+            .iter()
+            .map(|class| (class.name.as_str(), class.elements.len()))
+            .collect();
+        for expected in [
+            ("Identity", 1),
+            ("90° about a face", 6),
+            ("120° about a vertex", 8),
+            ("180° about a face", 3),
+            ("180° about an edge", 6),
+            ("Reflections", 9),
+        ] {
+            assert!(sizes.contains(&expected), "{:?}", sizes);
+        }
+        let total: usize = classes.iter().map(|class| class.elements.len()).sum();
+        assert_eq!(total, polyhedron.symmetries().len());
+    }
+}
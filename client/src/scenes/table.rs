@@ -0,0 +1,156 @@
+use crate::algebra::finite::FiniteGroup;
+use crate::algebra::group::PermGroup;
+use crate::algebra::lattice::{Subgroup, SubgroupLattice};
+use crate::engine::{Engine, EngineObject, Scene, SceneController};
+use crate::groups::{CayleyTable, Coloring};
+use crate::user_interface::{Rect, UserInterface};
+
+use super::fade;
+use super::presets::{preset, GroupPicker};
+
+const COLORINGS: [(&str, Coloring); 2] = [
+    ("By element", Coloring::Element),
+    ("By coset", Coloring::Coset),
+];
+
+/// The subgroups to order the table by, grouped by order
+struct Choices {
+    orders: Vec<String>,
+    /// Names and elements of the subgroups of each order
+    subgroups: Vec<Vec<(String, Vec<usize>)>>,
+}
+
+impl Choices {
+    /// Every subgroup when there aren't too many to find, otherwise only the trivial one
+    fn new(group: &FiniteGroup) -> Self {
+        let subgroups = match SubgroupLattice::new(group) {
+            Ok(lattice) => lattice.subgroups,
+            Err(_) => vec![Subgroup {
+                elements: vec![group.identity()],
+                generators: Vec::new(),
+                normal: true,
+                index: group.order(),
+            }],
+        };
+        let mut choices = Self {
+            orders: Vec::new(),
+            subgroups: Vec::new(),
+        };
+        // The lattice lists subgroups by increasing order
+        for subgroup in subgroups {
+            let order = subgroup.order().to_string();
+            if choices.orders.last() != Some(&order) {
+                choices.orders.push(order);
+                choices.subgroups.push(Vec::new());
+            }
+            let generators: Vec<String> = subgroup
+                .generators
+                .iter()
+                .map(|&g| group.element(g).to_string())
+                .collect();
+            let mut name = match generators.is_empty() {
+                true => "Trivial".to_string(),
+                false => generators.join(", "),
+            };
+            if subgroup.normal {
+                name.push_str(" (normal)");
+            }
+            if let Some(last) = choices.subgroups.last_mut() {
+                last.push((name, subgroup.elements));
+            }
+        }
+        choices
+    }
+}
+
+/// The Cayley table of a preset group or one given by generators, with its
+/// rows and columns grouped by the cosets of a chosen subgroup.
+/// Picking another group replaces the scene.
+pub struct TableScene {
+    group: PermGroup,
+}
+
+impl Default for TableScene {
+    fn default() -> Self {
+        Self {
+            group: preset("S3").expect("S3 is a preset"),
+        }
+    }
+}
+
+impl Scene for TableScene {
+    fn build(&mut self, engine: &Engine, scenes: &SceneController) -> Vec<Box<dyn EngineObject>> {
+        let (table, choices, mut error) = match FiniteGroup::new(&self.group) {
+            Ok(group) => {
+                let choices = Choices::new(&group);
+                (Some(CayleyTable::new(engine, group)), Some(choices), None)
+            }
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+        let controls = table.as_ref().map(CayleyTable::controls);
+
+        let coloring_names = COLORINGS.map(|(name, _)| name);
+        let mut picker = GroupPicker::new(&self.group);
+        let (mut order, mut subgroup, mut coloring) = (0, 0, 0);
+        let scenes = scenes.clone();
+
+        let ui = UserInterface::new(engine, move |ui| {
+            ui.panel("Cayley table", Rect::new(20.0, 20.0, 320.0, 330.0), |ui| {
+                match picker.show(ui) {
+                    Ok(Some(group)) => scenes.replace(TableScene { group }),
+                    Ok(None) => {}
+                    Err(e) => error = Some(e.to_string()),
+                }
+                if let (Some(choices), Some(controls)) = (&choices, &controls) {
+                    let orders: Vec<&str> = choices.orders.iter().map(String::as_str).collect();
+                    let mut changed = ui.dropdown("Cosets of order", &mut order, &orders);
+                    if changed {
+                        subgroup = 0;
+                    }
+                    let subgroups: Vec<&str> = choices.subgroups[order]
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect();
+                    changed |= ui.dropdown("Subgroup", &mut subgroup, &subgroups);
+                    if changed {
+                        controls.show_cosets_of(choices.subgroups[order][subgroup].1.clone());
+                    }
+                    if ui.dropdown("Colors", &mut coloring, &coloring_names) {
+                        controls.set_coloring(COLORINGS[coloring].1);
+                    }
+                }
+                match &error {
+                    Some(error) => ui.label(error),
+                    None => ui.label("Hover a cell to see the product"),
+                }
+                if ui.button("Back") {
+                    scenes.pop_with(fade());
+                }
+            });
+        });
+
+        let mut objects: Vec<Box<dyn EngineObject>> = Vec::new();
+        if let Some(table) = table {
+            objects.push(Box::new(table));
+        }
+        objects.push(Box::new(ui));
+        objects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choices_by_order() {
+        let group = FiniteGroup::new(&PermGroup::symmetric(3)).unwrap();
+        let choices = Choices::new(&group);
+        assert_eq!(choices.orders, ["1", "2", "3", "6"]);
+        let counts: Vec<usize> = choices.subgroups.iter().map(Vec::len).collect();
+        assert_eq!(counts, [1, 3, 1, 1]);
+        assert_eq!(choices.subgroups[0][0].0, "Trivial (normal)");
+        assert!(choices.subgroups[2][0].0.ends_with("(normal)"));
+        assert!(!choices.subgroups[1][0].0.ends_with("(normal)"));
+    }
+}
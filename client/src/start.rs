@@ -1,9 +1,8 @@
 // Since this project gets loaded into the browser as a dynamic library we need our entry point
 // to be part of the library rather than in the main.rs file
 
-use crate::complex::ComplexGrapher;
 use crate::engine::{Engine, EngineConfig};
-use crate::user_interface::UserInterface;
+use crate::scenes::MainMenu;
 use tracing::info;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -25,10 +24,7 @@ pub async fn run() {
 
     let engine_config = EngineConfig { msaa: None };
 
-    let mut engine = Engine::new(engine_config).await;
-    let complex = ComplexGrapher::new(&engine);
-    let ui = UserInterface::new(&engine);
-    engine.add_engine_object(Box::new(complex));
-    engine.add_engine_object(Box::new(ui));
+    let engine = Engine::new(engine_config).await;
+    engine.scenes().push(MainMenu);
     engine.run();
 }
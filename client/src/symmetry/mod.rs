@@ -0,0 +1,335 @@
+//! Watching the symmetries of a Platonic solid act on it. Each vertex carries a
+//! numbered marker, playing a symmetry turns or mirrors the solid into itself
+//! and leaves the markers permuted.
+
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+use winit::dpi::PhysicalSize;
+
+use crate::algebra::perm::Permutation;
+use crate::camera::{Camera, OrbitController};
+use crate::engine::{Engine, EngineObject, EventResponse, InputEvent, RenderData, UpdateContext};
+use crate::geometry::polyhedra::{Polyhedron, Solid, Symmetry};
+use crate::math_object::{MathObject, Mesh, Node, Transform};
+use crate::text::{HAlign, Text, TextRenderer, VAlign};
+
+/// Seconds a symmetry takes to play
+const DURATION: f32 = 1.5;
+const MARKER_RADIUS: f32 = 0.07;
+const LABEL_SIZE: f32 = 20.0;
+/// How far the axis and the mirror reach past the vertices
+const GUIDE_EXTENT: f32 = 1.4;
+const SOLID_COLOR: [f32; 4] = [0.55, 0.6, 0.7, 1.0];
+const GUIDE_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 0.45];
+
+#[derive(Default)]
+struct ControlState {
+    requested: Option<Symmetry>,
+    playing: bool,
+    last: Option<Permutation>,
+}
+
+/// Handle for playing symmetries on a `SymmetryViewer` from other objects,
+/// usually a panel listing the group elements
+#[derive(Clone, Default)]
+pub struct SymmetryControls(Rc<RefCell<ControlState>>);
+
+impl SymmetryControls {
+    /// Starts `symmetry` once the one playing, if any, has finished
+    pub fn play(&self, symmetry: Symmetry) {
+        self.0.borrow_mut().requested = Some(symmetry);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        let state = self.0.borrow();
+        state.playing || state.requested.is_some()
+    }
+
+    /// What the last finished symmetry did to the markers: the one labelled `i`
+    /// moved to where the one labelled `permutation[i]` was
+    pub fn last_permutation(&self) -> Option<Permutation> {
+        self.0.borrow().last.clone()
+    }
+}
+
+struct Action {
+    symmetry: Symmetry,
+    /// Where each vertex position is carried
+    permutation: Vec<usize>,
+    start: f32,
+}
+
+/// A solid with a numbered marker on each vertex, dragging circles the camera around it
+pub struct SymmetryViewer {
+    polyhedron: Polyhedron,
+    object: MathObject,
+    labels: TextRenderer,
+    controls: SymmetryControls,
+    /// `marker_at[v]` is the marker sitting on vertex `v`
+    marker_at: Vec<usize>,
+    action: Option<Action>,
+    size: [f32; 2],
+}
+
+impl SymmetryViewer {
+    pub fn new(engine: &Engine, solid: Solid) -> Self {
+        let polyhedron = solid.polyhedron();
+        let count = polyhedron.vertices.len();
+
+        let mut body = Node::new("body").with_child(
+            Node::new("solid")
+                .with_geometry(polyhedron.mesh())
+                .with_color(SOLID_COLOR),
+        );
+        for (marker, &vertex) in polyhedron.vertices.iter().enumerate() {
+            body.add_child(
+                Node::new(marker_name(marker))
+                    .with_geometry(Mesh::sphere(MARKER_RADIUS, 8, 16))
+                    .with_transform(Transform::from_position(vertex))
+                    .with_color(marker_color(marker, count)),
+            );
+        }
+        let mut axis = Node::new("axis")
+            .with_geometry(Mesh::cube(0.5))
+            .with_color(GUIDE_COLOR);
+        axis.transform.scale = Vec3::new(0.015, 0.015, 2.0 * GUIDE_EXTENT);
+        axis.visible = false;
+        let mut mirror = Node::new("mirror")
+            .with_geometry(Mesh::rect(
+                Vec2::splat(-GUIDE_EXTENT),
+                Vec2::splat(GUIDE_EXTENT),
+            ))
+            .with_color(GUIDE_COLOR);
+        mirror.visible = false;
+
+        let root = Node::new("root")
+            .with_child(Node::new("frame").with_child(Node::new("scale").with_child(body)))
+            .with_child(axis)
+            .with_child(mirror);
+        let camera = Camera::perspective(Vec3::new(0.0, 0.0, 4.0), Vec3::ZERO, 0.8);
+        let object = MathObject::new(engine, root)
+            .with_camera(camera)
+            .with_orbit(OrbitController::new(4.0).with_angles(0.4, 0.3));
+
+        let size = engine.size();
+        Self {
+            marker_at: (0..count).collect(),
+            polyhedron,
+            object,
+            labels: TextRenderer::new(engine),
+            controls: SymmetryControls::default(),
+            action: None,
+            size: [size.width as f32, size.height as f32],
+        }
+    }
+
+    pub fn polyhedron(&self) -> &Polyhedron {
+        &self.polyhedron
+    }
+
+    pub fn controls(&self) -> SymmetryControls {
+        self.controls.clone()
+    }
+
+    fn start(&mut self, symmetry: Symmetry, run_time: f32) {
+        let Some(permutation) = self.polyhedron.vertex_permutation(symmetry) else {
+            return;
+        };
+        // Show the axis being turned about or the plane being mirrored in
+        let guide = match symmetry {
+            Symmetry::Rotation(rotation) if rotation.angle > 0.0 => Some(("axis", rotation.axis)),
+            Symmetry::Rotation(_) => None,
+            Symmetry::Reflection(normal) => Some(("mirror", normal)),
+        };
+        if let Some((name, direction)) = guide {
+            if let Some(node) = self.object.root_mut().find_mut(name) {
+                node.visible = true;
+                node.transform.rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+            }
+        }
+        self.action = Some(Action {
+            symmetry,
+            permutation,
+            start: run_time,
+        });
+        self.controls.0.borrow_mut().playing = true;
+    }
+
+    /// Swaps the moved solid for the unmoved one with its markers permuted,
+    /// which looks the same since the symmetry maps the solid onto itself
+    fn finish(&mut self, action: Action) {
+        let mut marker_at = self.marker_at.clone();
+        for (vertex, &image) in action.permutation.iter().enumerate() {
+            marker_at[image] = self.marker_at[vertex];
+        }
+        // Marker m went from vertex v to vertex σ(v), which was marker_at[σ(v)]'s spot
+        let mut moved = vec![0; marker_at.len()];
+        for (vertex, &marker) in self.marker_at.iter().enumerate() {
+            moved[marker] = self.marker_at[action.permutation[vertex]];
+        }
+        self.marker_at = marker_at;
+        self.place_markers();
+        self.pose(&action.symmetry, 0.0);
+
+        let root = self.object.root_mut();
+        for guide in ["axis", "mirror"] {
+            if let Some(node) = root.find_mut(guide) {
+                node.visible = false;
+            }
+        }
+        let mut state = self.controls.0.borrow_mut();
+        state.playing = false;
+        state.last = Permutation::from_images(moved).ok();
+    }
+
+    fn place_markers(&mut self) {
+        let root = self.object.root_mut();
+        for (vertex, &marker) in self.marker_at.iter().enumerate() {
+            if let Some(node) = root.find_mut(&marker_name(marker)) {
+                node.transform.position = self.polyhedron.vertices[vertex];
+            }
+        }
+    }
+
+    fn pose(&mut self, symmetry: &Symmetry, t: f32) {
+        let [frame, scale, body] = poses(symmetry, t);
+        let root = self.object.root_mut();
+        for (name, transform) in [("frame", frame), ("scale", scale), ("body", body)] {
+            if let Some(node) = root.find_mut(name) {
+                node.transform = transform;
+            }
+        }
+    }
+
+    /// Marker numbers at their projected positions, dimmed on the far side
+    fn label_texts(&self) -> Vec<Text> {
+        let camera = self.object.camera();
+        let view_projection = camera.view_projection();
+        let mut texts = Vec::new();
+        self.object
+            .root()
+            .visit(Mat4::IDENTITY, &mut |node, model| {
+                let Some(marker) = node.name.strip_prefix("marker ") else {
+                    return;
+                };
+                let world = model.transform_point3(Vec3::ZERO);
+                let ndc = view_projection.project_point3(world * (1.0 + 2.0 * MARKER_RADIUS));
+                let facing = world.dot(camera.eye - world) > 0.0;
+                let position = [
+                    (ndc.x + 1.0) / 2.0 * self.size[0],
+                    (1.0 - ndc.y) / 2.0 * self.size[1],
+                ];
+                texts.push(
+                    Text::new(marker, position)
+                        .with_size(LABEL_SIZE)
+                        .with_color([1.0, 1.0, 1.0, if facing { 1.0 } else { 0.35 }])
+                        .with_align(HAlign::Center, VAlign::Middle),
+                );
+            });
+        texts
+    }
+}
+
+impl EngineObject for SymmetryViewer {
+    fn update(&mut self, context: &UpdateContext) {
+        if self.action.is_none() {
+            let requested = self.controls.0.borrow_mut().requested.take();
+            if let Some(symmetry) = requested {
+                self.start(symmetry, context.run_time);
+            }
+        }
+        if let Some(action) = &self.action {
+            let t = ((context.run_time - action.start) / DURATION).clamp(0.0, 1.0);
+            let symmetry = action.symmetry;
+            // Ease in and out so the solid settles into its image
+            self.pose(&symmetry, t * t * (3.0 - 2.0 * t));
+            if t >= 1.0 {
+                let action = self.action.take().expect("An action is playing");
+                self.finish(action);
+            }
+        }
+        self.object.update(context);
+
+        let texts = self.label_texts();
+        self.labels
+            .prepare(context.device, context.queue, &[&texts]);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = [size.width as f32, size.height as f32];
+        self.object.resize(size);
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        self.object.input(event)
+    }
+
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        let mut layers = self.object.render_layers();
+        layers.extend(self.labels.render_data(0));
+        layers
+    }
+}
+
+/// Transforms of the frame, scale and body nodes part way through a symmetry.
+/// Rotations slerp from the identity. Reflections turn the mirror's normal onto
+/// the z axis, squash z from 1 through 0 to -1 and turn back.
+fn poses(symmetry: &Symmetry, t: f32) -> [Transform; 3] {
+    match *symmetry {
+        Symmetry::Rotation(rotation) => [
+            Transform::IDENTITY.with_rotation(Quat::IDENTITY.slerp(rotation.quat(), t)),
+            Transform::IDENTITY,
+            Transform::IDENTITY,
+        ],
+        Symmetry::Reflection(normal) => {
+            let to_normal = Quat::from_rotation_arc(Vec3::Z, normal);
+            let mut scale = Transform::IDENTITY;
+            scale.scale = Vec3::new(1.0, 1.0, 1.0 - 2.0 * t);
+            [
+                Transform::IDENTITY.with_rotation(to_normal),
+                scale,
+                Transform::IDENTITY.with_rotation(to_normal.inverse()),
+            ]
+        }
+    }
+}
+
+fn marker_name(marker: usize) -> String {
+    format!("marker {marker}")
+}
+
+/// Evenly spaced hues so neighbouring numbers are easy to tell apart
+fn marker_color(marker: usize, count: usize) -> [f32; 4] {
+    let hue = marker as f32 / count as f32 * 2.0 * PI;
+    let channel = |offset: f32| 0.55 + 0.45 * (hue - offset).cos();
+    [
+        channel(0.0),
+        channel(2.0 * PI / 3.0),
+        channel(4.0 * PI / 3.0),
+        1.0,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poses_end_on_the_symmetry() {
+        let polyhedron = Solid::Cube.polyhedron();
+        for symmetry in polyhedron.symmetries() {
+            let [frame, scale, body] = poses(&symmetry, 1.0);
+            let matrix = frame.matrix() * scale.matrix() * body.matrix();
+            for &vertex in &polyhedron.vertices {
+                let moved = matrix.transform_point3(vertex);
+                assert!(moved.abs_diff_eq(symmetry.apply(vertex), 1e-4));
+            }
+            let [frame, scale, body] = poses(&symmetry, 0.0);
+            let start = frame.matrix() * scale.matrix() * body.matrix();
+            assert!(start.abs_diff_eq(Mat4::IDENTITY, 1e-5));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use super::font::Font;
+
+/// Empty pixels left around each glyph so linear filtering doesn't bleed between them
+const PADDING: u32 = 1;
+
+/// Where a rasterized glyph lives in the atlas and how to place it relative to the pen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasGlyph {
+    /// Top left of the bitmap relative to the pen position, y down
+    pub offset: [f32; 2],
+    pub size: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+}
+
+impl AtlasGlyph {
+    pub fn is_empty(&self) -> bool {
+        self.size[0] == 0.0 || self.size[1] == 0.0
+    }
+}
+
+/// Single channel coverage texture filled with glyphs on demand, packed in rows
+pub struct GlyphAtlas {
+    font: Font,
+    size: u32,
+    pixels: Vec<u8>,
+    /// `None` for glyphs that didn't fit, so they aren't rasterized again
+    glyphs: HashMap<(char, u32), Option<AtlasGlyph>>,
+    cursor: [u32; 2],
+    row_height: u32,
+    solid_uv: [f32; 2],
+    full: bool,
+    dirty: bool,
+}
+
+impl GlyphAtlas {
+    pub fn new(font: Font, size: u32) -> Self {
+        let mut atlas = Self {
+            font,
+            size,
+            pixels: vec![0; (size * size) as usize],
+            glyphs: HashMap::new(),
+            cursor: [PADDING, PADDING],
+            row_height: 0,
+            solid_uv: [0.0, 0.0],
+            full: false,
+            dirty: true,
+        };
+
+        // A fully covered block for drawing rules, sampled in the middle so filtering can't fade it
+        const SOLID: u32 = 4;
+        let [x, y] = atlas
+            .allocate(SOLID, SOLID)
+            .expect("Atlas fits the solid block");
+        for row in y..y + SOLID {
+            let start = (row * size + x) as usize;
+            atlas.pixels[start..start + SOLID as usize].fill(u8::MAX);
+        }
+        atlas.solid_uv = [
+            (x + SOLID / 2) as f32 / size as f32,
+            (y + SOLID / 2) as f32 / size as f32,
+        ];
+        atlas
+    }
+
+    /// Texture coordinate that is always fully covered
+    pub fn solid_uv(&self) -> [f32; 2] {
+        self.solid_uv
+    }
+
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns true once if glyphs were added since the last call
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Looks up a glyph at a pixel size, rasterizing it the first time.
+    /// Sizes are rounded to whole pixels so nearby sizes share a bitmap.
+    pub fn glyph(&mut self, c: char, size: f32) -> Option<AtlasGlyph> {
+        let px = size.round().max(1.0) as u32;
+        if let Some(glyph) = self.glyphs.get(&(c, px)) {
+            return *glyph;
+        }
+
+        let (metrics, bitmap) = self.font.rasterize(c, px as f32);
+        let [width, height] = [metrics.width as u32, metrics.height as u32];
+        let offset = [
+            metrics.xmin as f32,
+            -(metrics.ymin as f32 + metrics.height as f32),
+        ];
+
+        if width == 0 || height == 0 {
+            let glyph = AtlasGlyph {
+                offset,
+                size: [0.0, 0.0],
+                uv_min: [0.0, 0.0],
+                uv_max: [0.0, 0.0],
+            };
+            self.glyphs.insert((c, px), Some(glyph));
+            return Some(glyph);
+        }
+
+        let Some([x, y]) = self.allocate(width, height) else {
+            self.glyphs.insert((c, px), None);
+            return None;
+        };
+        for row in 0..height {
+            let src = (row * width) as usize;
+            let dst = ((y + row) * self.size + x) as usize;
+            self.pixels[dst..dst + width as usize]
+                .copy_from_slice(&bitmap[src..src + width as usize]);
+        }
+        self.dirty = true;
+
+        let scale = 1.0 / self.size as f32;
+        let glyph = AtlasGlyph {
+            offset,
+            size: [width as f32, height as f32],
+            uv_min: [x as f32 * scale, y as f32 * scale],
+            uv_max: [(x + width) as f32 * scale, (y + height) as f32 * scale],
+        };
+        self.glyphs.insert((c, px), Some(glyph));
+        Some(glyph)
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        if self.cursor[0] + width + PADDING > self.size {
+            self.cursor = [PADDING, self.cursor[1] + self.row_height + PADDING];
+            self.row_height = 0;
+        }
+        if self.cursor[0] + width + PADDING > self.size
+            || self.cursor[1] + height + PADDING > self.size
+        {
+            if !self.full {
+                warn!("Glyph atlas is full, some text won't be drawn");
+                self.full = true;
+            }
+            return None;
+        }
+
+        let position = self.cursor;
+        self.cursor[0] += width + PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs_are_cached_and_packed() {
+        let mut atlas = GlyphAtlas::new(Font::bundled(), 64);
+        assert!(atlas.take_dirty());
+
+        let a = atlas.glyph('a', 20.0).unwrap();
+        let b = atlas.glyph('b', 20.0).unwrap();
+        assert!(atlas.take_dirty());
+        assert_eq!(atlas.glyph('a', 20.2), Some(a));
+        assert!(!atlas.take_dirty());
+
+        // Side by side on the first row without overlapping
+        assert!(b.uv_min[0] >= a.uv_max[0]);
+        assert_eq!(a.uv_min[1], b.uv_min[1]);
+        assert!(atlas.glyph(' ', 20.0).unwrap().is_empty());
+
+        // Eventually the small atlas runs out of room instead of overwriting glyphs
+        let placed = "ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+            .chars()
+            .filter_map(|c| atlas.glyph(c, 20.0))
+            .count();
+        assert!(placed < 26);
+        // Glyphs that didn't fit are remembered rather than rasterized again
+        let cached = atlas.glyphs.len();
+        assert_eq!(atlas.glyph('Z', 20.0), None);
+        assert_eq!(atlas.glyphs.len(), cached);
+        assert!(atlas.glyphs[&('Z', 20)].is_none());
+    }
+}
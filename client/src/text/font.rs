@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// DejaVu Sans, covers Latin, Greek and the letterlike math symbols
+const BUNDLED_FONT: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontError(&'static str);
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Couldn't load font: {}", self.0)
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Vertical metrics of a line in pixels, ascent is above the baseline and descent below it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_height: f32,
+}
+
+#[derive(Clone)]
+pub struct Font {
+    inner: fontdue::Font,
+}
+
+impl Font {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, FontError> {
+        fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+            .map(|inner| Self { inner })
+            .map_err(FontError)
+    }
+
+    /// The font shipped with the client
+    pub fn bundled() -> Self {
+        Self::from_bytes(BUNDLED_FONT).expect("The bundled font is valid")
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.inner.lookup_glyph_index(c) != 0
+    }
+
+    pub fn advance(&self, c: char, size: f32) -> f32 {
+        self.inner.metrics(c, size).advance_width
+    }
+
+    /// Height of the glyph's outline above and below the baseline
+    pub fn extent(&self, c: char, size: f32) -> [f32; 2] {
+        let bounds = self.inner.metrics(c, size).bounds;
+        [bounds.ymin + bounds.height, -bounds.ymin]
+    }
+
+    pub fn kern(&self, left: char, right: char, size: f32) -> f32 {
+        self.inner.horizontal_kern(left, right, size).unwrap_or(0.0)
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        match self.inner.horizontal_line_metrics(size) {
+            Some(metrics) => LineMetrics {
+                ascent: metrics.ascent,
+                descent: -metrics.descent,
+                line_height: metrics.new_line_size,
+            },
+            None => LineMetrics {
+                ascent: size * 0.8,
+                descent: size * 0.2,
+                line_height: size * 1.2,
+            },
+        }
+    }
+
+    pub(super) fn rasterize(&self, c: char, size: f32) -> (fontdue::Metrics, Vec<u8>) {
+        self.inner.rasterize(c, size)
+    }
+}
@@ -0,0 +1,153 @@
+use super::font::Font;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VAlign {
+    #[default]
+    Top,
+    Middle,
+    Baseline,
+    Bottom,
+}
+
+/// A glyph placed on its baseline, `position` is the pen position in pixels (y down)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    pub c: char,
+    pub size: f32,
+    pub position: [f32; 2],
+}
+
+/// Glyphs laid out relative to the baseline of the first line at the origin
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub width: f32,
+    /// Extent above the first baseline
+    pub ascent: f32,
+    /// Extent below the first baseline
+    pub descent: f32,
+    /// Filled rectangles such as fraction bars, `[x, y, width, height]` with y down
+    pub rules: Vec<[f32; 4]>,
+}
+
+impl TextLayout {
+    /// Lays out plain text, lines are split on `\n` and aligned horizontally against each other
+    pub fn new(font: &Font, text: &str, size: f32, h_align: HAlign) -> Self {
+        let metrics = font.line_metrics(size);
+        let mut layout = Self {
+            ascent: metrics.ascent,
+            ..Self::default()
+        };
+
+        let mut lines = Vec::new();
+        for (i, line) in text.split('\n').enumerate() {
+            let baseline = i as f32 * metrics.line_height;
+            let start = layout.glyphs.len();
+            let mut pen = 0.0;
+            let mut previous = None;
+            for c in line.chars() {
+                if let Some(previous) = previous {
+                    pen += font.kern(previous, c, size);
+                }
+                layout.glyphs.push(PositionedGlyph {
+                    c,
+                    size,
+                    position: [pen, baseline],
+                });
+                pen += font.advance(c, size);
+                previous = Some(c);
+            }
+            layout.width = layout.width.max(pen);
+            layout.descent = baseline + metrics.descent;
+            lines.push((start..layout.glyphs.len(), pen));
+        }
+
+        for (glyphs, line_width) in lines {
+            let shift = match h_align {
+                HAlign::Left => 0.0,
+                HAlign::Center => (layout.width - line_width) / 2.0,
+                HAlign::Right => layout.width - line_width,
+            };
+            layout.glyphs[glyphs]
+                .iter_mut()
+                .for_each(|glyph| glyph.position[0] += shift);
+        }
+        layout
+    }
+
+    pub fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    /// Offset that puts the anchor point of the block at the origin
+    pub fn anchor_offset(&self, h_align: HAlign, v_align: VAlign) -> [f32; 2] {
+        let x = match h_align {
+            HAlign::Left => 0.0,
+            HAlign::Center => -self.width / 2.0,
+            HAlign::Right => -self.width,
+        };
+        let y = match v_align {
+            VAlign::Top => self.ascent,
+            VAlign::Middle => (self.ascent - self.descent) / 2.0,
+            VAlign::Baseline => 0.0,
+            VAlign::Bottom => -self.descent,
+        };
+        [x, y]
+    }
+
+    /// Moves everything by `offset`, the origin stays where it was
+    pub fn translate(&mut self, offset: [f32; 2]) {
+        for glyph in &mut self.glyphs {
+            glyph.position[0] += offset[0];
+            glyph.position[1] += offset[1];
+        }
+        for rule in &mut self.rules {
+            rule[0] += offset[0];
+            rule[1] += offset[1];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_and_alignment() {
+        let font = Font::bundled();
+        let layout = TextLayout::new(&font, "ab\nabcd", 20.0, HAlign::Right);
+        let metrics = font.line_metrics(20.0);
+
+        assert_eq!(layout.glyphs.len(), 6);
+        assert_eq!(layout.glyphs[2].position[0], 0.0);
+        assert_eq!(layout.glyphs[2].position[1], metrics.line_height);
+        // The short first line is pushed right so both lines end together
+        let first_line_end = layout.glyphs[1].position[0] + font.advance('b', 20.0);
+        assert!(layout.glyphs[0].position[0] > 0.0);
+        assert!((first_line_end - layout.width).abs() < 1e-3);
+
+        assert_eq!(
+            layout.anchor_offset(HAlign::Left, VAlign::Baseline),
+            [0.0, 0.0]
+        );
+        let [x, y] = layout.anchor_offset(HAlign::Center, VAlign::Bottom);
+        assert_eq!(x, -layout.width / 2.0);
+        assert_eq!(y, -(metrics.line_height + metrics.descent));
+    }
+
+    #[test]
+    fn test_bundled_font_covers_math() {
+        let font = Font::bundled();
+        for c in "αβγπΣΩℤℂℝℚℕ×∞".chars() {
+            assert!(font.has_glyph(c), "missing {}", c);
+        }
+    }
+}
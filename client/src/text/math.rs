@@ -0,0 +1,462 @@
+//! Typesets a small subset of LaTeX math such as `f(z) = z^{-2}` or
+//! `\mathbb{Z}_4 \times \mathbb{Z}_2` into a `TextLayout`.
+//!
+//! Supported:
+//! ```text
+//! x^a  x_a  x^{...}_{...}   scripts, either order
+//! \frac{a}{b}               fractions
+//! \sqrt{a}                  square roots
+//! \alpha ... \Omega         Greek letters
+//! \mathbb{C}                blackboard bold C H N P Q R Z
+//! \sin \cos \ln ...         upright operator names
+//! \times \cdot \pm \to ...  symbols, see `SYMBOLS`
+//! \, \quad                  spacing
+//! ```
+
+use std::fmt;
+
+use super::font::Font;
+use super::layout::{PositionedGlyph, TextLayout};
+
+/// Scripts are drawn at this fraction of the size of their base
+const SCRIPT_SCALE: f32 = 0.7;
+/// Numerators and denominators are drawn at this fraction of the surrounding size
+const FRACTION_SCALE: f32 = 0.85;
+/// Height of the fraction bar centre above the baseline, in ems
+const MATH_AXIS: f32 = 0.3;
+/// Space either side of binary operators and relations, in ems
+const OPERATOR_SPACE: f32 = 0.22;
+/// Deeper input is rejected so layout can't overflow the stack. `complex::expr`
+/// derives its own limit from this one so every caption it writes fits.
+pub const MAX_DEPTH: usize = 128;
+
+#[rustfmt::skip]
+const SYMBOLS: &[(&str, char)] = &[
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ε'),
+    ("zeta", 'ζ'), ("eta", 'η'), ("theta", 'θ'), ("iota", 'ι'), ("kappa", 'κ'),
+    ("lambda", 'λ'), ("mu", 'μ'), ("nu", 'ν'), ("xi", 'ξ'), ("pi", 'π'), ("rho", 'ρ'),
+    ("sigma", 'σ'), ("tau", 'τ'), ("upsilon", 'υ'), ("phi", 'φ'), ("chi", 'χ'),
+    ("psi", 'ψ'), ("omega", 'ω'),
+    ("Gamma", 'Γ'), ("Delta", 'Δ'), ("Theta", 'Θ'), ("Lambda", 'Λ'), ("Xi", 'Ξ'),
+    ("Pi", 'Π'), ("Sigma", 'Σ'), ("Upsilon", 'Υ'), ("Phi", 'Φ'), ("Psi", 'Ψ'),
+    ("Omega", 'Ω'),
+    ("times", '×'), ("cdot", '·'), ("pm", '±'), ("circ", '∘'), ("to", '→'),
+    ("mapsto", '↦'), ("in", '∈'), ("le", '≤'), ("ge", '≥'), ("neq", '≠'),
+    ("cong", '≅'), ("infty", '∞'), ("ldots", '…'), ("{", '{'), ("}", '}'),
+];
+
+const OPERATOR_NAMES: &[&str] = &["sin", "cos", "tan", "exp", "ln", "log", "det", "gcd"];
+
+/// Characters that get space around them
+const OPERATORS: &[char] = &[
+    '+', '−', '=', '<', '>', '×', '·', '±', '∘', '→', '↦', '∈', '≤', '≥', '≠', '≅',
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MathError {
+    UnknownCommand(String),
+    UnexpectedChar(char, usize),
+    UnexpectedEnd,
+    MissingArgument(String),
+    DoubleScript(usize),
+    NotBlackboard(String),
+    TooDeep,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(name) => write!(f, "unknown command '\\{}'", name),
+            Self::UnexpectedChar(c, at) => write!(f, "unexpected '{}' at {}", c, at),
+            Self::UnexpectedEnd => write!(f, "formula ended unexpectedly"),
+            Self::MissingArgument(name) => write!(f, "'\\{}' is missing an argument", name),
+            Self::DoubleScript(at) => write!(f, "script at {} is already set", at),
+            Self::NotBlackboard(text) => write!(f, "no blackboard bold for '{}'", text),
+            Self::TooDeep => write!(f, "formula is nested too deeply"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Char(char),
+    /// Upright text that is spaced like a function name
+    Operator(String),
+    Space(f32),
+    Row(Vec<Node>),
+    Scripts {
+        base: Box<Node>,
+        sup: Option<Box<Node>>,
+        sub: Option<Box<Node>>,
+    },
+    Fraction(Box<Node>, Box<Node>),
+    Sqrt(Box<Node>),
+}
+
+/// Parses and lays out `source` at `size` pixels, with the origin on the baseline
+pub fn layout(font: &Font, source: &str, size: f32) -> Result<TextLayout, MathError> {
+    let mut parser = Parser {
+        chars: source.chars().collect(),
+        position: 0,
+        depth: 0,
+    };
+    let row = parser.row()?;
+    if let Some(c) = parser.peek() {
+        return Err(MathError::UnexpectedChar(c, parser.position));
+    }
+    Ok(layout_node(font, &row, size))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Atoms with their scripts until the end of input or a closing brace
+    fn row(&mut self) -> Result<Node, MathError> {
+        let mut nodes = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('}') => break,
+                Some(c @ ('^' | '_')) => {
+                    let at = self.position;
+                    self.position += 1;
+                    let script = self.argument(if c == '^' { "^" } else { "_" })?;
+                    let base = nodes.pop().unwrap_or(Node::Row(vec![]));
+                    nodes.push(attach_script(base, c == '^', script, at)?);
+                }
+                Some(_) => nodes.push(self.atom()?),
+            }
+        }
+
+        Ok(Node::Row(nodes))
+    }
+
+    /// A braced group or a single atom
+    fn argument(&mut self, command: &str) -> Result<Node, MathError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None | Some('}') => Err(MathError::MissingArgument(command.to_string())),
+            Some(_) => self.atom(),
+        }
+    }
+
+    /// Braces, commands and scripts all nest through here, so this is where depth is limited
+    fn atom(&mut self) -> Result<Node, MathError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(MathError::TooDeep);
+        }
+
+        let at = self.position;
+        let c = self.peek().ok_or(MathError::UnexpectedEnd)?;
+        self.position += 1;
+        let node = match c {
+            '{' => {
+                let row = self.row()?;
+                match self.peek() {
+                    Some('}') => {
+                        self.position += 1;
+                        row
+                    }
+                    _ => return Err(MathError::UnexpectedEnd),
+                }
+            }
+            '\\' => self.command()?,
+            '^' | '_' | '}' => return Err(MathError::UnexpectedChar(c, at)),
+            '-' => Node::Char('−'),
+            c => Node::Char(c),
+        };
+
+        self.depth -= 1;
+        Ok(node)
+    }
+
+    fn command(&mut self) -> Result<Node, MathError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.position += 1;
+        }
+        // Single symbol commands like `\,` or `\{`
+        if self.position == start {
+            self.position += 1;
+        }
+        let name: String = self.chars[start..self.position.min(self.chars.len())]
+            .iter()
+            .collect();
+
+        match name.as_str() {
+            "" => Err(MathError::UnexpectedEnd),
+            "," => Ok(Node::Space(0.17)),
+            "quad" => Ok(Node::Space(1.0)),
+            "frac" => {
+                let numerator = self.argument(&name)?;
+                let denominator = self.argument(&name)?;
+                Ok(Node::Fraction(Box::new(numerator), Box::new(denominator)))
+            }
+            "mathbb" => match self.argument(&name)? {
+                Node::Char(c) => blackboard(c).map(Node::Char),
+                Node::Row(nodes) => match nodes.as_slice() {
+                    [Node::Char(c)] => blackboard(*c).map(Node::Char),
+                    _ => Err(MathError::NotBlackboard("{...}".to_string())),
+                },
+                _ => Err(MathError::NotBlackboard("\\...".to_string())),
+            },
+            "sqrt" => Ok(Node::Sqrt(Box::new(self.argument(&name)?))),
+            name if OPERATOR_NAMES.contains(&name) => Ok(Node::Operator(name.to_string())),
+            name => SYMBOLS
+                .iter()
+                .find(|(symbol, _)| *symbol == name)
+                .map(|(_, c)| Node::Char(*c))
+                .ok_or_else(|| MathError::UnknownCommand(name.to_string())),
+        }
+    }
+}
+
+fn attach_script(base: Node, sup: bool, script: Node, at: usize) -> Result<Node, MathError> {
+    let script = Some(Box::new(script));
+    match base {
+        Node::Scripts {
+            base,
+            sup: None,
+            sub,
+        } if sup => Ok(Node::Scripts {
+            base,
+            sup: script,
+            sub,
+        }),
+        Node::Scripts {
+            base,
+            sup: existing,
+            sub: None,
+        } if !sup => Ok(Node::Scripts {
+            base,
+            sup: existing,
+            sub: script,
+        }),
+        Node::Scripts { .. } => Err(MathError::DoubleScript(at)),
+        base => Ok(Node::Scripts {
+            base: Box::new(base),
+            sup: if sup { script.clone() } else { None },
+            sub: if sup { None } else { script },
+        }),
+    }
+}
+
+fn blackboard(c: char) -> Result<char, MathError> {
+    match c {
+        'C' => Ok('ℂ'),
+        'H' => Ok('ℍ'),
+        'N' => Ok('ℕ'),
+        'P' => Ok('ℙ'),
+        'Q' => Ok('ℚ'),
+        'R' => Ok('ℝ'),
+        'Z' => Ok('ℤ'),
+        c => Err(MathError::NotBlackboard(c.to_string())),
+    }
+}
+
+fn layout_node(font: &Font, node: &Node, size: f32) -> TextLayout {
+    match node {
+        Node::Char(c) => {
+            let [ascent, descent] = font.extent(*c, size);
+            TextLayout {
+                glyphs: vec![PositionedGlyph {
+                    c: *c,
+                    size,
+                    position: [0.0, 0.0],
+                }],
+                width: font.advance(*c, size),
+                ascent,
+                descent,
+                rules: vec![],
+            }
+        }
+        Node::Operator(name) => {
+            let chars = name.chars().map(Node::Char).collect();
+            let mut layout = layout_node(font, &Node::Row(chars), size);
+            layout.width += OPERATOR_SPACE * size * 0.75;
+            layout
+        }
+        Node::Space(ems) => TextLayout {
+            width: ems * size,
+            ..TextLayout::default()
+        },
+        Node::Row(nodes) => {
+            let mut row = TextLayout::default();
+            for (i, node) in nodes.iter().enumerate() {
+                let spaced = matches!(node, Node::Char(c) if OPERATORS.contains(c));
+                // A leading minus is a sign rather than an operator
+                if spaced && i > 0 {
+                    row.width += OPERATOR_SPACE * size;
+                }
+                let child = layout_node(font, node, size);
+                let x = row.width;
+                append(&mut row, child, [x, 0.0]);
+                if spaced && i > 0 {
+                    row.width += OPERATOR_SPACE * size;
+                }
+            }
+            row
+        }
+        Node::Scripts { base, sup, sub } => {
+            let mut layout = layout_node(font, base, size);
+            let base_width = layout.width;
+            let script_size = size * SCRIPT_SCALE;
+            let mut width = base_width;
+
+            if let Some(sup) = sup {
+                let sup = layout_node(font, sup, script_size);
+                let raise = (0.4 * size).max(layout.ascent - 0.3 * size);
+                width = width.max(base_width + sup.width);
+                append(&mut layout, sup, [base_width, -raise]);
+            }
+            if let Some(sub) = sub {
+                let sub = layout_node(font, sub, script_size);
+                let lower = (0.2 * size).max(layout.descent);
+                width = width.max(base_width + sub.width);
+                append(&mut layout, sub, [base_width, lower]);
+            }
+            layout.width = width + 0.05 * size;
+            layout
+        }
+        Node::Fraction(numerator, denominator) => {
+            let inner = size * FRACTION_SCALE;
+            let numerator = layout_node(font, numerator, inner);
+            let denominator = layout_node(font, denominator, inner);
+
+            let thickness = (0.06 * size).max(1.0);
+            let gap = 0.15 * size;
+            let padding = 0.1 * size;
+            let width = numerator.width.max(denominator.width) + 2.0 * padding;
+            let bar_top = -MATH_AXIS * size - thickness / 2.0;
+
+            let mut layout = TextLayout {
+                width,
+                rules: vec![[0.0, bar_top, width, thickness]],
+                ..TextLayout::default()
+            };
+            let numerator_baseline = bar_top - gap - numerator.descent;
+            let denominator_baseline = bar_top + thickness + gap + denominator.ascent;
+            let numerator_x = (width - numerator.width) / 2.0;
+            let denominator_x = (width - denominator.width) / 2.0;
+            append(&mut layout, numerator, [numerator_x, numerator_baseline]);
+            append(
+                &mut layout,
+                denominator,
+                [denominator_x, denominator_baseline],
+            );
+            layout.width = width;
+            layout
+        }
+        Node::Sqrt(radicand) => {
+            let mut layout = layout_node(font, &Node::Char('√'), size);
+            let radicand = layout_node(font, radicand, size);
+            let thickness = (0.06 * size).max(1.0);
+            // The bar continues from the top of the radical sign over the radicand
+            let top = -(radicand.ascent + 0.15 * size).max(layout.ascent - thickness);
+            let x = layout.width;
+            let bar_width = radicand.width + 0.1 * size;
+            layout.rules.push([x, top, bar_width, thickness]);
+            layout.ascent = layout.ascent.max(-top);
+            append(&mut layout, radicand, [x, 0.0]);
+            layout.width = x + bar_width;
+            layout
+        }
+    }
+}
+
+/// Places `child` with its origin at `offset`, growing `parent` to fit
+fn append(parent: &mut TextLayout, mut child: TextLayout, offset: [f32; 2]) {
+    parent.width = parent.width.max(offset[0] + child.width);
+    parent.ascent = parent.ascent.max(child.ascent - offset[1]);
+    parent.descent = parent.descent.max(child.descent + offset[1]);
+    child.translate(offset);
+    parent.glyphs.append(&mut child.glyphs);
+    parent.rules.append(&mut child.rules);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(layout: &TextLayout, c: char) -> PositionedGlyph {
+        *layout.glyphs.iter().find(|glyph| glyph.c == c).unwrap()
+    }
+
+    #[test]
+    fn test_scripts_and_symbols() {
+        let font = Font::bundled();
+        let layout = layout(&font, r"\mathbb{Z}_4 \times \alpha^{-2}", 20.0).unwrap();
+        let chars: String = layout.glyphs.iter().map(|glyph| glyph.c).collect();
+        assert_eq!(chars, "ℤ4×α−2");
+
+        let sub = glyph(&layout, '4');
+        assert!(sub.position[1] > 0.0);
+        assert_eq!(sub.size, 20.0 * SCRIPT_SCALE);
+        let sup = glyph(&layout, '2');
+        assert!(sup.position[1] < 0.0);
+        assert!(glyph(&layout, '×').position[0] > sub.position[0]);
+
+        // Both scripts on one base stack at the same x
+        let both = super::layout(&font, "x_1^2", 20.0).unwrap();
+        assert_eq!(glyph(&both, '1').position[0], glyph(&both, '2').position[0]);
+    }
+
+    #[test]
+    fn test_fraction() {
+        let font = Font::bundled();
+        let layout = layout(&font, r"\frac{a z + b}{c}", 20.0).unwrap();
+        let [x, y, width, height] = layout.rules[0];
+
+        assert_eq!(x, 0.0);
+        assert_eq!(width, layout.width);
+        assert!(glyph(&layout, 'a').position[1] < y);
+        assert!(glyph(&layout, 'c').position[1] > y + height);
+        // The short denominator is centred under the bar
+        let c = glyph(&layout, 'c');
+        let centre = c.position[0] + font.advance('c', c.size) / 2.0;
+        assert!((centre - width / 2.0).abs() < 0.5);
+        assert!(layout.ascent > 20.0 * MATH_AXIS);
+    }
+
+    #[test]
+    fn test_errors() {
+        let font = Font::bundled();
+        let error = |source: &str| layout(&font, source, 20.0).unwrap_err();
+        assert_eq!(error(r"\foo"), MathError::UnknownCommand("foo".to_string()));
+        assert_eq!(
+            error(r"\frac{a}"),
+            MathError::MissingArgument("frac".to_string())
+        );
+        assert_eq!(error("x^2^3"), MathError::DoubleScript(3));
+        assert_eq!(error("{x"), MathError::UnexpectedEnd);
+        assert_eq!(error("x}"), MathError::UnexpectedChar('}', 1));
+        assert_eq!(
+            error(r"\mathbb{A}"),
+            MathError::NotBlackboard("A".to_string())
+        );
+        assert_eq!(error(&"{".repeat(200)), MathError::TooDeep);
+        // Commands nest without braces
+        assert_eq!(
+            error(&format!("{}x", r"\sqrt".repeat(200))),
+            MathError::TooDeep
+        );
+        assert_eq!(error(&r"\frac".repeat(200)), MathError::TooDeep);
+    }
+}
@@ -0,0 +1,193 @@
+mod atlas;
+mod font;
+mod layout;
+pub mod math;
+mod renderer;
+
+use tracing::warn;
+
+use crate::engine::{Engine, EngineObject, RenderData, UpdateContext};
+pub use font::{Font, FontError, LineMetrics};
+pub use layout::{HAlign, PositionedGlyph, TextLayout, VAlign};
+pub use renderer::{TextRenderer, TextResources};
+
+/// A string placed on screen, `position` is in window pixels and is where the
+/// alignment anchor of the text ends up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    pub content: String,
+    pub position: [f32; 2],
+    pub size: f32,
+    pub color: [f32; 4],
+    pub h_align: HAlign,
+    pub v_align: VAlign,
+    /// Typeset `content` as LaTeX math, see `text::math`
+    pub math: bool,
+    /// Color of a box filled in behind the text, for reading it over busy backgrounds
+    pub background: Option<[f32; 4]>,
+}
+
+impl Text {
+    pub fn new(content: impl Into<String>, position: [f32; 2]) -> Self {
+        Self {
+            content: content.into(),
+            position,
+            size: 18.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            h_align: HAlign::Left,
+            v_align: VAlign::Top,
+            math: false,
+            background: None,
+        }
+    }
+
+    /// A formula such as `f(z) = z^{-2}`
+    pub fn math(source: impl Into<String>, position: [f32; 2]) -> Self {
+        Self {
+            math: true,
+            ..Self::new(source, position)
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_background(mut self, color: [f32; 4]) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub fn with_align(mut self, h_align: HAlign, v_align: VAlign) -> Self {
+        self.h_align = h_align;
+        self.v_align = v_align;
+        self
+    }
+
+    fn anchor(&self, layout: &TextLayout) -> [f32; 2] {
+        layout.anchor_offset(self.h_align, self.v_align)
+    }
+
+    /// Falls back to showing the source when a formula doesn't parse
+    fn layout(&self, font: &Font) -> TextLayout {
+        if self.math {
+            match math::layout(font, &self.content, self.size) {
+                Ok(layout) => return layout,
+                Err(error) => warn!("Couldn't typeset '{}': {}", self.content, error),
+            }
+        }
+        TextLayout::new(font, &self.content, self.size, self.h_align)
+    }
+}
+
+/// Draws a set of `Text`s on top of whatever was added before it
+pub struct TextObject {
+    texts: Vec<Text>,
+    dirty: bool,
+    renderer: TextRenderer,
+}
+
+impl TextObject {
+    pub fn new(engine: &Engine) -> Self {
+        Self {
+            texts: Vec::new(),
+            dirty: true,
+            renderer: TextRenderer::new(engine),
+        }
+    }
+
+    pub fn with_text(mut self, text: Text) -> Self {
+        self.push(text);
+        self
+    }
+
+    /// Adds a text, returning its index for the setters
+    pub fn push(&mut self, text: Text) -> usize {
+        self.texts.push(text);
+        self.dirty = true;
+        self.texts.len() - 1
+    }
+
+    pub fn set(&mut self, index: usize, text: Text) {
+        self.texts[index] = text;
+        self.dirty = true;
+    }
+
+    pub fn set_content(&mut self, index: usize, content: impl Into<String>) {
+        self.texts[index].content = content.into();
+        self.dirty = true;
+    }
+
+    pub fn set_size(&mut self, index: usize, size: f32) {
+        self.texts[index].size = size;
+        self.dirty = true;
+    }
+
+    pub fn set_color(&mut self, index: usize, color: [f32; 4]) {
+        self.texts[index].color = color;
+        self.dirty = true;
+    }
+
+    pub fn set_align(&mut self, index: usize, h_align: HAlign, v_align: VAlign) {
+        let text = &mut self.texts[index];
+        text.h_align = h_align;
+        text.v_align = v_align;
+        self.dirty = true;
+    }
+
+    pub fn texts(&self) -> &[Text] {
+        &self.texts
+    }
+
+    pub fn clear(&mut self) {
+        self.texts.clear();
+        self.dirty = true;
+    }
+}
+
+impl EngineObject for TextObject {
+    fn update(&mut self, context: &UpdateContext) {
+        if self.dirty {
+            self.renderer
+                .prepare(context.device, context.queue, &[&self.texts]);
+            self.dirty = false;
+        }
+    }
+
+    fn render(&self) -> Option<RenderData<'_>> {
+        self.renderer.render_data(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_engine;
+
+    #[test]
+    fn test_text_object_draws_with_its_color() {
+        let Some(mut engine) = test_engine(64, 32) else {
+            return;
+        };
+        let mut object = TextObject::new(&engine);
+        let index = object.push(Text::new("Hi", [4.0, 4.0]));
+        object.set_size(index, 24.0);
+        object.set_color(index, [1.0, 0.0, 0.0, 1.0]);
+        object.set_align(index, HAlign::Left, VAlign::Top);
+        assert_eq!(object.texts()[index].size, 24.0);
+        engine.add_engine_object(Box::new(object));
+        engine.render_frame().unwrap();
+
+        // Red glyphs on the black background, nothing else
+        let frame = engine.capture_frame().unwrap();
+        let pixels: Vec<&[u8]> = frame.pixels.chunks(4).collect();
+        assert!(pixels.iter().any(|p| p[0] > 200 && p[1] < 40 && p[2] < 40));
+        assert!(pixels.iter().all(|p| p[1] < 40 && p[2] < 40));
+    }
+}
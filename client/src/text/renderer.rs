@@ -0,0 +1,320 @@
+use std::cell::RefCell;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::rc::Rc;
+
+use wgpu::util::DeviceExt;
+
+use super::atlas::GlyphAtlas;
+use super::font::Font;
+use super::layout::TextLayout;
+use super::Text;
+use crate::engine::{overlay_depth_state, Engine, InstanceBuffer, RenderData};
+use crate::types::{GlyphInstance, Vertex2};
+
+/// Side of the square glyph atlas texture in pixels
+const ATLAS_SIZE: u32 = 1024;
+
+/// Margin around text with a background, as a fraction of the font size
+const BACKGROUND_PADDING: f32 = 0.35;
+
+const QUAD: &[Vertex2] = &[
+    Vertex2::new([0.0, 0.0]),
+    Vertex2::new([1.0, 0.0]),
+    Vertex2::new([0.0, 1.0]),
+    Vertex2::new([1.0, 1.0]),
+];
+
+/// The glyph atlas and the GPU objects for drawing from it. Parsing the font and
+/// allocating the atlas is costly, so every `TextRenderer` shares the engine's one.
+pub struct TextResources {
+    atlas: RefCell<GlyphAtlas>,
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+}
+
+impl TextResources {
+    pub fn new(engine: &Engine, font: Font) -> Self {
+        let device = engine.device();
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./text.wgsl"));
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("glyph_atlas_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("glyph_atlas_bind_group"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Text Layout"),
+                bind_group_layouts: &[engine.uniform_bind_group(), &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Descriptor"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex2::desc(), GlyphInstance::instance_desc()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(overlay_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: engine.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: engine.surface_format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Glyph Quad"),
+            contents: bytemuck::cast_slice(QUAD),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self {
+            atlas: RefCell::new(GlyphAtlas::new(font, ATLAS_SIZE)),
+            texture,
+            bind_group,
+            render_pipeline,
+            vertex_buffer,
+        }
+    }
+
+    /// Writes the atlas to the texture if any renderer added glyphs since the last write
+    fn upload(&self, queue: &wgpu::Queue) {
+        let mut atlas = self.atlas.borrow_mut();
+        if !atlas.take_dirty() {
+            return;
+        }
+        let size = atlas.size();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            atlas.pixels(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(size),
+                rows_per_image: NonZeroU32::new(size),
+            },
+            wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+/// Lays out text and draws it as instanced quads sampling the shared glyph atlas.
+/// Text is grouped in layers so it can be interleaved with other draws.
+pub struct TextRenderer {
+    resources: Rc<TextResources>,
+    instance_buffer: InstanceBuffer<GlyphInstance>,
+    instances: Vec<GlyphInstance>,
+    layers: Vec<Range<u32>>,
+}
+
+impl TextRenderer {
+    pub fn new(engine: &Engine) -> Self {
+        Self {
+            resources: engine.text_resources(),
+            instance_buffer: InstanceBuffer::new(engine.device(), 256, "Glyph Instance Buffer"),
+            instances: Vec::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Lays out every layer of text and uploads the glyphs along with any new atlas pixels
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, layers: &[&[Text]]) {
+        self.instances.clear();
+        self.layers.clear();
+        for texts in layers {
+            let start = self.instances.len() as u32;
+            for text in texts.iter() {
+                let layout = text.layout(self.resources.atlas.borrow().font());
+                let anchor = text.anchor(&layout);
+                if let Some(background) = text.background {
+                    let padding = (text.size * BACKGROUND_PADDING).round();
+                    self.push_background(&layout, text.position, anchor, padding, background);
+                }
+                self.push_layout(&layout, text.position, anchor, text.color);
+            }
+            self.layers.push(start..self.instances.len() as u32);
+        }
+        self.upload(device, queue);
+    }
+
+    /// Adds glyphs that were already laid out, `origin` is where the layout's origin goes
+    pub fn push_layout(
+        &mut self,
+        layout: &TextLayout,
+        origin: [f32; 2],
+        anchor: [f32; 2],
+        color: [f32; 4],
+    ) {
+        let base = [origin[0] + anchor[0], origin[1] + anchor[1]];
+        let mut atlas = self.resources.atlas.borrow_mut();
+        for glyph in &layout.glyphs {
+            let Some(atlas_glyph) = atlas.glyph(glyph.c, glyph.size) else {
+                continue;
+            };
+            if atlas_glyph.is_empty() {
+                continue;
+            }
+            // Snapping to whole pixels keeps the bitmaps crisp
+            let offset = [
+                (base[0] + glyph.position[0]).round() + atlas_glyph.offset[0],
+                (base[1] + glyph.position[1]).round() + atlas_glyph.offset[1],
+            ];
+            self.instances.push(GlyphInstance::new(
+                offset,
+                atlas_glyph.size,
+                atlas_glyph.uv_min,
+                atlas_glyph.uv_max,
+                color,
+            ));
+        }
+
+        let solid = atlas.solid_uv();
+        for [x, y, width, height] in &layout.rules {
+            let offset = [(base[0] + x).round(), (base[1] + y).round()];
+            let size = [width.round().max(1.0), height.round().max(1.0)];
+            self.instances
+                .push(GlyphInstance::new(offset, size, solid, solid, color));
+        }
+    }
+
+    /// A box around a layout with `padding` pixels to spare on each side
+    fn push_background(
+        &mut self,
+        layout: &TextLayout,
+        origin: [f32; 2],
+        anchor: [f32; 2],
+        padding: f32,
+        color: [f32; 4],
+    ) {
+        let offset = [
+            (origin[0] + anchor[0]).round() - padding,
+            (origin[1] + anchor[1] - layout.ascent).round() - padding,
+        ];
+        let size = [
+            layout.width.round() + 2.0 * padding,
+            layout.height().round() + 2.0 * padding,
+        ];
+        let solid = self.resources.atlas.borrow().solid_uv();
+        self.instances
+            .push(GlyphInstance::new(offset, size, solid, solid, color));
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.instance_buffer.write(device, queue, &self.instances);
+        self.resources.upload(queue);
+    }
+
+    /// Draw call for one of the layers passed to the last `prepare`
+    pub fn render_data(&self, layer: usize) -> Option<RenderData<'_>> {
+        let instances = self.layers.get(layer)?.clone();
+        if instances.is_empty() {
+            return None;
+        }
+        Some(RenderData {
+            render_pipeline: &self.resources.render_pipeline,
+            bind_groups: vec![&self.resources.bind_group],
+            vertex_buffers: vec![&self.resources.vertex_buffer],
+            instance_buffer: Some(self.instance_buffer.buffer()),
+            index_buffer: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            num_vertices: QUAD.len() as u32,
+            num_indices: 0,
+            instances,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_shader_compiles() {
+        crate::engine::validate_shader(include_str!("text.wgsl"));
+    }
+}
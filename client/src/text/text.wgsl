@@ -0,0 +1,56 @@
+struct Uniforms {
+    delta_time: f32,
+    run_time: f32,
+    width: f32,
+    height: f32
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+};
+
+// One glyph quad in window pixels, top left origin
+struct InstanceInput {
+    @location(1) offset: vec2<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) uv_min: vec2<f32>,
+    @location(4) uv_max: vec2<f32>,
+    @location(5) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let pixel = instance.offset + vertex.position * instance.size;
+    let ndc = vec2(
+        pixel.x / uniforms.width * 2.0 - 1.0,
+        1.0 - pixel.y / uniforms.height * 2.0,
+    );
+    out.position = vec4(ndc, 0.0, 1.0);
+    out.uv = mix(instance.uv_min, instance.uv_max, vertex.position);
+    out.color = instance.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas, atlas_sampler, in.uv).r;
+    return vec4(in.color.rgb, in.color.a * coverage);
+}
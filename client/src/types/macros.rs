@@ -16,8 +16,12 @@ macro_rules! match_type {
 
 // Generates a type that can be sent to the GPU
 // Pod requires a 0 padding alignment using C alignment rules
+// `Name @ n` starts the shader locations at n so per-instance data can follow per-vertex data
 macro_rules! vertex_struct {
     ($name:ident, $($field:ident: [$type:tt; $size:tt]),* $(,)?) => {
+        vertex_struct!($name @ 0, $($field: [$type; $size]),*);
+    };
+    ($name:ident @ $start:literal, $($field:ident: [$type:tt; $size:tt]),* $(,)?) => {
         #[repr(C)]
         #[derive(Copy, Clone, Debug, Pod, Zeroable, Default)]
         pub struct $name {
@@ -28,7 +32,7 @@ macro_rules! vertex_struct {
         impl $name {
             const ATTRIBUTES: [ VertexAttribute; count!($($size),*) ] = {
                 // Underscore to ignore clippy unused_assignment lints
-                let mut _shader_location = 0;
+                let mut _shader_location = $start;
                 let mut _offset: u64 = 0;
                 [$(
                     {
@@ -50,10 +54,24 @@ macro_rules! vertex_struct {
                 }
             }
 
+            pub fn instance_desc<'a>() -> VertexBufferLayout<'a> {
+                VertexBufferLayout {
+                    array_stride: size_of::<Self>() as BufferAddress,
+                    step_mode: VertexStepMode::Instance,
+                    attributes: &Self::ATTRIBUTES,
+                }
+            }
+
             pub const fn new($($field: [$type; $size]),*) -> Self {
                 Self { $($field),* }
             }
         }
+
+        impl Instance for $name {
+            fn layout<'a>() -> VertexBufferLayout<'a> {
+                Self::instance_desc()
+            }
+        }
     };
 }
 
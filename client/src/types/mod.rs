@@ -6,8 +6,38 @@ use wgpu::{BufferAddress, VertexAttribute, VertexBufferLayout, VertexFormat::*,
 #[macro_use]
 mod macros;
 
+/// Data that can be drawn once per instance, for code generic over the instance type
+pub trait Instance: Pod {
+    fn layout<'a>() -> VertexBufferLayout<'a>;
+}
+
 vertex_struct!(Vertex2, pos: [f32; 2]);
-vertex_struct!(Vertex3, pos: [f32; 3]);
+vertex_struct!(Vertex3, pos: [f32; 3], normal: [f32; 3]);
+
+// Per-instance rectangle/point data, follows a single attribute vertex
+vertex_struct!(Instance2 @ 1, offset: [f32; 2], scale: [f32; 2], color: [f32; 4]);
+
+// Per-instance placement of a solid mesh scaled about its origin, follows Vertex3
+vertex_struct!(Instance3 @ 2, offset: [f32; 3], scale: [f32; 1], color: [f32; 4]);
+
+// Per-instance placement of a solid mesh stretched along a segment, follows Vertex3
+vertex_struct!(
+    SegmentInstance @ 2,
+    start: [f32; 3],
+    end: [f32; 3],
+    radius: [f32; 1],
+    color: [f32; 4]
+);
+
+// A glyph quad in pixels with its region of the glyph atlas in texture coordinates
+vertex_struct!(
+    GlyphInstance @ 1,
+    offset: [f32; 2],
+    size: [f32; 2],
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    color: [f32; 4]
+);
 
 #[cfg(test)]
 mod tests {
@@ -51,4 +81,30 @@ mod tests {
         assert_eq!(TestVertex::ATTRIBUTES.len(), 4);
         assert_eq!(TestVertex::ATTRIBUTES, expected_attributes);
     }
+
+    #[test]
+    fn test_instance_attributes() {
+        vertex_struct!(TestInstance @ 2, offset: [f32; 2], color: [u8; 4]);
+
+        let expected_attributes = [
+            VertexAttribute {
+                format: Float32x2,
+                offset: 0,
+                shader_location: 2,
+            },
+            VertexAttribute {
+                format: Uint8x4,
+                offset: 8,
+                shader_location: 3,
+            },
+        ];
+
+        assert_eq!(TestInstance::ATTRIBUTES, expected_attributes);
+        assert_eq!(
+            TestInstance::instance_desc().step_mode,
+            VertexStepMode::Instance
+        );
+        assert_eq!(TestInstance::desc().step_mode, VertexStepMode::Vertex);
+        assert_eq!(Instance2::ATTRIBUTES[0].shader_location, 1);
+    }
 }
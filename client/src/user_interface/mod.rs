@@ -1,106 +1,53 @@
-use wgpu::util::DeviceExt;
+mod rects;
+mod ui;
 
-use crate::engine::{Engine, EngineObject, RenderData};
-use crate::types::Vertex2;
+use crate::engine::{Engine, EngineObject, EventResponse, InputEvent, RenderData, UpdateContext};
+use crate::text::TextRenderer;
 
-const SQUARE: &[Vertex2] = &[
-    Vertex2::new([0.0, 0.0]),
-    Vertex2::new([1.0, 0.0]),
-    Vertex2::new([0.0, 1.0]),
-    Vertex2::new([1.0, 1.0]),
-];
-
-const SQUARE_INDICES: &[u16] = &[0, 1, 2, 3];
+pub use rects::RectRenderer;
+pub use ui::{Color, Layer, Rect, Style, Ui};
 
+/// Draws an immediate mode `Ui` on top of the scene. The build function
+/// is called every frame to declare the widgets.
 pub struct UserInterface {
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    ui: Ui,
+    build: Box<dyn FnMut(&mut Ui)>,
+    rects: RectRenderer,
+    text: TextRenderer,
 }
 
 impl UserInterface {
-    pub fn new(engine: &Engine) -> Self {
-        let device = engine.device();
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shader.wgsl"));
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("UI Layout"),
-                bind_group_layouts: &[engine.uniform_bind_group()],
-                push_constant_ranges: &[],
-            });
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("UI Descriptor"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex2::desc()],
-            },
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: engine.sample_count(),
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: *engine.surface_format(),
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent::OVER,
-                    }),
-
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
-
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("UI Vertex Buffer"),
-            contents: bytemuck::cast_slice(SQUARE),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("UI Index Buffer"),
-            contents: bytemuck::cast_slice(SQUARE_INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
+    pub fn new(engine: &Engine, build: impl FnMut(&mut Ui) + 'static) -> Self {
         Self {
-            render_pipeline,
-            vertex_buffer,
-            index_buffer,
+            ui: Ui::new(),
+            build: Box::new(build),
+            rects: RectRenderer::new(engine),
+            text: TextRenderer::new(engine),
         }
     }
 }
 
 impl EngineObject for UserInterface {
-    fn render(&self) -> Option<RenderData> {
-        Some(RenderData {
-            render_pipeline: &self.render_pipeline,
-            vertex_buffer: &self.vertex_buffer,
-            index_buffer: Some(&self.index_buffer),
-            num_vertices: 4,
-            num_indices: SQUARE_INDICES.len() as u32,
-        })
+    fn update(&mut self, context: &UpdateContext) {
+        self.ui.begin_frame();
+        (self.build)(&mut self.ui);
+        let layers = self.ui.end_frame();
+
+        let rects: Vec<_> = layers.iter().map(|layer| layer.rects.as_slice()).collect();
+        self.rects.prepare(context.device, context.queue, &rects);
+        let texts: Vec<_> = layers.iter().map(|layer| layer.texts.as_slice()).collect();
+        self.text.prepare(context.device, context.queue, &texts);
+    }
+
+    fn input(&mut self, event: &InputEvent) -> EventResponse {
+        self.ui.handle_input(event)
+    }
+
+    /// Text is drawn after the rects of its layer
+    fn render_layers(&self) -> Vec<RenderData<'_>> {
+        (0..self.rects.layer_count())
+            .flat_map(|i| [self.rects.render_data(i), self.text.render_data(i)])
+            .flatten()
+            .collect()
     }
 }
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use wgpu::util::DeviceExt;
+
+use crate::engine::{overlay_depth_state, Engine, InstanceBuffer, RenderData};
+use crate::types::{Instance2, Vertex2};
+
+const SQUARE: &[Vertex2] = &[
+    Vertex2::new([0.0, 0.0]),
+    Vertex2::new([1.0, 0.0]),
+    Vertex2::new([0.0, 1.0]),
+    Vertex2::new([1.0, 1.0]),
+];
+
+const SQUARE_INDICES: &[u16] = &[0, 1, 2, 3];
+
+/// Draws solid rectangles in window pixels as instanced quads, over whatever
+/// was drawn before. Like text, rects are grouped in layers.
+pub struct RectRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    instance_buffer: InstanceBuffer<Instance2>,
+    instances: Vec<Instance2>,
+    layers: Vec<Range<u32>>,
+}
+
+impl RectRenderer {
+    pub fn new(engine: &Engine) -> Self {
+        let device = engine.device();
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shader.wgsl"));
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Rect Layout"),
+                bind_group_layouts: &[engine.uniform_bind_group()],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Rect Descriptor"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex2::desc(), Instance2::instance_desc()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // Pixel space has y pointing down which flips the winding
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(overlay_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: engine.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: engine.surface_format(),
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::SrcAlpha,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::OVER,
+                    }),
+
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Rect Vertex Buffer"),
+            contents: bytemuck::cast_slice(SQUARE),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Rect Index Buffer"),
+            contents: bytemuck::cast_slice(SQUARE_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            instance_buffer: InstanceBuffer::new(device, 64, "Rect Instance Buffer"),
+            instances: Vec::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Uploads every layer of rects, replacing the last ones
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, layers: &[&[Instance2]]) {
+        self.instances.clear();
+        self.layers.clear();
+        for rects in layers {
+            let start = self.instances.len() as u32;
+            self.instances.extend_from_slice(rects);
+            self.layers.push(start..self.instances.len() as u32);
+        }
+        self.instance_buffer.write(device, queue, &self.instances);
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// The draw for one layer, `None` if it has no rects
+    pub fn render_data(&self, layer: usize) -> Option<RenderData<'_>> {
+        let instances = self.layers.get(layer)?.clone();
+        if instances.is_empty() {
+            return None;
+        }
+        Some(RenderData {
+            render_pipeline: &self.render_pipeline,
+            bind_groups: vec![],
+            vertex_buffers: vec![&self.vertex_buffer],
+            instance_buffer: Some(self.instance_buffer.buffer()),
+            index_buffer: Some(&self.index_buffer),
+            index_format: wgpu::IndexFormat::Uint16,
+            num_vertices: 4,
+            num_indices: SQUARE_INDICES.len() as u32,
+            instances,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_shader_compiles() {
+        crate::engine::validate_shader(include_str!("shader.wgsl"));
+    }
+}
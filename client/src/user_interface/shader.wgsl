@@ -1,40 +1,46 @@
+struct Uniforms {
+    delta_time: f32,
+    run_time: f32,
+    width: f32,
+    height: f32
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
 };
 
+// One rectangle in window pixels, top left origin
+struct InstanceInput {
+    @location(1) offset: vec2<f32>,
+    @location(2) scale: vec2<f32>,
+    @location(3) color: vec4<f32>,
+};
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
 };
 
-fn construct_matrix() -> mat3x3<f32> {
-    // TODO: Get from engines uniform buffer
-    let screen_size = vec2(1800.0, 1000.0);
-    // TODO: Pass in via instance buffer
-    let rect_size = vec2(400.0, 200.0);
-    let pos = vec2(100.0, 100.0);
-
-    let scale = 2.0 * (rect_size / screen_size);
-    let translation = vec2(-1.0) + (2.0 * pos / screen_size);
-
-    return mat3x3(
-        vec3(scale.x, 0.0, 0.0), 
-        vec3(0.0, scale.y, 0.0), 
-        vec3(translation, 1.0),
-    );
-}
-
-
 @vertex
 fn vs_main(
     vertex: VertexInput,
+    instance: InstanceInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    let translated = construct_matrix() * vec3(vertex.position, 1.0);
-    out.position = vec4(translated.xy, 0.0, 1.0);
+    let pixel = instance.offset + vertex.position * instance.scale;
+    let ndc = vec2(
+        pixel.x / uniforms.width * 2.0 - 1.0,
+        1.0 - pixel.y / uniforms.height * 2.0,
+    );
+    out.position = vec4(ndc, 0.0, 1.0);
+    out.color = instance.color;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 1.0, 1.0, 0.75);
-}
\ No newline at end of file
+    return in.color;
+}
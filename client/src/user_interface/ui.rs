@@ -0,0 +1,692 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+
+use winit::event::VirtualKeyCode;
+
+use crate::engine::{EventResponse, InputEvent, PointerButton};
+use crate::text::{HAlign, Text, VAlign};
+use crate::types::Instance2;
+
+pub type Color = [f32; 4];
+
+/// Axis aligned rectangle in window pixels, top left origin
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, [x, y]: [f32; 2]) -> bool {
+        (self.x..=self.x + self.width).contains(&x) && (self.y..=self.y + self.height).contains(&y)
+    }
+
+    /// Shrinks the rect by `amount` on every side
+    pub fn shrink(&self, amount: f32) -> Self {
+        Self::new(
+            self.x + amount,
+            self.y + amount,
+            (self.width - 2.0 * amount).max(0.0),
+            (self.height - 2.0 * amount).max(0.0),
+        )
+    }
+
+    fn instance(&self, color: Color) -> Instance2 {
+        Instance2::new([self.x, self.y], [self.width, self.height], color)
+    }
+}
+
+pub struct Style {
+    pub panel: Color,
+    pub title: Color,
+    pub widget: Color,
+    pub hovered: Color,
+    pub active: Color,
+    pub accent: Color,
+    pub text: Color,
+    pub muted_text: Color,
+    pub text_size: f32,
+    pub title_height: f32,
+    pub row_height: f32,
+    pub padding: f32,
+    pub spacing: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            panel: [0.1, 0.1, 0.12, 0.85],
+            title: [0.2, 0.2, 0.26, 0.95],
+            widget: [0.25, 0.25, 0.3, 1.0],
+            hovered: [0.35, 0.35, 0.42, 1.0],
+            active: [0.45, 0.45, 0.55, 1.0],
+            accent: [0.3, 0.6, 0.95, 1.0],
+            text: [0.95, 0.95, 0.95, 1.0],
+            muted_text: [0.6, 0.6, 0.65, 1.0],
+            text_size: 15.0,
+            title_height: 24.0,
+            row_height: 26.0,
+            padding: 8.0,
+            spacing: 6.0,
+        }
+    }
+}
+
+type WidgetId = u64;
+
+const BASE: usize = 0;
+const OVERLAY: usize = 1;
+
+/// Everything drawn at one depth, rectangles go under the text
+#[derive(Default)]
+pub struct Layer {
+    pub rects: Vec<Instance2>,
+    pub texts: Vec<Text>,
+}
+
+/// Where the next widget goes inside the current panel
+struct Layout {
+    id: WidgetId,
+    content: Rect,
+    cursor: f32,
+}
+
+/// Typing for the focused text field, kept in the order it happened
+#[derive(Clone, Copy, Debug)]
+enum Edit {
+    Insert(char),
+    Key(VirtualKeyCode),
+}
+
+/// Immediate mode UI context. Widgets are declared every frame between
+/// `begin_frame` and `end_frame`, which produces what to draw.
+#[derive(Default)]
+pub struct Ui {
+    pub style: Style,
+
+    // Input collected since the last frame
+    pointer: [f32; 2],
+    pointer_down: bool,
+    press_position: [f32; 2],
+    pressed: bool,
+    released: bool,
+    edits: Vec<Edit>,
+
+    // State that lives across frames
+    active: Option<WidgetId>,
+    focused: Option<WidgetId>,
+    open_dropdown: Option<WidgetId>,
+    panels: Vec<Rect>,
+
+    // Built up during the frame
+    layout: Option<Layout>,
+    overlay_rect: Option<Rect>,
+    frame_panels: Vec<Rect>,
+    layers: [Layer; 2],
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an input event, consuming it if it belongs to the UI
+    pub fn handle_input(&mut self, event: &InputEvent) -> EventResponse {
+        let over_ui = |ui: &Self, position| {
+            ui.panels.iter().any(|panel| panel.contains(position))
+                || ui.overlay_rect.is_some_and(|rect| rect.contains(position))
+        };
+
+        let consumed = match *event {
+            InputEvent::PointerMoved { position, .. } => {
+                self.pointer = position;
+                self.active.is_some()
+            }
+            InputEvent::PointerButton {
+                button: PointerButton::Left,
+                pressed,
+                position,
+            } => {
+                self.pointer = position;
+                self.pointer_down = pressed;
+                if pressed {
+                    self.pressed = true;
+                    self.press_position = position;
+                } else {
+                    self.released = true;
+                }
+                over_ui(self, position) || self.active.is_some()
+            }
+            InputEvent::PointerButton { position, .. } | InputEvent::Scroll { position, .. } => {
+                over_ui(self, position)
+            }
+            InputEvent::PointerLeft => {
+                self.pointer = [f32::NEG_INFINITY; 2];
+                false
+            }
+            InputEvent::Text(c) if self.focused.is_some() => {
+                self.edits.push(Edit::Insert(c));
+                true
+            }
+            InputEvent::Key {
+                key, pressed: true, ..
+            } if self.focused.is_some() => {
+                self.edits.push(Edit::Key(key));
+                true
+            }
+            _ => false,
+        };
+
+        if consumed {
+            EventResponse::Consumed
+        } else {
+            EventResponse::Ignored
+        }
+    }
+
+    pub fn begin_frame(&mut self) {
+        for layer in &mut self.layers {
+            layer.rects.clear();
+            layer.texts.clear();
+        }
+        self.frame_panels.clear();
+        self.overlay_rect = None;
+        self.layout = None;
+    }
+
+    /// Finishes the frame, returning the layers to draw back to front
+    pub fn end_frame(&mut self) -> &[Layer] {
+        // Clicking outside of everything drops focus and closes menus
+        if self.pressed && self.active.is_none() {
+            self.focused = None;
+            self.open_dropdown = None;
+        }
+        if !self.pointer_down {
+            self.active = None;
+        }
+
+        self.pressed = false;
+        self.released = false;
+        self.edits.clear();
+        self.panels = std::mem::take(&mut self.frame_panels);
+        if let Some(rect) = self.overlay_rect {
+            self.panels.push(rect);
+        }
+
+        &self.layers
+    }
+
+    /// Whether the pointer is over anything the UI drew last frame
+    pub fn wants_pointer(&self) -> bool {
+        self.active.is_some() || self.panels.iter().any(|panel| panel.contains(self.pointer))
+    }
+
+    /// A titled panel, widgets declared in `contents` are stacked vertically inside it
+    pub fn panel(&mut self, title: &str, rect: Rect, contents: impl FnOnce(&mut Ui)) {
+        let style = &self.style;
+        let title_bar = Rect::new(rect.x, rect.y, rect.width, style.title_height);
+        let content = Rect::new(
+            rect.x + style.padding,
+            rect.y + style.title_height + style.padding,
+            rect.width - 2.0 * style.padding,
+            rect.height - style.title_height - 2.0 * style.padding,
+        );
+        let title_text = Text::new(
+            title,
+            [
+                title_bar.x + style.padding,
+                title_bar.y + title_bar.height / 2.0,
+            ],
+        )
+        .with_size(style.text_size)
+        .with_color(style.text)
+        .with_align(HAlign::Left, VAlign::Middle);
+        self.draw_rect(BASE, rect, self.style.panel);
+        self.draw_rect(BASE, title_bar, self.style.title);
+        self.layers[BASE].texts.push(title_text);
+        self.frame_panels.push(rect);
+
+        let parent = self.layout.replace(Layout {
+            id: hash_id(0, title),
+            content,
+            cursor: content.y,
+        });
+        contents(self);
+        self.layout = parent;
+    }
+
+    /// Reserves the next row of the current panel
+    pub fn allocate(&mut self, height: f32) -> Rect {
+        let spacing = self.style.spacing;
+        let layout = self
+            .layout
+            .as_mut()
+            .expect("Widgets must be declared inside a panel");
+        let rect = Rect::new(
+            layout.content.x,
+            layout.cursor,
+            layout.content.width,
+            height,
+        );
+        layout.cursor += height + spacing;
+        rect
+    }
+
+    fn id(&self, label: &str) -> WidgetId {
+        hash_id(self.layout.as_ref().map_or(0, |layout| layout.id), label)
+    }
+
+    fn hit(&self, rect: &Rect, position: [f32; 2]) -> bool {
+        let blocked = self
+            .overlay_rect
+            .is_some_and(|overlay| overlay.contains(position));
+        !blocked && rect.contains(position)
+    }
+
+    /// Shared click behaviour, returns (hovered, clicked)
+    fn interact(&mut self, id: WidgetId, rect: &Rect) -> (bool, bool) {
+        let hovered = self.hit(rect, self.pointer);
+        // The pointer may have moved on since the press, so test where it went down
+        if self.pressed && self.active.is_none() && self.hit(rect, self.press_position) {
+            self.active = Some(id);
+        }
+        let clicked = self.released && hovered && self.active == Some(id);
+        (hovered, clicked)
+    }
+
+    fn widget_color(&self, id: WidgetId, hovered: bool) -> Color {
+        if self.active == Some(id) {
+            self.style.active
+        } else if hovered {
+            self.style.hovered
+        } else {
+            self.style.widget
+        }
+    }
+
+    fn draw_rect(&mut self, layer: usize, rect: Rect, color: Color) {
+        self.layers[layer].rects.push(rect.instance(color));
+    }
+
+    /// Text vertically centred in `rect`, `h_align` picks the edge or centre it sits against
+    fn draw_text(
+        &mut self,
+        layer: usize,
+        content: &str,
+        rect: Rect,
+        h_align: HAlign,
+        color: Color,
+    ) {
+        let inset = self.style.padding / 2.0;
+        let x = match h_align {
+            HAlign::Left => rect.x + inset,
+            HAlign::Center => rect.x + rect.width / 2.0,
+            HAlign::Right => rect.x + rect.width - inset,
+        };
+        let text = Text::new(content, [x, rect.y + rect.height / 2.0])
+            .with_size(self.style.text_size)
+            .with_color(color)
+            .with_align(h_align, VAlign::Middle);
+        self.layers[layer].texts.push(text);
+    }
+
+    /// A line of text taking up a row of the panel
+    pub fn label(&mut self, content: &str) {
+        let rect = self.allocate(self.style.row_height);
+        self.draw_text(BASE, content, rect, HAlign::Left, self.style.text);
+    }
+
+    pub fn button(&mut self, label: &str) -> bool {
+        let id = self.id(label);
+        let rect = self.allocate(self.style.row_height);
+        let (hovered, clicked) = self.interact(id, &rect);
+        self.draw_rect(BASE, rect, self.widget_color(id, hovered));
+        self.draw_text(BASE, label, rect, HAlign::Center, self.style.text);
+        clicked
+    }
+
+    pub fn checkbox(&mut self, label: &str, checked: &mut bool) -> bool {
+        let id = self.id(label);
+        let row = self.allocate(self.style.row_height);
+        let (hovered, clicked) = self.interact(id, &row);
+        if clicked {
+            *checked = !*checked;
+        }
+
+        let size = row.height;
+        let check_box = Rect::new(row.x, row.y, size, size);
+        self.draw_rect(BASE, check_box, self.widget_color(id, hovered));
+        if *checked {
+            self.draw_rect(BASE, check_box.shrink(size / 4.0), self.style.accent);
+        }
+        let text_rect = Rect::new(row.x + size, row.y, row.width - size, row.height);
+        self.draw_text(BASE, label, text_rect, HAlign::Left, self.style.text);
+        clicked
+    }
+
+    pub fn slider(&mut self, label: &str, value: &mut f32, range: RangeInclusive<f32>) -> bool {
+        let id = self.id(label);
+        let rect = self.allocate(self.style.row_height);
+        let (hovered, _) = self.interact(id, &rect);
+
+        let (min, max) = (*range.start(), *range.end());
+        let mut changed = false;
+        if self.active == Some(id) && self.pointer.iter().all(|x| x.is_finite()) {
+            let t = ((self.pointer[0] - rect.x) / rect.width).clamp(0.0, 1.0);
+            let new_value = min + t * (max - min);
+            changed = new_value != *value;
+            *value = new_value;
+        }
+
+        let t = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let track = Rect::new(rect.x, rect.y + rect.height / 2.0 - 2.0, rect.width, 4.0);
+        let handle_width = 10.0;
+        let handle = Rect::new(
+            rect.x + t * (rect.width - handle_width),
+            rect.y,
+            handle_width,
+            rect.height,
+        );
+        let fill = Rect::new(track.x, track.y, handle.x - rect.x, track.height);
+        self.draw_rect(BASE, track, self.style.widget);
+        self.draw_rect(BASE, fill, self.style.accent);
+        self.draw_rect(BASE, handle, self.widget_color(id, hovered));
+        let text = format!("{}: {:.2}", label, value);
+        self.draw_text(BASE, &text, rect, HAlign::Center, self.style.text);
+        changed
+    }
+
+    /// A button that opens a list of options below it, returns true when the selection changes
+    pub fn dropdown(&mut self, label: &str, selected: &mut usize, options: &[&str]) -> bool {
+        let id = self.id(label);
+        let rect = self.allocate(self.style.row_height);
+        let (hovered, clicked) = self.interact(id, &rect);
+        self.draw_rect(BASE, rect, self.widget_color(id, hovered));
+        let current = options.get(*selected).copied().unwrap_or_default();
+        self.draw_text(BASE, label, rect, HAlign::Left, self.style.muted_text);
+        self.draw_text(BASE, current, rect, HAlign::Right, self.style.text);
+
+        if clicked {
+            self.open_dropdown = match self.open_dropdown {
+                Some(open) if open == id => None,
+                _ => Some(id),
+            };
+            return false;
+        }
+        if self.open_dropdown != Some(id) {
+            return false;
+        }
+
+        let row_height = self.style.row_height;
+        let list = Rect::new(
+            rect.x,
+            rect.y + rect.height,
+            rect.width,
+            row_height * options.len() as f32,
+        );
+        let mut changed = false;
+        for (i, option) in options.iter().enumerate() {
+            let row = Rect::new(
+                list.x,
+                list.y + i as f32 * row_height,
+                list.width,
+                row_height,
+            );
+            let row_hovered = row.contains(self.pointer);
+            if row_hovered && self.released {
+                changed = *selected != i;
+                *selected = i;
+                self.open_dropdown = None;
+            }
+            let color = if i == *selected {
+                self.style.accent
+            } else if row_hovered {
+                self.style.hovered
+            } else {
+                self.style.widget
+            };
+            self.draw_rect(OVERLAY, row, color);
+            self.draw_text(OVERLAY, option, row, HAlign::Left, self.style.text);
+        }
+        // Stops widgets further down the panel from reacting to clicks on the list
+        self.overlay_rect = Some(list);
+        if self.pressed && list.contains(self.press_position) {
+            self.active = Some(id);
+        }
+        changed
+    }
+
+    /// Single line text field, returns true when the text changes
+    pub fn text_input(&mut self, label: &str, text: &mut String) -> bool {
+        let id = self.id(label);
+        let rect = self.allocate(self.style.row_height);
+        let (hovered, clicked) = self.interact(id, &rect);
+        if clicked {
+            self.focused = Some(id);
+        }
+
+        let focused = self.focused == Some(id);
+        let mut changed = false;
+        if focused {
+            for edit in &self.edits {
+                match edit {
+                    Edit::Insert(c) => {
+                        text.push(*c);
+                        changed = true;
+                    }
+                    Edit::Key(VirtualKeyCode::Back) => changed |= text.pop().is_some(),
+                    Edit::Key(VirtualKeyCode::Return | VirtualKeyCode::Tab) => {
+                        // Whatever was typed after leaving the field isn't meant for it
+                        self.focused = None;
+                        break;
+                    }
+                    Edit::Key(_) => (),
+                }
+            }
+        }
+
+        let color = if focused {
+            self.style.active
+        } else {
+            self.widget_color(id, hovered)
+        };
+        self.draw_rect(BASE, rect, color);
+        if focused {
+            // Right aligned while editing so the end of the text sits against the caret
+            let text_rect = Rect::new(rect.x, rect.y, rect.width - 4.0, rect.height);
+            self.draw_text(BASE, text, text_rect, HAlign::Right, self.style.text);
+        } else if text.is_empty() {
+            self.draw_text(BASE, label, rect, HAlign::Left, self.style.muted_text);
+        } else {
+            self.draw_text(BASE, text, rect, HAlign::Left, self.style.text);
+        }
+        if focused {
+            let caret = Rect::new(
+                rect.x + rect.width - 6.0,
+                rect.y + 4.0,
+                2.0,
+                rect.height - 8.0,
+            );
+            self.draw_rect(BASE, caret, self.style.accent);
+        }
+        changed
+    }
+}
+
+fn hash_id(parent: WidgetId, label: &str) -> WidgetId {
+    let mut hasher = DefaultHasher::new();
+    parent.hash(&mut hasher);
+    label.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PANEL: Rect = Rect::new(10.0, 10.0, 200.0, 300.0);
+
+    fn frame(ui: &mut Ui, contents: impl FnOnce(&mut Ui)) {
+        ui.begin_frame();
+        ui.panel("Test", PANEL, contents);
+        ui.end_frame();
+    }
+
+    fn pointer(ui: &mut Ui, position: [f32; 2], pressed: bool) -> EventResponse {
+        ui.handle_input(&InputEvent::PointerMoved {
+            position,
+            delta: [0.0, 0.0],
+        });
+        ui.handle_input(&InputEvent::PointerButton {
+            button: PointerButton::Left,
+            pressed,
+            position,
+        })
+    }
+
+    // First row of the panel starts below the title bar and padding
+    const FIRST_ROW: [f32; 2] = [30.0, 50.0];
+
+    #[test]
+    fn test_button_clicks_on_release() {
+        let mut ui = Ui::new();
+        frame(&mut ui, |ui| assert!(!ui.button("Go")));
+
+        assert_eq!(pointer(&mut ui, FIRST_ROW, true), EventResponse::Consumed);
+        frame(&mut ui, |ui| assert!(!ui.button("Go")));
+        pointer(&mut ui, FIRST_ROW, false);
+        frame(&mut ui, |ui| assert!(ui.button("Go")));
+        frame(&mut ui, |ui| assert!(!ui.button("Go")));
+
+        // Clicks outside the panel pass through to the scene
+        assert_eq!(
+            pointer(&mut ui, [500.0, 500.0], true),
+            EventResponse::Ignored
+        );
+    }
+
+    #[test]
+    fn test_checkbox_and_slider() {
+        let mut ui = Ui::new();
+        let mut checked = false;
+        let mut value = 0.0;
+        frame(&mut ui, |ui| {
+            ui.checkbox("Check", &mut checked);
+        });
+
+        pointer(&mut ui, FIRST_ROW, true);
+        frame(&mut ui, |ui| {
+            ui.checkbox("Check", &mut checked);
+        });
+        pointer(&mut ui, FIRST_ROW, false);
+        frame(&mut ui, |ui| {
+            ui.checkbox("Check", &mut checked);
+        });
+        assert!(checked);
+
+        // Drag the slider in the second row to three quarters of the way across
+        let second_row = [20.0, 80.0];
+        let content = PANEL.shrink(8.0);
+        pointer(&mut ui, second_row, true);
+        ui.handle_input(&InputEvent::PointerMoved {
+            position: [content.x + content.width * 0.75, 500.0],
+            delta: [0.0, 0.0],
+        });
+        frame(&mut ui, |ui| {
+            ui.checkbox("Check", &mut checked);
+            assert!(ui.slider("Slide", &mut value, 0.0..=4.0));
+        });
+        assert!((value - 3.0).abs() < 1e-4);
+        assert!(checked);
+    }
+
+    #[test]
+    fn test_dropdown_selects_option() {
+        let mut ui = Ui::new();
+        let mut selected = 2;
+        let mut clicked_below = false;
+        let options = ["A", "B", "C"];
+        let mut run = |ui: &mut Ui, selected: &mut usize| {
+            frame(ui, |ui| {
+                ui.dropdown("Pick", selected, &options);
+                clicked_below |= ui.button("Below");
+            })
+        };
+
+        run(&mut ui, &mut selected);
+        pointer(&mut ui, FIRST_ROW, true);
+        pointer(&mut ui, FIRST_ROW, false);
+        run(&mut ui, &mut selected);
+
+        // First option opens right below the dropdown, on top of the button
+        let option = [FIRST_ROW[0], FIRST_ROW[1] + 40.0];
+        pointer(&mut ui, option, true);
+        run(&mut ui, &mut selected);
+        pointer(&mut ui, option, false);
+        run(&mut ui, &mut selected);
+
+        assert_eq!(selected, 0);
+        assert!(!clicked_below);
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut ui = Ui::new();
+        let mut text = String::from("z^");
+
+        // Typing without focus goes nowhere
+        assert_eq!(
+            ui.handle_input(&InputEvent::Text('x')),
+            EventResponse::Ignored
+        );
+
+        pointer(&mut ui, FIRST_ROW, true);
+        frame(&mut ui, |ui| {
+            ui.text_input("f(z)", &mut text);
+        });
+        pointer(&mut ui, FIRST_ROW, false);
+        frame(&mut ui, |ui| {
+            ui.text_input("f(z)", &mut text);
+        });
+
+        ui.handle_input(&InputEvent::Text('3'));
+        ui.handle_input(&InputEvent::Text('2'));
+        ui.handle_input(&InputEvent::Key {
+            key: VirtualKeyCode::Back,
+            pressed: true,
+            modifiers: Default::default(),
+        });
+        frame(&mut ui, |ui| {
+            assert!(ui.text_input("f(z)", &mut text));
+        });
+        assert_eq!(text, "z^3");
+
+        // A backspace only removes what was typed before it
+        ui.handle_input(&InputEvent::Key {
+            key: VirtualKeyCode::Back,
+            pressed: true,
+            modifiers: Default::default(),
+        });
+        ui.handle_input(&InputEvent::Text('4'));
+        frame(&mut ui, |ui| {
+            assert!(ui.text_input("f(z)", &mut text));
+        });
+        assert_eq!(text, "z^4");
+    }
+}